@@ -10,9 +10,15 @@ fn main() {
     let target = Vec3::new(5., 5., 0.);
     let center_offset = Vec2::new(0., 5.);
     let turn_ccw = false;
-    let abc = Arc::new_center_mode(start, target, center_offset, turn_ccw);
-    let diff = Arc::check_center_mode(start, target, center_offset);
-    assert_eq!(diff, 0.0);
+    let abc = Arc::new_center_mode(
+        start,
+        target,
+        center_offset,
+        turn_ccw,
+        1,
+        Default::default(),
+    )
+    .unwrap();
 
     //check output include target
     let all: Vec<_> = abc.collect();
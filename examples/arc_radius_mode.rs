@@ -9,9 +9,9 @@ fn main() {
     let start = Vec3::new(0., 0., 0.);
     let target = Vec3::new(5., 5., 0.);
 
-    let radius = 5.0_f32;
+    let radius = 5.0;
     let turn_ccw = false;
-    let abc = Arc::new_radius_mode(start, target, radius, turn_ccw);
+    let abc = Arc::new_radius_mode(start, target, radius, turn_ccw, 1).unwrap();
     // let diff = Arc::check_center_mode(start, target, center_offset);
     // assert_eq!(diff, 0.0);
 
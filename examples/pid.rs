@@ -1,11 +1,11 @@
-use ngc_geometry::PID;
+use ngc_geometry::{Scalar, PID};
 // use poloto::build::bar::gen_bar;
 use rand::distributions::{Distribution, Uniform};
 mod draw;
 use poloto::prelude::*;
 
-fn pid_output(kp: f32, ki: f32, kd: f32) -> Vec<(f64, f64)> {
-    let setpoint: f32 = 100.;
+fn pid_output(kp: Scalar, ki: Scalar, kd: Scalar) -> Vec<(f64, f64)> {
+    let setpoint: Scalar = 100.;
 
     // let kp: f32 = 0.005;
     // let ki: f32 = 0.; // 0.12;
@@ -13,10 +13,10 @@ fn pid_output(kp: f32, ki: f32, kd: f32) -> Vec<(f64, f64)> {
     // let sampletime: f32 = 0.016; //16ms
 
     let mut rng = rand::thread_rng();
-    let range = Uniform::new(0.0_f32, 1.0_f32);
+    let range = Uniform::<Scalar>::new(0.0, 1.0);
 
     //由于setpoit是100，所以system_modal outupt会趋向20
-    let mut system_modal = |a: f32| -> f32 {
+    let mut system_modal = |a: Scalar| -> Scalar {
         let s = range.sample(&mut rng);
 
         5.0 * a + s
@@ -32,9 +32,9 @@ fn pid_output(kp: f32, ki: f32, kd: f32) -> Vec<(f64, f64)> {
     // 记录被控对象输入
     // let mut u: f32;
     // # k=0时刻被控对象输入值
-    let u_0 = 0.0_f32; // random 此时未经过pid控制器，随机选择一个值
-                       //保存被控对象输出
-    let mut real_output = Vec::<f32>::new();
+    let u_0: Scalar = 0.0; // random 此时未经过pid控制器，随机选择一个值
+                           //保存被控对象输出
+    let mut real_output = Vec::<Scalar>::new();
     real_output.push(system_modal(u_0));
 
     let mut p_v = Vec::<(f64, f64)>::new();
@@ -64,9 +64,9 @@ fn pid_output(kp: f32, ki: f32, kd: f32) -> Vec<(f64, f64)> {
 }
 
 fn main() {
-    let kp: f32 = 0.005;
-    let ki: f32 = 0.22;
-    let kd: f32 = 0.0022;
+    let kp: Scalar = 0.005;
+    let ki: Scalar = 0.22;
+    let kd: Scalar = 0.0022;
     let p_v0 = pid_output(kp, ki, 0.0012);
     let p_v1 = pid_output(kp, ki, kd);
     let _p_v2 = pid_output(kp, ki, 0.032);
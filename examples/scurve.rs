@@ -4,7 +4,7 @@ use ngc_geometry::{
         LinearMotionSCurve,
         // SCurve, SCurveConstraints, SCurveStartConditions,
     },
-    Vec3,
+    Scalar, Vec3,
 };
 #[allow(unused_imports)]
 use num_traits::Inv;
@@ -72,16 +72,17 @@ use poloto::prelude::*;
 // }
 mod draw;
 fn main() {
-    let enter_velocity: f32 = 48.0;
-    let end_velocity: f32 = 0.0;
-    let max_acceleration: f32 = 360000.;
-    let max_velocity: f32 = 48.0;
+    let enter_velocity: Scalar = 48.0;
+    let end_velocity: Scalar = 0.0;
+    let max_acceleration: Scalar = 360000.;
+    let max_velocity: Scalar = 48.0;
     let steps: Vec3<i32> = Vec3::new(200, 700, 0);
 
     let mut linear = LinearMotionSCurve::new(
         100.,
         &steps,
         max_acceleration,
+        3.,
         max_velocity,
         enter_velocity,
         end_velocity,
@@ -3,7 +3,7 @@ use ngc_geometry::{
         /* trapezoidal::{Conditions, Trapezoidal},*/
         LinearMotionProfile, LinearMotionTrapezoidal,
     },
-    Vec3,
+    Scalar, Vec3,
 };
 
 // use fmt::Write;
@@ -81,11 +81,11 @@ mod draw;
 // }
 
 fn main() {
-    let enter_velocity: f32 = 0.0;
-    let end_velocity: f32 = 0.0;
-    let target_accel: f32 = 21.5;
+    let enter_velocity: Scalar = 0.0;
+    let end_velocity: Scalar = 0.0;
+    let target_accel: Scalar = 21.5;
 
-    let max_velocity: f32 = 20.0;
+    let max_velocity: Scalar = 20.0;
     // let num_steps: u32 = 100;
 
     let steps: Vec3<i32> = Vec3::new(500, 100, 0);
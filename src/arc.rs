@@ -1,9 +1,10 @@
+use super::vecx::{CanonPlane, Vec2, Vec3};
 ///! circle interpolation
 ///!  satisfy rs274ngc arc requirements
 ///!
-use super::vecx::{Vec2, Vec3};
+use crate::{Real, Scalar};
 
-mod config {
+pub(crate) mod config {
     /// The arc G2/3 g-code standard is problematic by definition. Radius-based arcs have horrible numerical
     /// errors when arc at semi-circles(pi) or full-circles(2*pi). Offset-based arcs are much more accurate
     /// but still have a problem when arcs are full-circles (2*pi). This define accounts for the floating
@@ -13,7 +14,7 @@ mod config {
     /// NOTE: Be very careful when adjusting this value. It should always be greater than 1.2e-7 but not too
     /// much greater than this. The default setting should capture most, if not all, full arc error situations.
     /// note: "num_traits::Float::epsilon()" is `const EPSILON: f32 = 1.19209290e-07_f32;`
-    pub const ARC_ANGULAR_TRAVEL_EPSILON: f32 = 5e-07_f32; // Float (radians)
+    pub const ARC_ANGULAR_TRAVEL_EPSILON: crate::Scalar = 5e-07; // Float (radians)
 
     /// Number of arc generation iterations by small angle approximation before exact arc trajectory
     /// correction with expensive sin() and cos() calcualtions. This parameter maybe decreased if there
@@ -21,42 +22,124 @@ mod config {
     /// bogged down by too many trig calculations.
     pub const ARC_DEFAULT_N_CORRECTION: i32 = 12; // Integer (1-255)
 
-    pub const ARC_DEFAULT_TOLERANCE: f32 = 0.0002_f32; //mm
+    pub const ARC_DEFAULT_TOLERANCE: crate::Scalar = 0.0002; //mm
+
+    /// in radius mode, a half circle may have its chord a little longer than 2*r due to round-off. the chord is
+    /// taken as 2*r when the difference of their squares is in this part of 4*r^2.
+    pub const ARC_RADIUS_MODE_EPSILON: crate::Scalar = 1e-5;
+}
+
+/// runtime arc parameters, the defaults are the values in `config`
+///
+/// example
+/// ```
+/// use ngc_geometry::{ArcSettings, CNCCfgs};
+/// // a large router: coarse chords, but at most 5mm per segment to keep enough blocks for lookahead
+/// let cfg = CNCCfgs {
+///     arc: ArcSettings {
+///         tolerance: 0.002,
+///         max_segment_length: 5.,
+///         ..ArcSettings::default()
+///     },
+///     ..CNCCfgs::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArcSettings {
+    /// mm, the maximum normal distance from a segment to the circle
+    pub tolerance: Scalar,
+    /// number of small angle approximation iterations before an exact sin()/cos() correction
+    pub n_correction: u32,
+    /// radians, an arc with travel less than it is taken as a full circle, see `ARC_ANGULAR_TRAVEL_EPSILON`
+    pub angular_travel_epsilon: Scalar,
+    /// mm, the maximum length of a segment including the linear axis, 0 means no limit
+    pub max_segment_length: Scalar,
+    /// g-code center format arcs are built with [`RadiusTolerance::Spiral`], so a different end radius makes
+    /// a spiral instead of an error. cutter radius compensation still needs circles
+    pub spiral: bool,
+}
+
+impl core::default::Default for ArcSettings {
+    fn default() -> Self {
+        Self {
+            tolerance: config::ARC_DEFAULT_TOLERANCE,
+            n_correction: config::ARC_DEFAULT_N_CORRECTION as u32,
+            angular_travel_epsilon: config::ARC_ANGULAR_TRAVEL_EPSILON,
+            max_segment_length: 0.,
+            spiral: false,
+        }
+    }
+}
+
+/// reasons that an arc can not be built
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArcError {
+    /// radius mode, |2r| is smaller than the distance from current to target
+    RadiusTooSmall,
+    /// center mode, the distances from the center to current and to target differ beyond the limit.
+    /// the value is the difference in mm
+    RadiusMismatch(Scalar),
+    /// radius or center offset is zero
+    ZeroRadius,
+    /// radius mode, current and target are the same point, the circle is not defined
+    FullCircle,
+}
+
+/// the limit of the radius mismatch in center mode
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RadiusTolerance {
+    /// rs274ngc in mm: 0.002 mm
+    #[default]
+    Rs274ngc,
+    /// rs274ngc in inch: 0.0002 inch
+    Rs274ngcInch,
+    /// grbl: more than 0.5 mm, or more than 0.005 mm and 0.1% of radius
+    Grbl,
+    /// any difference, the arc is an archimedean spiral whose radius changes linearly with the angle,
+    /// as LinuxCNC does. the other tolerances keep the start radius and the last segment goes to the target
+    Spiral,
+}
+
+impl RadiusTolerance {
+    /// true if delta_r is beyond the limit
+    pub fn is_exceeded(&self, radius: Scalar, delta_r: Scalar) -> bool {
+        match self {
+            Self::Rs274ngc => delta_r > 0.002,
+            Self::Rs274ngcInch => delta_r > 0.0002 * 25.4,
+            Self::Grbl => delta_r > 0.5 || (delta_r > 0.005 && delta_r > 0.001 * radius),
+            Self::Spiral => false,
+        }
+    }
 }
 
 /// store information needed by when generating next point in arc
 #[allow(dead_code)]
+#[derive(Default)]
 struct CalcInfo {
     // in radius mode, it is None
     //"[Arc definition error] > 0.5mm", "[Arc definition error] > 0.005mm AND 0.1% radius"
     /// ccw angle between vec(from center to current) and vec(from center to target)
-    pub angular_travel: f32,
+    pub angular_travel: Scalar,
     /// how many segments.
     pub segments: u32,
     /// angle of each segment
-    pub theta_per_segment: f32,
+    pub theta_per_segment: Scalar,
     /// value of linear axis
-    pub linear_per_segment: f32,
+    pub linear_per_segment: Scalar,
     /// help to calculate next point in arc
-    pub sin_t: f32,
+    pub sin_t: Scalar,
     /// help to calculate next point in arc
-    pub cos_t: f32,
-    /// store how many sengment has output
-    cnt_segments: u32,
-    /// help to sotre completed
-    completed: bool,
-    /// store temporary sub-level
-    count: i32,
+    pub cos_t: Scalar,
 }
 
 impl CalcInfo {
     pub fn new(
-        angular_travel: f32,
+        angular_travel: Scalar,
         segments: u32,
-        theta_per_segment: f32,
-        linear_per_segment: f32,
-        sin_t: f32,
-        cos_t: f32,
+        theta_per_segment: Scalar,
+        linear_per_segment: Scalar,
+        sin_t: Scalar,
+        cos_t: Scalar,
     ) -> Self {
         Self {
             angular_travel,
@@ -65,10 +148,6 @@ impl CalcInfo {
             linear_per_segment,
             sin_t,
             cos_t,
-
-            cnt_segments: 1,
-            count: 0,
-            completed: false,
         }
     }
 }
@@ -78,19 +157,27 @@ impl CalcInfo {
 /// ARC_TOLERANCE unis is um, "1000um=1mm", radius and position fields is in mm unit
 pub struct Arc {
     /// orig current position
-    orig_c: Vec3<f32>,
+    orig_c: Vec3<Scalar>,
     /// T - Target position
-    t: Vec3<f32>,
+    t: Vec3<Scalar>,
     /// center- circle center postion, the circle that pass through both C and T
-    center: Vec2<f32>,
+    center: Vec2<Scalar>,
     /// r - designated radius, it store in positive
-    r: f32,
+    r: Scalar,
+    /// radius at the target, it differs from r in a spiral
+    r_end: Scalar,
     /// true means counter clockwise direction, otherwise is clockwise direction
     turn_ccw: bool,
+    /// P word of rs274ngc, 1 is a normal arc, each more turn adds a full circle
+    turns: u32,
+    /// the plane that the positions are represented in, outputs are converted to XYZ from it
+    plane: CanonPlane,
+    settings: ArcSettings,
 
-    /// C - last Current position, it will modify when generate next point
-    c: Vec3<f32>,
-    info: Option<CalcInfo>,
+    /// the iteration from C, it is reset by the settings
+    segs: ArcSegments<Scalar>,
+    /// computed at construction, so that next() only does the iteration
+    info: CalcInfo,
 }
 
 impl Arc {
@@ -98,14 +185,17 @@ impl Arc {
     /// current positon and target positon represent with plane and linear
     /// r is radius,
     /// trurn_ccw is true means trun in counter clockwise direction, otherwise means clockwise
+    /// turns is the P word, 1 is a normal arc, each more turn adds a full circle, 0 is taken as 1
     ///
+    /// error if radius is zero, current and target are the same, or radius is smaller than half of the chord
     #[allow(dead_code)]
     pub fn new_radius_mode(
-        current: Vec3<f32>,
-        target: Vec3<f32>,
-        radius: f32,
+        current: Vec3<Scalar>,
+        target: Vec3<Scalar>,
+        radius: Scalar,
         turn_ccw: bool,
-    ) -> Self {
+        turns: u32,
+    ) -> Result<Self, ArcError> {
         // vec from current to target
         let t = target.plane() - current.plane();
         // Calculate the change in position along each selected axis
@@ -157,7 +247,19 @@ impl Arc {
             i = (x + (y * h_x2_div_d))/2
             j = (y - (x * h_x2_div_d))/2
         */
-        let h_x2_div_d = 4.0 * r * r - x * x - y * y;
+        if r == 0. {
+            return Err(ArcError::ZeroRadius);
+        }
+        if x == 0. && y == 0. {
+            return Err(ArcError::FullCircle);
+        }
+        let mut h_x2_div_d = 4.0 * r * r - x * x - y * y;
+        if h_x2_div_d < 0. {
+            if -h_x2_div_d > 4.0 * r * r * config::ARC_RADIUS_MODE_EPSILON {
+                return Err(ArcError::RadiusTooSmall);
+            }
+            h_x2_div_d = 0.;
+        }
         let mut h_x2_div_d = num_traits::Float::sqrt(h_x2_div_d) / num_traits::Float::hypot(x, y);
         // Invert the sign of h_x2_div_d if the circle is clockwise
         if turn_ccw == false {
@@ -176,15 +278,20 @@ impl Arc {
         let o = Vec2::new(o_i, o_j);
         let center = current.plane() + o;
 
-        Self {
+        Ok(Self {
             orig_c: current,
-            c: current,
+            segs: ArcSegments::at(current),
             t: target,
             center,
             r,
+            r_end: r,
             turn_ccw,
-            info: None,
+            turns: turns.max(1),
+            plane: CanonPlane::CanonPlaneXY,
+            settings: ArcSettings::default(),
+            info: CalcInfo::default(),
         }
+        .init())
     }
 
     /// Arc center Mode
@@ -192,13 +299,30 @@ impl Arc {
     /// center_offset is offset from the current position. represent with axis0 and axis 1.  
     /// the center of circle that pass through both C and T.
     /// trurn_ccw is true means trun in counterclockwise direction, otherwise means clockwise
+    /// turns is the P word, 1 is a normal arc, each more turn adds a full circle, 0 is taken as 1
     ///
+    /// error if the center offset is zero, or the radius mismatch is beyond tolerance.
+    /// current and target may be the same, it is a full circle.
+    /// with [`RadiusTolerance::Spiral`] the radius goes from the start radius to the target radius
+    ///
+    /// e.g. a helix of 3 turns, the linear axis goes down 1.5mm in each turn
+    /// ```
+    /// use ngc_geometry::{Arc, Vec2, Vec3};
+    /// let start = Vec3::new(0., 0., 0.);
+    /// let target = Vec3::new(0., 0., -4.5);
+    /// let arc = Arc::new_center_mode(start, target, Vec2::new(5., 0.), true, 3, Default::default()).unwrap();
+    /// let (_, angular_travel) = arc.get_segments_and_angular_travel();
+    /// assert!((angular_travel - 3. * ngc_geometry::consts::TAU).abs() < 1e-5);
+    /// ```
     pub fn new_center_mode(
-        current: Vec3<f32>,
-        target: Vec3<f32>,
-        center_offset: Vec2<f32>,
+        current: Vec3<Scalar>,
+        target: Vec3<Scalar>,
+        center_offset: Vec2<Scalar>,
         turn_ccw: bool,
-    ) -> Self {
+        turns: u32,
+        tolerance: RadiusTolerance,
+    ) -> Result<Self, ArcError> {
+        Self::check_center_mode(current, target, center_offset, tolerance)?;
         let center = current.plane() + center_offset;
         // rt vector from center to target
         // let t = target.plane() - center;
@@ -206,19 +330,337 @@ impl Arc {
         // // let target_r = t.distance();
         // arc radius current to center.
         let r = center_offset.distance();
+        let r_end = match tolerance {
+            RadiusTolerance::Spiral => (target.plane() - center).distance(),
+            _ => r,
+        };
 
-        Self {
+        Ok(Self {
             orig_c: current,
             t: target,
             center,
             r,
+            r_end,
             turn_ccw,
-            c: current,
-            info: None,
+            turns: turns.max(1),
+            plane: CanonPlane::CanonPlaneXY,
+            settings: ArcSettings::default(),
+            segs: ArcSegments::at(current),
+            info: CalcInfo::default(),
         }
+        .init())
+    }
+
+    /// Arc Radius Mode in a plane, current and target are XYZ positions, and the arc outputs XYZ positions.
+    ///
+    /// turn_ccw is the direction viewed from the positive end of the axis perpendicular to the plane, as rs274ngc
+    /// defines G2/G3. e.g. in G18 the plane axes are (Z, X), so a ccw arc goes from +Z to +X, it is cw if it is
+    /// drawn with X to the right and Z up.
+    pub fn new_radius_mode_in_plane(
+        plane: CanonPlane,
+        current: Vec3<Scalar>,
+        target: Vec3<Scalar>,
+        radius: Scalar,
+        turn_ccw: bool,
+        turns: u32,
+    ) -> Result<Self, ArcError> {
+        let xy = CanonPlane::CanonPlaneXY;
+        let mut arc = Self::new_radius_mode(
+            xy.to_plane(&current, &plane),
+            xy.to_plane(&target, &plane),
+            radius,
+            turn_ccw,
+            turns,
+        )?;
+        arc.plane = plane;
+        Ok(arc)
+    }
+
+    /// Arc Center Mode in a plane, current, target and center_offset(I, J, K) are XYZ, and the arc outputs XYZ
+    /// positions. the offset along the axis perpendicular to the plane is ignored.
+    ///
+    /// turn_ccw is the same with [`Arc::new_radius_mode_in_plane`].
+    ///
+    /// ```
+    /// use ngc_geometry::{Arc, CanonPlane, RadiusTolerance, Vec3};
+    /// // G18 G2 X10 Z0 I5 K0 from origin, it is clockwise viewed from +Y, so it goes through Z-5
+    /// let start = Vec3::new(0., 0., 0.);
+    /// let target = Vec3::new(10., 0., 0.);
+    /// let offset = Vec3::new(5., 0., 0.);
+    /// let plane = CanonPlane::CanonPlaneXZ;
+    /// let arc = Arc::new_center_mode_in_plane(plane, start, target, offset, false, 1, RadiusTolerance::Rs274ngc)
+    ///     .unwrap();
+    /// let all: Vec<_> = arc.collect();
+    /// assert_eq!(all.last(), Some(&target));
+    /// assert!(all.iter().all(|p| p.1 == 0. && p.2 <= 0.));
+    /// ```
+    pub fn new_center_mode_in_plane(
+        plane: CanonPlane,
+        current: Vec3<Scalar>,
+        target: Vec3<Scalar>,
+        center_offset: Vec3<Scalar>,
+        turn_ccw: bool,
+        turns: u32,
+        tolerance: RadiusTolerance,
+    ) -> Result<Self, ArcError> {
+        let xy = CanonPlane::CanonPlaneXY;
+        let mut arc = Self::new_center_mode(
+            xy.to_plane(&current, &plane),
+            xy.to_plane(&target, &plane),
+            xy.to_plane(&center_offset, &plane).plane(),
+            turn_ccw,
+            turns,
+            tolerance,
+        )?;
+        arc.plane = plane;
+        Ok(arc)
+    }
+
+    /// use settings instead of the default, the iteration restarts from the start point
+    pub fn with_settings(mut self, settings: ArcSettings) -> Self {
+        self.settings = settings;
+        self.init()
+    }
+
+    /// compute segments and the iteration parameters, and reset the iteration
+    fn init(mut self) -> Self {
+        let (segments, angular_travel) = self.calc_segments_and_angular_travel();
+        let theta_per_segment = angular_travel / (segments as Scalar);
+        let linear_per_segment = (self.t.linear() - self.orig_c.linear()) / (segments as Scalar);
+
+        // /**
+        //  * according taylor :  cosδ= 1 - (δ^2)/2 ;  sinδ= δ - (δ^3)/6
+        //  *  because "Δ=2*cosδ= 2 - (δ^2)", so "sinδ= δ - (δ^3)/6=(δ*(6-δ^2))/6 = (δ*(6-(2-Δ))))/6".
+        //  *
+        //  *  so we use: Δ=2-δ^2 ; cosδ=Δ/2;     sinδ==(δ*(4+Δ))/6
+        //  */
+        let cos_t_2 = 2.0 - theta_per_segment * theta_per_segment; //to simple, this calculate is double of cos_t
+        let sin_t = theta_per_segment * 0.16666667 * (cos_t_2 + 4.0);
+        let cos_t = cos_t_2 * 0.5; //
+
+        self.info = CalcInfo::new(
+            angular_travel,
+            segments,
+            theta_per_segment,
+            linear_per_segment,
+            sin_t,
+            cos_t,
+        );
+        self.segs = self.segments_in();
+        self
     }
 
-    /// according current and center_offset, output diff_r to help checking whether target is valid or not
+    /// the interpolation of the arc in another scalar, it starts at the start point whatever this arc has output
+    ///
+    /// example, the arc in fixed point
+    /// ```
+    /// use ngc_geometry::{Arc, Q16_16, Vec2, Vec3};
+    /// let (start, target) = (Vec3::new(10., 0., 0.), Vec3::new(0., 10., 0.));
+    /// let arc = Arc::new_center_mode(start, target, Vec2::new(-10., 0.), true, 1, Default::default()).unwrap();
+    /// let end = arc.segments_in::<Q16_16>().last().unwrap();
+    /// assert_eq!(end.1, Q16_16::from_int(10));
+    /// ```
+    pub fn segments_in<T: Real>(&self) -> ArcSegments<T> {
+        let from = |v: Scalar| T::from_f64(v.into());
+        let vec3 = |v: Vec3<Scalar>| Vec3::new(from(v.0), from(v.1), from(v.2));
+        ArcSegments {
+            start: vec3(self.orig_c),
+            target: vec3(self.t),
+            center: Vec2::new(from(self.center.0), from(self.center.1)),
+            r: from(self.r),
+            r_end: from(self.r_end),
+            spiral: self.r_end != self.r,
+            plane: self.plane,
+            n_correction: self.settings.n_correction,
+            segments: self.info.segments,
+            angular_travel: from(self.info.angular_travel),
+            linear_per_segment: from(self.info.linear_per_segment),
+            sin_t: from(self.info.sin_t),
+            cos_t: from(self.info.cos_t),
+            c: vec3(self.orig_c),
+            cnt_segments: 1,
+            count: 0,
+            completed: false,
+        }
+    }
+
+    pub fn settings(&self) -> &ArcSettings {
+        &self.settings
+    }
+
+    /// the plane of the arc, outputs of the plane constructors are XYZ positions
+    #[inline]
+    pub fn plane(&self) -> CanonPlane {
+        self.plane
+    }
+
+    /// circle center in plane, it is (axis0, axis1) of [`Arc::plane`]
+    #[inline]
+    pub fn center(&self) -> Vec2<Scalar> {
+        self.center
+    }
+
+    /// true means counter clockwise direction
+    #[inline]
+    pub fn turn_ccw(&self) -> bool {
+        self.turn_ccw
+    }
+
+    /// P word, 1 is a normal arc
+    #[inline]
+    pub fn turns(&self) -> u32 {
+        self.turns
+    }
+
+    /// radius in mm, it is the start radius of a spiral
+    #[inline]
+    pub fn radius(&self) -> Scalar {
+        self.r
+    }
+
+    /// radius at the target in mm, it is [`Arc::radius`] unless the arc is a spiral
+    #[inline]
+    pub fn end_radius(&self) -> Scalar {
+        self.r_end
+    }
+
+    /// true if the radius changes along the arc beyond the tolerance of the settings, see
+    /// [`RadiusTolerance::Spiral`]. a smaller change is round-off of the end point
+    #[inline]
+    pub fn is_spiral(&self) -> bool {
+        num_traits::Float::abs(self.r_end - self.r) > self.settings.tolerance
+    }
+
+    /// radius at the fraction of the path
+    #[inline]
+    fn radius_at(&self, fraction: Scalar) -> Scalar {
+        self.r + (self.r_end - self.r) * fraction
+    }
+
+    /// start position, in XYZ
+    pub fn start(&self) -> Vec3<Scalar> {
+        self.to_xyz(&self.orig_c)
+    }
+
+    /// target position, in XYZ
+    pub fn target(&self) -> Vec3<Scalar> {
+        self.to_xyz(&self.t)
+    }
+
+    /// length of the path in mm, including the linear axis of a helix
+    pub fn length(&self) -> Scalar {
+        let travel = num_traits::Float::abs(self.info.angular_travel);
+        let arc_len = if self.is_spiral() && travel > 0. {
+            // r = r0 + b * a, the length is the integral of sqrt(r^2 + b^2) over the angle:
+            // [r * s + b^2 * ln(r + s)] / 2b with s = hypot(r, b). the difference of r * s is divided by b
+            // in closed form, so it does not cancel when b is small
+            let (r0, r1) = (self.r, self.r_end);
+            let b = (r1 - r0) / travel;
+            let (s0, s1) = (
+                num_traits::Float::hypot(r0, b),
+                num_traits::Float::hypot(r1, b),
+            );
+            let rs = travel * (r0 + r1) * (r0 * r0 + r1 * r1 + b * b) / (2. * (r1 * s1 + r0 * s0));
+            rs + 0.5 * b * num_traits::Float::ln((r1 + s1) / (r0 + s0))
+        } else {
+            travel * self.r
+        };
+        num_traits::Float::hypot(arc_len, self.t.linear() - self.orig_c.linear())
+    }
+
+    /// axis-aligned bounding box (min, max) of the path, in XYZ
+    pub fn bounding_box(&self) -> (Vec3<Scalar>, Vec3<Scalar>) {
+        use crate::consts::{FRAC_PI_2, TAU};
+        let (start, end) = (self.orig_c, self.t);
+        let mut min = Vec3::new(start.0.min(end.0), start.1.min(end.1), start.2.min(end.2));
+        let mut max = Vec3::new(start.0.max(end.0), start.1.max(end.1), start.2.max(end.2));
+        // the extreme points of the circle at 0, pi/2, pi, 3pi/2 are included if the path goes through them.
+        // on a spiral r = r0 + b * swept, the extreme towards an axis angle is where tan(psi) = b / r, psi is
+        // the swept angle after the axis angle. it is on every turn, and the later turns of a growing spiral
+        // are wider
+        let travel = self.info.angular_travel;
+        let abs_travel = num_traits::Float::abs(travel);
+        let a0 = self.start_angle();
+        let b = if abs_travel > 0. {
+            (self.r_end - self.r) / abs_travel
+        } else {
+            0.
+        };
+        let r_at = |swept: Scalar| self.r + b * swept;
+        let turns = num_traits::Float::ceil(abs_travel / TAU) as u32;
+        for i in 0..4 {
+            let a = i as Scalar * FRAC_PI_2;
+            let first = if travel >= 0. { a - a0 } else { a0 - a };
+            let first = first - TAU * num_traits::Float::floor(first / TAU);
+            for k in 0..=turns {
+                let axis = first + k as Scalar * TAU;
+                let mut psi = 0.;
+                for _ in 0..3 {
+                    psi = num_traits::Float::atan(b / r_at(axis + psi).max(Scalar::EPSILON));
+                }
+                for swept in [axis, axis + psi] {
+                    if !(0. ..=abs_travel).contains(&swept) || r_at(swept) <= 0. {
+                        continue;
+                    }
+                    let angle = if travel >= 0. { a0 + swept } else { a0 - swept };
+                    let (sin, cos) = num_traits::Float::sin_cos(angle);
+                    let p = self.center + Vec2::new(cos, sin) * r_at(swept);
+                    min = Vec3::new(min.0.min(p.0), min.1.min(p.1), min.2);
+                    max = Vec3::new(max.0.max(p.0), max.1.max(p.1), max.2);
+                }
+            }
+        }
+        (self.to_xyz(&min), self.to_xyz(&max))
+    }
+
+    /// point at the fraction of the path, 0 is the start and 1 is the target, in XYZ
+    pub fn point_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let a = self.start_angle() + self.info.angular_travel * fraction;
+        let (sin, cos) = num_traits::Float::sin_cos(a);
+        let linear = self.orig_c.linear() + (self.t.linear() - self.orig_c.linear()) * fraction;
+        let r = self.radius_at(fraction);
+        let p = Vec3::new_from_plane(self.center + Vec2::new(r * cos, r * sin), linear);
+        self.to_xyz(&p)
+    }
+
+    /// unit vector of the moving direction at the fraction of the path, in XYZ
+    pub fn tangent_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let travel = self.info.angular_travel;
+        let a = self.start_angle() + travel * fraction;
+        let (sin, cos) = num_traits::Float::sin_cos(a);
+        // derivative of the point by fraction
+        let (r, dr) = (self.radius_at(fraction), self.r_end - self.r);
+        let d = Vec3::new(
+            dr * cos - r * sin * travel,
+            dr * sin + r * cos * travel,
+            self.t.linear() - self.orig_c.linear(),
+        );
+        self.to_xyz(&d.as_unit_vec())
+    }
+
+    /// unit vector of the moving direction at the start, in XYZ
+    pub fn start_tangent(&self) -> Vec3<Scalar> {
+        self.tangent_at(0.)
+    }
+
+    /// unit vector of the moving direction at the target, in XYZ
+    pub fn end_tangent(&self) -> Vec3<Scalar> {
+        self.tangent_at(1.)
+    }
+
+    /// angle of the start point from the center
+    fn start_angle(&self) -> Scalar {
+        let v = self.orig_c.plane() - self.center;
+        num_traits::Float::atan2(v.1, v.0)
+    }
+
+    /// plane coordinates to XYZ
+    #[inline]
+    fn to_xyz(&self, p: &Vec3<Scalar>) -> Vec3<Scalar> {
+        self.plane.to_plane(p, &CanonPlane::CanonPlaneXY)
+    }
+
+    /// according current and center_offset, check whether target is valid or not
     ///
     /// in rs274ngc.pdf "3.5.3.2 Center Format Arc" defined:
     /// It is an error if: when the arc is projected on the selected plane, the distance from the current point to
@@ -227,12 +669,12 @@ impl Arc {
     ///
     /// in grbl defined:
     /// " [Arc definition error] > 0.5mm", and "[Arc definition error] > 0.005mm AND 0.1% radius"
-    #[allow(dead_code)]
     pub fn check_center_mode(
-        current: Vec3<f32>,
-        target: Vec3<f32>,
-        center_offset: Vec2<f32>,
-    ) -> f32 {
+        current: Vec3<Scalar>,
+        target: Vec3<Scalar>,
+        center_offset: Vec2<Scalar>,
+        tolerance: RadiusTolerance,
+    ) -> Result<(), ArcError> {
         let center = current.plane() + center_offset;
         // rt vector from center to target
         let t = target.plane() - center;
@@ -240,20 +682,26 @@ impl Arc {
         let target_r = t.distance();
         // arc radius current to center.
         let r = center_offset.distance();
+        if r == 0. {
+            return Err(ArcError::ZeroRadius);
+        }
 
-        // // Compute difference between current location and target radii for final error-checks.
+        // Compute difference between current location and target radii for final error-checks.
         let delta_r = num_traits::Float::abs(target_r - r);
-
-        delta_r
+        if tolerance.is_exceeded(r, delta_r) {
+            return Err(ArcError::RadiusMismatch(delta_r));
+        }
+        Ok(())
     }
 
     /// ccw angle between current-postion and target-position from circle center
     ///
     /// The included radian is given by ccw rule regardless of the actual rotation direction.
-    /// for example, actual rotation is cw, then the output will be a negative value
-    fn get_radian_travel_by_ccw(&self) -> f32 {
-        // Radius vector from center to current location
-        let r_v = self.c.plane() - self.center;
+    /// for example, actual rotation is cw, then the output will be a negative value.
+    /// each turn more than 1 adds a full circle
+    fn get_radian_travel_by_ccw(&self) -> Scalar {
+        // Radius vector from center to start location
+        let r_v = self.orig_c.plane() - self.center;
         // Radius vector from center to target location
         let rt_v = self.t.plane() - self.center;
 
@@ -261,19 +709,25 @@ impl Arc {
         // float angular_travel = atan2(r_axis0*rt_axis1-r_axis1*rt_axis0, r_axis0*rt_axis0+r_axis1*rt_axis1);
         let mut angular_travel = num_traits::Float::atan2(r_v.wedge(rt_v), r_v.dot(rt_v));
 
-        use core::f32::consts::TAU;
+        use crate::consts::TAU;
         // Correct atan2 output per direction
         if self.turn_ccw == false {
             //如果圆弧顺时针移动，角度应该是负值，如果计算出的角度为正值，需要在计算出的角度基础上减去2*pi（pi为圆周率）
-            if angular_travel >= -config::ARC_ANGULAR_TRAVEL_EPSILON {
+            if angular_travel >= -self.settings.angular_travel_epsilon {
                 angular_travel -= TAU;
             }
         } else {
             //如果圆弧逆时针移动，角度应该是正值，如果计算出的角度为负值，需要在计算出的角度基础上加上2*pi（pi为圆周率）
-            if angular_travel <= config::ARC_ANGULAR_TRAVEL_EPSILON {
+            if angular_travel <= self.settings.angular_travel_epsilon {
                 angular_travel += TAU;
             }
         }
+        let full_turns = (self.turns - 1) as Scalar * TAU;
+        if self.turn_ccw {
+            angular_travel += full_turns;
+        } else {
+            angular_travel -= full_turns;
+        }
 
         angular_travel
     }
@@ -284,8 +738,13 @@ impl Arc {
     /// the arc_tolerance which is defined to be the maximum normal distance from segment
     /// to the circle when the end points both lie on the circle. its unit is um
     ///
-    /// result (segments, angular_travel)
-    pub fn get_segments_and_angular_travel(&self) -> (u32, f32) {
+    /// result (segments, angular_travel), they are computed at construction
+    #[inline]
+    pub fn get_segments_and_angular_travel(&self) -> (u32, Scalar) {
+        (self.info.segments, self.info.angular_travel)
+    }
+
+    fn calc_segments_and_angular_travel(&self) -> (u32, Scalar) {
         let angular_travel = self.get_radian_travel_by_ccw();
         let radius = self.r;
         /*
@@ -313,13 +772,23 @@ impl Arc {
             h = r - arc_tolerance
             k^2 == r^2 - (r - arc_tolerance)^2== arc_tolerance(2*r - arc_tolerance)
             segments = (angular_travel * radius) / (2 * k)
+
+            at last, segments is increased so that no segment is longer than max_segment_length.
         */
-        let k = num_traits::Float::sqrt(
-            config::ARC_DEFAULT_TOLERANCE * (2.0 * radius - config::ARC_DEFAULT_TOLERANCE),
-        );
+        // a spiral is cut as the circle of its larger radius, and its length is about the mean radius
+        let tolerance = self.settings.tolerance.min(radius.max(self.r_end));
+        let k = num_traits::Float::sqrt(tolerance * (2.0 * radius.max(self.r_end) - tolerance));
 
-        let t = num_traits::Float::abs(angular_travel * radius) / k;
-        let t = num_traits::Float::floor(0.5 * t) as u32;
+        let arc_len = num_traits::Float::abs(angular_travel * (radius + self.r_end) * 0.5);
+        let t = arc_len / k;
+        let mut t = num_traits::Float::floor(0.5 * t) as u32;
+
+        let max_len = self.settings.max_segment_length;
+        if max_len > 0. {
+            let linear = self.t.linear() - self.orig_c.linear();
+            let len = num_traits::Float::hypot(arc_len, linear);
+            t = t.max(num_traits::Float::ceil(len / max_len) as u32);
+        }
 
         (t, angular_travel)
     }
@@ -331,100 +800,151 @@ impl Arc {
 /// according cos（α+β）=cosαcosβ-sinαsinβ, cos（α+β）=cosαcosβ-sinαsinβ
 /// we can get iteration formula: next = ( p0*cosδ - p1*sinδ , p1*cosδ + p0*sinδ)
 #[inline]
-fn circle_formular(start: &Vec2<f32>, cos: f32, sin: f32) -> (f32, f32) {
+fn circle_formular<T: Real>(start: &Vec2<T>, cos: T, sin: T) -> (T, T) {
     (start.0 * cos - start.1 * sin, start.1 * cos + start.0 * sin)
 }
 
 impl core::iter::Iterator for Arc {
-    type Item = Vec3<f32>;
+    type Item = Vec3<Scalar>;
 
     /// output internal interpolation, not include start point:
     ///
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (segments, angular_travel) = self.get_segments_and_angular_travel();
-
-        let info = self.info.get_or_insert_with(|| {
-            let theta_per_segment = angular_travel / (segments as f32);
-            let linear_per_segment = (self.t.linear() - self.c.linear()) / (segments as f32);
-
-            // /**
-            //  * according taylor :  cosδ= 1 - (δ^2)/2 ;  sinδ= δ - (δ^3)/6
-            //  *  because "Δ=2*cosδ= 2 - (δ^2)", so "sinδ= δ - (δ^3)/6=(δ*(6-δ^2))/6 = (δ*(6-(2-Δ))))/6".
-            //  *
-            //  *  so we use: Δ=2-δ^2 ; cosδ=Δ/2;     sinδ==(δ*(4+Δ))/6
-            //  */
-            let cos_t_2 = 2.0 - theta_per_segment * theta_per_segment; //to simple, this calculate is double of cos_t
-            let sin_t = theta_per_segment * 0.16666667 * (cos_t_2 + 4.0);
-            let cos_t = cos_t_2 * 0.5; //
-
-            let value = CalcInfo::new(
-                angular_travel,
-                segments,
-                theta_per_segment,
-                linear_per_segment,
-                sin_t,
-                cos_t,
-            );
-            value
-        });
+        let p = self.segs.next_in_plane()?;
+        Some(self.to_xyz(&p))
+    }
+}
 
-        if info.completed == true {
-            return None;
-        } else if info.segments == 0 {
-            // Ensure last segment arrives at target location.
-            info.completed = true;
-            return Some(self.t);
+/// the segment points of an [`Arc`] in `T`, made by [`Arc::segments_in`].
+///
+/// the arc is set up in [`Scalar`], each point is then computed with the arithmetic of `T` only, so a part
+/// without a float unit interpolates in [`crate::Q16_16`]. the points are in XYZ and do not include the
+/// start point, as the iteration of [`Arc`].
+#[derive(Clone, Debug)]
+pub struct ArcSegments<T> {
+    /// start, target and current point in plane coordinates
+    start: Vec3<T>,
+    target: Vec3<T>,
+    c: Vec3<T>,
+    center: Vec2<T>,
+    r: T,
+    r_end: T,
+    spiral: bool,
+    plane: CanonPlane,
+    n_correction: u32,
+    segments: u32,
+    angular_travel: T,
+    linear_per_segment: T,
+    sin_t: T,
+    cos_t: T,
+    /// store how many sengment has output
+    cnt_segments: u32,
+    /// store temporary sub-level
+    count: u32,
+    /// help to sotre completed
+    completed: bool,
+}
+
+impl<T: Real> ArcSegments<T> {
+    /// no segments, only the point
+    fn at(p: Vec3<T>) -> Self {
+        Self {
+            start: p,
+            target: p,
+            c: p,
+            center: p.plane(),
+            r: T::zero(),
+            r_end: T::zero(),
+            spiral: false,
+            plane: CanonPlane::CanonPlaneXY,
+            n_correction: 0,
+            segments: 0,
+            angular_travel: T::zero(),
+            linear_per_segment: T::zero(),
+            sin_t: T::zero(),
+            cos_t: T::one(),
+            cnt_segments: 1,
+            count: 0,
+            completed: false,
         }
+    }
 
-        if info.cnt_segments >= info.segments {
+    /// next point in plane coordinates
+    #[inline]
+    fn next_in_plane(&mut self) -> Option<Vec3<T>> {
+        if self.completed {
+            return None;
+        } else if self.segments == 0 || self.cnt_segments >= self.segments {
             // Ensure last segment arrives at target location.
-            info.completed = true;
-            return Some(self.t);
+            self.completed = true;
+            return Some(self.target);
         }
 
         // Radius vector from center to current location
-        // let (mut r_axis0, mut r_axis1) = (0.0_f32, 0.0_f32);
-        let r_axis0: f32;
-        let r_axis1: f32;
+        let (mut r_axis0, mut r_axis1);
 
-        if info.count < config::ARC_DEFAULT_N_CORRECTION {
+        if self.count < self.n_correction {
             let r_v = self.c.plane() - self.center;
             // use theta_per_segment do iteration to get next point.
-            (r_axis0, r_axis1) = circle_formular(&r_v, info.cos_t, info.sin_t);
-            info.count += 1;
+            (r_axis0, r_axis1) = circle_formular(&r_v, self.cos_t, self.sin_t);
+            self.count += 1;
         } else {
             // Arc correction to radius vector. Computed only every N_ARC_CORRECTION increments. ~375 usec
             // Compute exact location by applying transformation matrix from initial radius vector(=-offset).
-            let delta = info.cnt_segments as f32 * info.theta_per_segment;
-            let cos_ti = num_traits::Float::cos(delta);
-            let sin_ti = num_traits::Float::sin(delta);
+            let i = T::from_f32(self.cnt_segments as f32);
+            let delta = self.angular_travel * (i / T::from_f32(self.segments as f32));
+            let (sin_ti, cos_ti) = delta.sin_cos();
 
-            let r_v = self.orig_c.plane() - self.center;
+            let r_v = self.start.plane() - self.center;
             (r_axis0, r_axis1) = circle_formular(&r_v, cos_ti, sin_ti);
 
-            info.count = 0;
+            self.count = 0;
+        }
+        if self.spiral {
+            // scale the rotated radius vector to the radius of this point
+            let n = T::from_f32(self.segments as f32);
+            let i = T::from_f32(self.cnt_segments as f32);
+            let dr = self.r_end - self.r;
+            let from = if self.count == 0 {
+                self.r
+            } else {
+                self.r + dr * (i - T::one()) / n
+            };
+            let scale = (self.r + dr * i / n) / from;
+            (r_axis0, r_axis1) = (r_axis0 * scale, r_axis1 * scale);
         }
         let t = Vec3::new_from_plane(
             self.center + Vec2::new(r_axis0, r_axis1),
-            self.c.linear() + info.linear_per_segment,
+            self.c.linear() + self.linear_per_segment,
         );
         //update last current postion
         self.c = t;
 
-        info.cnt_segments += 1;
+        self.cnt_segments += 1;
 
         Some(t)
     }
 }
 
+impl<T: Real> core::iter::Iterator for ArcSegments<T> {
+    type Item = Vec3<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let p = self.next_in_plane()?;
+        Some(self.plane.to_plane(&p, &CanonPlane::CanonPlaneXY))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
     use super::{
         config::{ARC_DEFAULT_N_CORRECTION, ARC_DEFAULT_TOLERANCE},
-        Arc, Vec2, Vec3,
+        Arc, ArcError, ArcSettings, RadiusTolerance, Vec2, Vec3,
     };
+    use crate::Scalar;
     // use rand::Rng;
     #[test]
     fn test_center_mode() {
@@ -433,10 +953,10 @@ mod tests {
         let center_offset = Vec2::new(0.0, 4.0);
         // let radius = 4.0_f32;
         let turn_ccw = true;
-        let arc = Arc::new_center_mode(start, target, center_offset, turn_ccw);
+        let tolerance = RadiusTolerance::Rs274ngc;
+        let arc =
+            Arc::new_center_mode(start, target, center_offset, turn_ccw, 1, tolerance).unwrap();
         assert_eq!(arc.r, 4.0);
-        let diff = Arc::check_center_mode(start, target, center_offset);
-        assert_eq!(diff, 0.0);
 
         let _center = arc.center.clone();
 
@@ -468,19 +988,425 @@ mod tests {
     fn test_radius_mode() {
         let start = Vec3::new(0., 0., 0.);
         let target = Vec3::new(4., 4., 0.);
-        let radius = 4.0_f32;
+        let radius = 4.0;
         let turn_ccw = true;
-        let arc = Arc::new_radius_mode(start, target, radius, turn_ccw);
+        let arc = Arc::new_radius_mode(start, target, radius, turn_ccw, 1).unwrap();
         assert_eq!(arc.center, Vec2::new(0.0, 4.0));
 
         let start = Vec3::new(1., 1., 0.);
         let target = Vec3::new(5., 5., 0.);
-        let radius = 4.0_f32;
+        let radius = 4.0;
         let turn_ccw = false;
-        let arc = Arc::new_radius_mode(start, target, radius, turn_ccw);
+        let arc = Arc::new_radius_mode(start, target, radius, turn_ccw, 1).unwrap();
         assert_eq!(arc.center, Vec2::new(5.0, 1.0));
     }
-    // #[test]
-    // fn test_line3d_example_2() {
-    // }
+    #[test]
+    fn test_helix_turns() {
+        use crate::consts::TAU;
+        let start = Vec3::new(5., 0., 0.);
+        let target = Vec3::new(0., 5., -10.);
+        let offset = Vec2::new(-5., 0.);
+        let quarter =
+            Arc::new_center_mode(start, target, offset, false, 1, Default::default()).unwrap();
+        let (segments, travel) = quarter.get_segments_and_angular_travel();
+        assert!((travel + 0.75 * TAU).abs() < 1e-5);
+
+        let helix = Arc::new_radius_mode(start, target, -5., false, 3).unwrap();
+        let (helix_segments, helix_travel) = helix.get_segments_and_angular_travel();
+        assert!((helix_travel + 2.75 * TAU).abs() < 1e-4);
+        assert!(helix_segments > segments * 3);
+
+        // the linear axis goes down evenly over all turns
+        let all: Vec<_> = helix.collect();
+        assert_eq!(all.len() as u32, helix_segments);
+        let step = -10. / helix_segments as Scalar;
+        for (i, p) in all.iter().enumerate() {
+            assert!((p.2 - step * (i + 1) as Scalar).abs() < 1e-3);
+            assert!(((p.plane() - Vec2::new(0., 0.)).distance() - 5.).abs() < 1e-3);
+        }
+        assert_eq!(all.last(), Some(&target));
+    }
+
+    #[test]
+    fn test_arc_in_plane() {
+        use super::CanonPlane;
+        // G19 G3 from Y5 to Z5 around the origin, ccw viewed from +X goes from +Y to +Z
+        let start = Vec3::new(1., 5., 0.);
+        let target = Vec3::new(1., 0., 5.);
+        let offset = Vec3::new(0., -5., 0.);
+        let plane = CanonPlane::CanonPlaneYZ;
+        let tolerance = RadiusTolerance::Rs274ngc;
+        let arc = Arc::new_center_mode_in_plane(plane, start, target, offset, true, 1, tolerance)
+            .unwrap();
+        assert_eq!(arc.plane(), plane);
+        assert_eq!(arc.center(), Vec2::new(0., 0.));
+        let (_, travel) = arc.get_segments_and_angular_travel();
+        assert!((travel - crate::consts::FRAC_PI_2).abs() < 1e-5);
+        let all: Vec<_> = arc.collect();
+        assert_eq!(all.last(), Some(&target));
+        for p in all.iter() {
+            assert_eq!(p.0, 1.);
+            assert!(p.1 >= 0. && p.2 >= 0.);
+        }
+
+        // the same arc in radius mode
+        let arc = Arc::new_radius_mode_in_plane(plane, start, target, 5., true, 1).unwrap();
+        assert!((arc.center() - Vec2::new(0., 0.)).distance() < 1e-5);
+    }
+
+    #[test]
+    fn test_arc_errors() {
+        let start = Vec3::new(0., 0., 0.);
+        let target = Vec3::new(10., 0., 0.);
+        assert_eq!(
+            Arc::new_radius_mode(start, target, 4.9, true, 1).err(),
+            Some(ArcError::RadiusTooSmall)
+        );
+        assert_eq!(
+            Arc::new_radius_mode(start, target, 0., true, 1).err(),
+            Some(ArcError::ZeroRadius)
+        );
+        assert_eq!(
+            Arc::new_radius_mode(start, start, 5., true, 1).err(),
+            Some(ArcError::FullCircle)
+        );
+        // a half circle with round-off is accepted
+        let arc = Arc::new_radius_mode(start, target, 4.99999, true, 1).unwrap();
+        assert!((arc.center - Vec2::new(5., 0.)).distance() < 1e-4);
+
+        // center mode full circle is valid
+        let offset = Vec2::new(5., 0.);
+        assert!(Arc::new_center_mode(start, start, offset, true, 1, Default::default()).is_ok());
+        assert_eq!(
+            Arc::new_center_mode(
+                start,
+                target,
+                Vec2::new(0., 0.),
+                true,
+                1,
+                Default::default()
+            )
+            .err(),
+            Some(ArcError::ZeroRadius)
+        );
+
+        // target radius is 5.004, mismatch is 0.004 mm
+        let target = Vec3::new(10.004, 0., 0.);
+        let check = |tolerance| Arc::check_center_mode(start, target, offset, tolerance);
+        assert!(matches!(
+            check(RadiusTolerance::Rs274ngc),
+            Err(ArcError::RadiusMismatch(_))
+        ));
+        assert_eq!(check(RadiusTolerance::Rs274ngcInch), Ok(()));
+        assert_eq!(check(RadiusTolerance::Grbl), Ok(()));
+        // 0.1% of 5mm is 0.005mm
+        let target = Vec3::new(10.006, 0., 0.);
+        let check = |tolerance| Arc::check_center_mode(start, target, offset, tolerance);
+        assert!(check(RadiusTolerance::Grbl).is_err());
+    }
+
+    #[test]
+    fn test_settings() {
+        use super::ArcSettings;
+        let start = Vec3::new(100., 0., 0.);
+        let target = Vec3::new(-100., 0., 0.);
+        let offset = Vec2::new(-100., 0.);
+        let arc =
+            || Arc::new_center_mode(start, target, offset, true, 1, Default::default()).unwrap();
+        let (fine, _) = arc().get_segments_and_angular_travel();
+
+        let coarse = ArcSettings {
+            tolerance: 0.1,
+            ..ArcSettings::default()
+        };
+        let (segments, _) = arc()
+            .with_settings(coarse)
+            .get_segments_and_angular_travel();
+        assert!(segments < fine / 10);
+        assert_eq!(segments, 35);
+
+        // half circle is 314mm, at most 5mm per segment
+        let capped = ArcSettings {
+            max_segment_length: 5.,
+            ..coarse
+        };
+        let all: Vec<_> = arc().with_settings(capped).collect();
+        assert_eq!(all.len(), 63);
+        let mut last = start;
+        for p in all {
+            assert!((p - last).distance() <= 5.);
+            last = p;
+        }
+    }
+
+    #[test]
+    fn test_cached_segments() {
+        let start = Vec3::new(10., 0., 0.);
+        let target = Vec3::new(0., 10., 3.);
+        let offset = Vec2::new(-10., 0.);
+        let mut arc =
+            Arc::new_center_mode(start, target, offset, true, 2, Default::default()).unwrap();
+        let (segments, travel) = arc.get_segments_and_angular_travel();
+        let mut n = 0;
+        while arc.next().is_some() {
+            n += 1;
+            assert_eq!(arc.get_segments_and_angular_travel(), (segments, travel));
+        }
+        assert_eq!(n, segments);
+    }
+
+    #[test]
+    fn test_queries() {
+        use super::CanonPlane;
+        use crate::consts::PI;
+        let near = |a: Vec3<Scalar>, b: Vec3<Scalar>| (a - b).distance() < 1e-4;
+
+        // G17 G2 quarter circle from (0,5) to (5,0) around the origin, going down 2mm
+        let arc = Arc::new_radius_mode(Vec3::new(0., 5., 2.), Vec3::new(5., 0., 0.), 5., false, 1)
+            .unwrap();
+        assert_eq!(arc.radius(), 5.);
+        let quarter = 2.5 * PI;
+        assert!((arc.length() - num_traits::Float::hypot(quarter, 2.)).abs() < 1e-4);
+        assert!(near(arc.point_at(0.5), Vec3::new(3.535534, 3.535534, 1.)));
+        let d = arc.start_tangent();
+        assert!(near(d, Vec3::new(quarter, 0., -2.).as_unit_vec()));
+        assert!(near(
+            arc.end_tangent(),
+            Vec3::new(0., -quarter, -2.).as_unit_vec()
+        ));
+        let (min, max) = arc.bounding_box();
+        assert!(near(min, Vec3::new(0., 0., 0.)));
+        assert!(near(max, Vec3::new(5., 5., 2.)));
+
+        // G18 G3 from X5 to Z-5 around the origin, ccw viewed from +Y goes from +X to -Z directly
+        let plane = CanonPlane::CanonPlaneXZ;
+        let (start, target) = (Vec3::new(5., 0., 0.), Vec3::new(0., 0., -5.));
+        let arc = Arc::new_radius_mode_in_plane(plane, start, target, 5., true, 1).unwrap();
+        assert!(near(arc.start_tangent(), Vec3::new(0., 0., -1.)));
+        let (min, max) = arc.bounding_box();
+        assert!(near(min, Vec3::new(0., 0., -5.)));
+        assert!(near(max, Vec3::new(5., 0., 0.)));
+
+        // G18 G2 three quarters, it goes through Z+5 and X-5
+        let arc = Arc::new_radius_mode_in_plane(plane, start, target, -5., false, 1).unwrap();
+        assert!(near(arc.start(), start));
+        assert!(near(arc.target(), target));
+        assert!(near(arc.point_at(1. / 3.), Vec3::new(0., 0., 5.)));
+        assert!(near(arc.start_tangent(), Vec3::new(0., 0., 1.)));
+        let (min, max) = arc.bounding_box();
+        assert!(near(min, Vec3::new(-5., 0., -5.)));
+        assert!(near(max, Vec3::new(5., 0., 5.)));
+    }
+
+    #[test]
+    fn test_segments_fixed_point() {
+        use super::CanonPlane;
+        use crate::{Real, Q16_16};
+        // the fixed point segments follow the scalar ones within a few resolutions of Q16.16
+        let assert_same = |arc: Arc| {
+            let fixed: Vec<_> = arc.segments_in::<Q16_16>().collect();
+            let scalar: Vec<_> = arc.collect();
+            assert_eq!(fixed.len(), scalar.len());
+            for (q, p) in fixed.iter().zip(scalar.iter()) {
+                let to = |v: Q16_16| Scalar::from_f32(v.to_f32());
+                let q = Vec3::new(to(q.0), to(q.1), to(q.2));
+                assert!((q - *p).distance() < 3e-3, "{:?} {:?}", q, p);
+            }
+        };
+        let start = Vec3::new(10., 0., 0.);
+        assert_same(Arc::new_radius_mode(start, Vec3::new(0., 10., 0.), 10., true, 1).unwrap());
+        // a helix of 3 turns in the YZ plane
+        let offset = Vec3::new(0., 5., 0.);
+        let (plane, tolerance) = (CanonPlane::CanonPlaneYZ, RadiusTolerance::Rs274ngc);
+        let target = Vec3::new(-4.5, 0., 0.);
+        assert_same(
+            Arc::new_center_mode_in_plane(plane, Vec3::zero(), target, offset, false, 3, tolerance)
+                .unwrap(),
+        );
+        // a spiral from radius 10 to 5
+        let spiral = RadiusTolerance::Spiral;
+        let offset = Vec2::new(-10., 0.);
+        assert_same(
+            Arc::new_center_mode(start, Vec3::new(0., 5., 0.), offset, true, 1, spiral).unwrap(),
+        );
+    }
+
+    #[test]
+    fn test_spiral() {
+        use crate::consts::{FRAC_PI_2, TAU};
+        // quarter turn from radius 10 to 5 around the origin
+        let (start, target) = (Vec3::new(10., 0., 0.), Vec3::new(0., 5., 0.));
+        let offset = Vec2::new(-10., 0.);
+        let err = Arc::new_center_mode(start, target, offset, true, 1, RadiusTolerance::Grbl);
+        assert!(matches!(err, Err(ArcError::RadiusMismatch(_))));
+        let arc =
+            Arc::new_center_mode(start, target, offset, true, 1, RadiusTolerance::Spiral).unwrap();
+        assert!(arc.is_spiral());
+        assert_eq!((arc.radius(), arc.end_radius()), (10., 5.));
+
+        let mut last = start;
+        let mut polyline = 0.;
+        let all: Vec<_> =
+            Arc::new_center_mode(start, target, offset, true, 1, RadiusTolerance::Spiral)
+                .unwrap()
+                .collect();
+        for p in all.iter() {
+            let a = num_traits::Float::atan2(p.1, p.0);
+            let r = 10. - 5. * a / FRAC_PI_2;
+            assert!((p.distance() - r).abs() < 1e-3, "{:?}", p);
+            polyline += (*p - last).distance();
+            last = *p;
+        }
+        assert_eq!(all.last(), Some(&target));
+        assert!((arc.length() - polyline).abs() < 1e-3);
+        // the radius shrinks, so Y is the most before the end, at about 5.74
+        assert_box(&arc);
+        assert!(arc.bounding_box().1 .1 > 5.7);
+        // moving inwards at the end
+        let d = arc.end_tangent();
+        let expected = Vec3::new(-5. * FRAC_PI_2, -5., 0.).as_unit_vec();
+        assert!((d - expected).distance() < 1e-5);
+
+        // two turns from radius 10 to 5, it passes (-7.5, 0) in the middle
+        let target = Vec3::new(5., 0., -2.);
+        let arc =
+            Arc::new_center_mode(start, target, offset, true, 2, RadiusTolerance::Spiral).unwrap();
+        let (_, travel) = arc.get_segments_and_angular_travel();
+        assert!((travel - 2. * TAU).abs() < 1e-5);
+        assert!((arc.point_at(0.25) - Vec3::new(-8.75, 0., -0.5)).distance() < 1e-4);
+        let all: Vec<_> = arc.collect();
+        assert_eq!(all.last(), Some(&target));
+        assert!(all.iter().all(|p| p.plane().distance() <= 10. + 1e-4));
+
+        // a G2 of two turns growing from radius 5 to 10, the second turn is the widest
+        let (start, target) = (Vec3::new(5., 0., 0.), Vec3::new(10., 0., 0.));
+        let arc = Arc::new_center_mode(
+            start,
+            target,
+            Vec2::new(-5., 0.),
+            false,
+            2,
+            RadiusTolerance::Spiral,
+        )
+        .unwrap();
+        assert_box(&arc);
+        let (min, max) = arc.bounding_box();
+        assert!(
+            (min - Vec3::new(-8.76, -8.13, 0.)).distance() < 0.01,
+            "{:?}",
+            min
+        );
+        assert!(
+            (max - Vec3::new(10., 9.38, 0.)).distance() < 0.01,
+            "{:?}",
+            max
+        );
+
+        // an end point off by round-off is a circle, the length is not of a spiral
+        let (start, target) = (Vec3::new(10., 0., 0.), Vec3::new(-10. - 1e-6, 0., 0.));
+        let arc = Arc::new_center_mode(
+            start,
+            target,
+            Vec2::new(-10., 0.),
+            true,
+            1,
+            RadiusTolerance::Spiral,
+        )
+        .unwrap();
+        assert!(!arc.is_spiral());
+        assert!((arc.length() - 10. * crate::consts::PI).abs() < 1e-4);
+    }
+
+    /// the bounding box holds the path, and it is tight to 1e-3
+    fn assert_box(arc: &Arc) {
+        let (min, max) = arc.bounding_box();
+        let (mut lo, mut hi) = (arc.start(), arc.start());
+        for i in 0..=20000 {
+            let p = arc.point_at(i as Scalar / 20000.);
+            assert!(
+                p.clamp(
+                    min - Vec3::new(1e-4, 1e-4, 1e-4),
+                    max + Vec3::new(1e-4, 1e-4, 1e-4)
+                ) == p
+            );
+            (lo, hi) = (lo.min(p), hi.max(p));
+        }
+        assert!(
+            (lo - min).distance() < 1e-3 && (hi - max).distance() < 1e-3,
+            "{:?} {:?}",
+            (lo, hi),
+            (min, max)
+        );
+    }
+
+    /// a half circle across the 3m travel of a gantry, the tolerance is a step. f32 rounds off to 2 steps of
+    /// 1000 steps/mm at 3000mm, f64 keeps within a step of 100000 steps/mm
+    #[test]
+    fn test_full_travel() {
+        #[cfg(not(feature = "f64"))]
+        let (step, limit) = (1e-3, 2e-3);
+        #[cfg(feature = "f64")]
+        let (step, limit) = (1e-5, 1e-5);
+        let (start, target) = (Vec3::new(3000., 1500., -10.), Vec3::new(0., 1500., -10.));
+        let offset = Vec2::new(-1500., 0.);
+        let settings = ArcSettings {
+            tolerance: step,
+            ..ArcSettings::default()
+        };
+        let arc = Arc::new_center_mode(start, target, offset, true, 1, RadiusTolerance::Rs274ngc)
+            .unwrap()
+            .with_settings(settings);
+        let center = Vec2::new(1500., 1500.);
+        let mut max_error: Scalar = 0.;
+        let mut last = start;
+        for p in arc {
+            let r = (p.plane() - center).distance();
+            // the chord middle is inside by the tolerance
+            let mid = (last.plane() + p.plane() - center - center).distance() * 0.5;
+            max_error = max_error.max((r - 1500.).abs()).max(1500. - mid - step);
+            assert_eq!(p.2, -10.);
+            last = p;
+        }
+        assert_eq!(last, target);
+        assert!(max_error < limit, "{}", max_error);
+    }
+
+    extern crate test;
+
+    /// one iteration is one segment, the arc restarts when it ends
+    #[bench]
+    fn bench_arc_segment(b: &mut test::Bencher) {
+        let new = || {
+            let start = Vec3::new(50., 0., 0.);
+            let target = Vec3::new(50., 0., -20.);
+            let offset = Vec2::new(-50., 0.);
+            Arc::new_center_mode(start, target, offset, true, 10, Default::default()).unwrap()
+        };
+        let mut arc = new();
+        b.iter(|| match arc.next() {
+            Some(p) => p,
+            None => {
+                arc = new();
+                arc.next().unwrap()
+            }
+        });
+    }
+
+    #[bench]
+    fn bench_arc_construction(b: &mut test::Bencher) {
+        let start = Vec3::new(50., 0., 0.);
+        let target = Vec3::new(0., 50., 0.);
+        let offset = Vec2::new(-50., 0.);
+        b.iter(|| {
+            let arc = Arc::new_center_mode(
+                test::black_box(start),
+                target,
+                offset,
+                true,
+                1,
+                Default::default(),
+            );
+            arc.unwrap().get_segments_and_angular_travel()
+        });
+    }
 }
@@ -0,0 +1,329 @@
+//! arc fitting, replace runs of short lines by arcs or longer lines, e.g. the G1 streams of CAM posts and DXF imports.
+//!
+//! a run grows while all its points are within the tolerance of one line, or of one arc in XY plane whose linear
+//! axis moves evenly with the angle (helix). a sharp corner or a change of data always ends the run.
+use crate::vecx::{Vec2, Vec3};
+use crate::{Scalar, Segment};
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use num_traits::Float;
+
+/// the most points of a run, it bounds the fitting cost of each point
+const ARC_FIT_MAX_RUN: usize = 256;
+
+/// arc fitting parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ArcFitSettings {
+    /// mm, the maximum distance from the input points and lines to the output segment
+    pub tolerance: Scalar,
+    /// radians, a direction change larger than it between two lines is a corner, it is never smoothed
+    pub corner_angle: Scalar,
+    /// an arc replaces at least so many lines, shorter runs are output as they are
+    pub min_arc_lines: usize,
+}
+
+impl core::default::Default for ArcFitSettings {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.01,
+            corner_angle: (20. as Scalar).to_radians(),
+            min_arc_lines: 3,
+        }
+    }
+}
+
+/// fit a stream of points with data, output lines and arcs with the data.
+///
+/// the output segments are decided when the next point does not fit, so they lag behind the input.
+/// an arc segment is the G2/G3 parameters, [`crate::Arc::new_center_mode`] builds it by `center - start`.
+///
+/// example
+/// ```
+/// use ngc_geometry::{ArcFitSettings, ArcFitter, Scalar, Segment, Vec3};
+/// let mut fitter = ArcFitter::new(ArcFitSettings::default(), Vec3::new(10., 0., 0.));
+/// let mut out = Vec::new();
+/// for i in 1..=30 {
+///     let a = i as Scalar / 30. * ngc_geometry::consts::FRAC_PI_2;
+///     out.extend(fitter.push(Vec3::new(10. * a.cos(), 10. * a.sin(), 0.), ()));
+/// }
+/// out.extend(fitter.finish());
+/// assert_eq!(out.len(), 1);
+/// assert!(matches!(out[0].0, Segment::Arc { turn_ccw: true, .. }));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ArcFitter<D: Copy + PartialEq> {
+    settings: ArcFitSettings,
+    /// start of the run, it is the end of the last output segment
+    start: Vec3<Scalar>,
+    /// points of the run after start
+    run: Vec<Vec3<Scalar>>,
+    data: Option<D>,
+    /// the segment of the whole run
+    fit: Option<Segment>,
+}
+
+impl<D: Copy + PartialEq> ArcFitter<D> {
+    /// start is the current position
+    pub fn new(settings: ArcFitSettings, start: Vec3<Scalar>) -> Self {
+        Self {
+            settings,
+            start,
+            run: Vec::new(),
+            data: None,
+            fit: None,
+        }
+    }
+
+    pub fn settings(&self) -> &ArcFitSettings {
+        &self.settings
+    }
+
+    /// add the next point, output the segments that are decided
+    pub fn push(&mut self, p: Vec3<Scalar>, data: D) -> Vec<(Segment, D)> {
+        let mut out = Vec::new();
+        let last = self.run.last().copied().unwrap_or(self.start);
+        if (p - last).distance_sqr() == 0. {
+            return out;
+        }
+        if !self.run.is_empty()
+            && (self.data != Some(data) || self.is_corner(p) || self.run.len() >= ARC_FIT_MAX_RUN)
+        {
+            self.flush(&mut out);
+        }
+        self.data = Some(data);
+        self.run.push(p);
+        match self.fit_run() {
+            Some(seg) => self.fit = Some(seg),
+            None => {
+                // p starts the next run
+                self.run.pop();
+                self.flush(&mut out);
+                self.run.push(p);
+                self.fit = self.fit_run();
+            }
+        }
+        out
+    }
+
+    /// output the rest segments
+    pub fn finish(mut self) -> Vec<(Segment, D)> {
+        let mut out = Vec::new();
+        self.flush(&mut out);
+        out
+    }
+
+    /// true if the direction changes too much at the last point
+    fn is_corner(&self, p: Vec3<Scalar>) -> bool {
+        let n = self.run.len();
+        let prev = if n >= 2 { self.run[n - 2] } else { self.start };
+        let last = self.run[n - 1];
+        let (d1, d2) = ((last - prev).as_unit_vec(), (p - last).as_unit_vec());
+        d1.dot(d2) < self.settings.corner_angle.cos()
+    }
+
+    /// output the run, as its fit if it is a line or a long enough arc, otherwise as it is
+    fn flush(&mut self, out: &mut Vec<(Segment, D)>) {
+        let data = match self.data {
+            Some(d) if !self.run.is_empty() => d,
+            _ => return,
+        };
+        match self.fit.take() {
+            Some(seg @ Segment::Line { .. }) => out.push((seg, data)),
+            Some(seg @ Segment::Arc { .. }) if self.run.len() >= self.settings.min_arc_lines => {
+                out.push((seg, data))
+            }
+            _ => {
+                let mut start = self.start;
+                for end in self.run.iter() {
+                    out.push((Segment::Line { start, end: *end }, data));
+                    start = *end;
+                }
+            }
+        }
+        self.start = self.run[self.run.len() - 1];
+        self.run.clear();
+    }
+
+    /// one line or arc through start and the run within the tolerance
+    fn fit_run(&self) -> Option<Segment> {
+        let start = self.start;
+        let end = *self.run.last()?;
+        let tolerance = self.settings.tolerance;
+
+        // line: every point is near the line and moves forward
+        let d = end - start;
+        let len_sqr = d.distance_sqr();
+        let mut last_t = 0.;
+        let is_line = self.run.iter().all(|p| {
+            let v = *p - start;
+            let t = v.dot(d) / len_sqr;
+            let off = v - d * t;
+            let forward = t > last_t;
+            last_t = t;
+            forward && off.distance() <= tolerance
+        });
+        if is_line {
+            return Some(Segment::Line { start, end });
+        }
+        if self.run.len() < 2 {
+            return None;
+        }
+
+        // arc: the circle through start, the middle point and end in XY plane
+        let mid = self.run[(self.run.len() - 1) / 2];
+        let center = circumcenter(start.plane(), mid.plane(), end.plane())?;
+        let turn_ccw = (mid.plane() - start.plane()).wedge(end.plane() - mid.plane()) > 0.;
+        let r = (start.plane() - center).distance();
+        let mut angles = Vec::with_capacity(self.run.len());
+        let mut travel = 0.;
+        let mut last = start;
+        for p in self.run.iter() {
+            let (a, b) = (last.plane() - center, p.plane() - center);
+            let mut da = a.wedge(b).atan2(a.dot(b));
+            if !turn_ccw {
+                da = -da;
+            }
+            // the chord is within the tolerance if its sagitta is
+            let chord = (p.plane() - last.plane()).distance();
+            let sagitta = r - (r * r - 0.25 * chord * chord).max(0.).sqrt();
+            if da <= 0. || sagitta > tolerance || ((b.distance() - r).abs() > tolerance) {
+                return None;
+            }
+            travel += da;
+            angles.push(travel);
+            last = *p;
+        }
+        if travel >= crate::consts::TAU {
+            return None;
+        }
+        // helix: the linear axis moves evenly with the angle
+        let dz = end.2 - start.2;
+        let even = self
+            .run
+            .iter()
+            .zip(angles.iter())
+            .all(|(p, a)| (p.2 - (start.2 + dz * a / travel)).abs() <= tolerance);
+        even.then_some(Segment::Arc {
+            start,
+            end,
+            center,
+            turn_ccw,
+        })
+    }
+}
+
+/// center of the circle through three points, None if they are on a line
+fn circumcenter(a: Vec2<Scalar>, b: Vec2<Scalar>, c: Vec2<Scalar>) -> Option<Vec2<Scalar>> {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2. * ab.wedge(ac);
+    if d.abs() <= Scalar::EPSILON * ab.distance_sqr().max(ac.distance_sqr()) {
+        return None;
+    }
+    let (ab2, ac2) = (ab.distance_sqr(), ac.distance_sqr());
+    Some(a + Vec2::new((ac.1 * ab2 - ab.1 * ac2) / d, (ab.0 * ac2 - ac.0 * ab2) / d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArcFitSettings, ArcFitter};
+    use crate::consts::PI;
+    use crate::Scalar;
+    use crate::{Arc, RadiusTolerance, Segment, Vec3};
+
+    #[test]
+    fn test_fit_helix() {
+        // half a turn of radius 10 around (5,5), down 2mm, cw
+        let point = |i: usize| {
+            let a = PI - i as Scalar / 60. * PI;
+            Vec3::new(
+                5. + 10. * a.cos(),
+                5. + 10. * a.sin(),
+                -2. * i as Scalar / 60.,
+            )
+        };
+        let mut fitter = ArcFitter::new(ArcFitSettings::default(), point(0));
+        let mut out = Vec::new();
+        for i in 1..=60 {
+            out.extend(fitter.push(point(i), 1));
+        }
+        // a straight lead out
+        out.extend(fitter.push(Vec3::new(15., 5., -3.), 1));
+        out.extend(fitter.push(Vec3::new(15., 5., -4.), 1));
+        out.extend(fitter.finish());
+
+        assert_eq!(out.len(), 2);
+        match out[0].0 {
+            Segment::Arc {
+                start,
+                end,
+                center,
+                turn_ccw,
+            } => {
+                assert_eq!((start, end), (point(0), point(60)));
+                assert!((center - crate::Vec2::new(5., 5.)).distance() < 1e-3);
+                assert!(!turn_ccw);
+                let arc = Arc::new_center_mode(
+                    start,
+                    end,
+                    center - start.plane(),
+                    turn_ccw,
+                    1,
+                    RadiusTolerance::Grbl,
+                )
+                .unwrap();
+                for i in 0..=60 {
+                    assert!((arc.point_at(i as Scalar / 60.) - point(i)).distance() < 1e-2);
+                }
+            }
+            _ => panic!("{:?}", out[0]),
+        }
+        assert_eq!(
+            out[1].0,
+            Segment::Line {
+                start: point(60),
+                end: Vec3::new(15., 5., -4.)
+            }
+        );
+    }
+
+    #[test]
+    fn test_fit_corners() {
+        // a square of many short lines, the corners stay sharp and each side is one line
+        let mut fitter = ArcFitter::new(ArcFitSettings::default(), Vec3::new(0., 0., 0.));
+        let mut out = Vec::new();
+        let corners = [(10., 0.), (10., 10.), (0., 10.)];
+        let mut last = (0., 0.);
+        for (i, c) in corners.iter().enumerate() {
+            for k in 1..=10 {
+                let t = k as Scalar / 10.;
+                let p = Vec3::new(last.0 + (c.0 - last.0) * t, last.1 + (c.1 - last.1) * t, 0.);
+                // the data changes after the first side
+                out.extend(fitter.push(p, i.min(1)));
+            }
+            last = *c;
+        }
+        out.extend(fitter.finish());
+        let ends: Vec<_> = out.iter().map(|(s, d)| (s.end(), *d)).collect();
+        assert_eq!(
+            ends,
+            [
+                (Vec3::new(10., 0., 0.), 0),
+                (Vec3::new(10., 10., 0.), 1),
+                (Vec3::new(0., 10., 0.), 1)
+            ]
+        );
+
+        // a zigzag beyond the tolerance is not fitted
+        let settings = ArcFitSettings {
+            corner_angle: PI,
+            ..ArcFitSettings::default()
+        };
+        let mut fitter = ArcFitter::new(settings, Vec3::new(0., 0., 0.));
+        let mut out = Vec::new();
+        for i in 1..=6 {
+            out.extend(fitter.push(Vec3::new(i as Scalar, (i % 2) as Scalar * 0.1, 0.), ()));
+        }
+        out.extend(fitter.finish());
+        assert_eq!(out.len(), 6);
+    }
+}
@@ -0,0 +1,630 @@
+//! tool radius compensation(G41/G42), offset a stream of lines and arcs by the tool radius.
+//!
+//! all positions are represented with plane and linear, the offset is done in the plane.
+//!
+//! ```text
+//!   outside corner: insert an arc around the       inside corner: trim both offset
+//!   programmed corner                              segments at their intersection
+//!
+//!          offset                                         |   |
+//!       ---------.                                        |   | offset
+//!                 `.                                      |   |
+//!     path ------+  \                           path -----+   |
+//!                |   |                                        |
+//!                |   | offset                  offset --------+
+//! ```
+use crate::vecx::{Vec2, Vec3};
+use crate::Scalar;
+use alloc::vec::Vec;
+#[allow(unused_imports)]
+use num_traits::Float;
+
+/// tangents with a smaller cross value are taken as a straight junction
+const TANGENT_EPSILON: Scalar = 1e-5;
+
+/// side of the tool relative to the direction of travel
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Side {
+    /// G41
+    Left,
+    /// G42
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompError {
+    /// the entry move is an arc, it must be a line
+    ArcEntry,
+    /// the exit move is an arc, it must be a line
+    ArcExit,
+    /// the entry move is not longer than the tool radius
+    EntryTooShort,
+    /// concave arc with radius not greater than the tool radius, at the arc center
+    ToolRadiusTooLarge(Vec2<Scalar>),
+    /// the offset segments around the programmed corner do not meet, the tool would cut into the part
+    Gouge(Vec2<Scalar>),
+}
+
+/// a programmed or offset motion
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Segment {
+    Line {
+        start: Vec3<Scalar>,
+        end: Vec3<Scalar>,
+    },
+    Arc {
+        start: Vec3<Scalar>,
+        end: Vec3<Scalar>,
+        center: Vec2<Scalar>,
+        turn_ccw: bool,
+    },
+}
+
+impl Segment {
+    pub fn start(&self) -> Vec3<Scalar> {
+        match self {
+            Self::Line { start, .. } | Self::Arc { start, .. } => *start,
+        }
+    }
+
+    pub fn end(&self) -> Vec3<Scalar> {
+        match self {
+            Self::Line { end, .. } | Self::Arc { end, .. } => *end,
+        }
+    }
+
+    /// true if it does not move in the plane, e.g. a plunge
+    fn is_linear_only(&self) -> bool {
+        match self {
+            Self::Line { start, end } => (end.plane() - start.plane()).distance() < TANGENT_EPSILON,
+            Self::Arc { .. } => false,
+        }
+    }
+
+    /// unit tangent in the plane at point p of the segment
+    fn tangent_at(&self, p: Vec2<Scalar>) -> Vec2<Scalar> {
+        match self {
+            Self::Line { start, end } => (end.plane() - start.plane()).as_unit_vec(),
+            Self::Arc {
+                center, turn_ccw, ..
+            } => {
+                let t = (p - *center).as_unit_vec().perp();
+                if *turn_ccw {
+                    t
+                } else {
+                    -t
+                }
+            }
+        }
+    }
+
+    pub fn start_tangent(&self) -> Vec2<Scalar> {
+        self.tangent_at(self.start().plane())
+    }
+
+    pub fn end_tangent(&self) -> Vec2<Scalar> {
+        self.tangent_at(self.end().plane())
+    }
+
+    /// signed angular travel of an arc, the same rule as [`crate::Arc`], start == end means a full circle
+    fn angular_travel(&self) -> Scalar {
+        match self {
+            Self::Line { .. } => 0.,
+            Self::Arc {
+                start,
+                end,
+                center,
+                turn_ccw,
+            } => {
+                let (r_v, rt_v) = (start.plane() - *center, end.plane() - *center);
+                let mut angular_travel = r_v.wedge(rt_v).atan2(r_v.dot(rt_v));
+                let eps = crate::arc::config::ARC_ANGULAR_TRAVEL_EPSILON;
+                if *turn_ccw && angular_travel <= eps {
+                    angular_travel += crate::consts::TAU;
+                } else if !*turn_ccw && angular_travel >= -eps {
+                    angular_travel -= crate::consts::TAU;
+                }
+                angular_travel
+            }
+        }
+    }
+
+    /// fraction of the segment where point p(lying on it, or on its extension) is
+    fn param_of(&self, p: Vec2<Scalar>) -> Scalar {
+        match self {
+            Self::Line { start, end } => {
+                let d = end.plane() - start.plane();
+                (p - start.plane()).dot(d) / d.distance_sqr()
+            }
+            Self::Arc {
+                start,
+                center,
+                turn_ccw,
+                ..
+            } => {
+                let (r_v, rp_v) = (start.plane() - *center, p - *center);
+                let mut a = r_v.wedge(rp_v).atan2(r_v.dot(rp_v));
+                if !*turn_ccw {
+                    a = -a;
+                }
+                if a < -TANGENT_EPSILON {
+                    a += crate::consts::TAU;
+                }
+                a / self.angular_travel().abs()
+            }
+        }
+    }
+
+    /// move the start point to p, which lies at fraction u
+    fn with_start(self, p: Vec2<Scalar>, u: Scalar) -> Self {
+        let (s, e) = (self.start(), self.end());
+        let start = Vec3::new_from_plane(p, s.2 + (e.2 - s.2) * u);
+        match self {
+            Self::Line { end, .. } => Self::Line { start, end },
+            Self::Arc {
+                end,
+                center,
+                turn_ccw,
+                ..
+            } => Self::Arc {
+                start,
+                end,
+                center,
+                turn_ccw,
+            },
+        }
+    }
+
+    /// move the end point to p, which lies at fraction u
+    fn with_end(self, p: Vec2<Scalar>, u: Scalar) -> Self {
+        let (s, e) = (self.start(), self.end());
+        let end = Vec3::new_from_plane(p, s.2 + (e.2 - s.2) * u);
+        match self {
+            Self::Line { start, .. } => Self::Line { start, end },
+            Self::Arc {
+                start,
+                center,
+                turn_ccw,
+                ..
+            } => Self::Arc {
+                start,
+                end,
+                center,
+                turn_ccw,
+            },
+        }
+    }
+
+    /// intersections of the two segments, both are taken as infinite line or full circle
+    fn intersections(&self, other: &Self) -> Vec<Vec2<Scalar>> {
+        let mut points = Vec::new();
+        match (self, other) {
+            (Self::Line { .. }, Self::Line { .. }) => {
+                let (s1, d1) = (
+                    self.start().plane(),
+                    self.end().plane() - self.start().plane(),
+                );
+                let (s2, d2) = (
+                    other.start().plane(),
+                    other.end().plane() - other.start().plane(),
+                );
+                let denom = d1.wedge(d2);
+                if denom.abs() > TANGENT_EPSILON * d1.distance() * d2.distance() {
+                    let t = (s2 - s1).wedge(d2) / denom;
+                    points.push(s1 + d1 * t);
+                }
+            }
+            (Self::Line { .. }, Self::Arc { center, .. })
+            | (Self::Arc { center, .. }, Self::Line { .. }) => {
+                let (line, arc) = if let Self::Line { .. } = self {
+                    (self, other)
+                } else {
+                    (other, self)
+                };
+                let radius = (arc.start().plane() - *center).distance();
+                let s = line.start().plane();
+                let d = line.end().plane() - s;
+                let f = s - *center;
+                let a = d.distance_sqr();
+                let b = 2. * f.dot(d);
+                let c = f.distance_sqr() - radius * radius;
+                let disc = b * b - 4. * a * c;
+                if disc >= 0. {
+                    let sq = disc.sqrt();
+                    points.push(s + d * ((-b - sq) / (2. * a)));
+                    points.push(s + d * ((-b + sq) / (2. * a)));
+                }
+            }
+            (Self::Arc { center: c1, .. }, Self::Arc { center: c2, .. }) => {
+                let r1 = (self.start().plane() - *c1).distance();
+                let r2 = (other.start().plane() - *c2).distance();
+                let v = *c2 - *c1;
+                let d = v.distance();
+                if d > 0. && d <= r1 + r2 && d >= (r1 - r2).abs() {
+                    let a = (r1 * r1 - r2 * r2 + d * d) / (2. * d);
+                    let h = (r1 * r1 - a * a).max(0.).sqrt();
+                    let base = *c1 + v * (a / d);
+                    let off = v.perp() * (h / d);
+                    points.push(base + off);
+                    points.push(base - off);
+                }
+            }
+        }
+        points
+    }
+}
+
+/// a segment that waits for the next one to decide its end
+#[derive(Clone, Debug)]
+struct Pending<D> {
+    orig: Segment,
+    offset: Segment,
+    data: D,
+    /// linear-only moves programmed after it, they run at its end
+    linear_moves: Vec<(Scalar, D)>,
+}
+
+/// cutter radius compensation engine, it offsets one segment after the next one is known.
+///
+/// `D` is any data along with each segment, e.g. [`crate::PlanLineData`]. inserted corner arcs carry
+/// the data of the segment after the corner.
+///
+/// example
+/// ```
+/// use ngc_geometry::{CutterComp, Side, Segment, Vec3};
+/// let mut comp = CutterComp::new(1.0, Side::Left, Vec3::new(-5., -5., 0.));
+/// let mut out = Vec::new();
+/// for (start, end) in [((-5., -5.), (0., 0.)), ((0., 0.), (0., 10.)), ((0., 10.), (10., 10.))] {
+///     let seg = Segment::Line {
+///         start: Vec3::new(start.0, start.1, 0.),
+///         end: Vec3::new(end.0, end.1, 0.),
+///     };
+///     out.extend(comp.push(seg, ()).unwrap());
+/// }
+/// out.extend(comp.finish(None, ()));
+/// // lead-in, left side, outside corner arc, top side
+/// assert_eq!(out.len(), 4);
+/// ```
+#[derive(Clone, Debug)]
+pub struct CutterComp<D: Copy> {
+    radius: Scalar,
+    side: Side,
+    /// the tool position before the entry move
+    start: Vec3<Scalar>,
+    pending: Option<Pending<D>>,
+}
+
+impl<D: Copy> CutterComp<D> {
+    /// start is the current tool position, it is not compensated
+    pub fn new(radius: Scalar, side: Side, start: Vec3<Scalar>) -> Self {
+        Self {
+            radius: radius.abs(),
+            side,
+            start,
+            pending: None,
+        }
+    }
+
+    pub fn radius(&self) -> Scalar {
+        self.radius
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// normal in the plane pointing to the tool side
+    fn side_normal(&self, tangent: Vec2<Scalar>) -> Vec2<Scalar> {
+        match self.side {
+            Side::Left => tangent.perp(),
+            Side::Right => -tangent.perp(),
+        }
+    }
+
+    /// offset a programmed segment by the tool radius
+    pub fn offset(&self, seg: &Segment) -> Result<Segment, CompError> {
+        match *seg {
+            Segment::Line { start, end } => {
+                let n = self.side_normal(seg.start_tangent()) * self.radius;
+                Ok(Segment::Line {
+                    start: Vec3::new_from_plane(start.plane() + n, start.2),
+                    end: Vec3::new_from_plane(end.plane() + n, end.2),
+                })
+            }
+            Segment::Arc {
+                start,
+                end,
+                center,
+                turn_ccw,
+            } => {
+                let radius = (start.plane() - center).distance();
+                // the tool is inside the arc if it is on the left of a ccw arc, or on the right of a cw arc
+                let inside = turn_ccw == (self.side == Side::Left);
+                let new_radius = if inside {
+                    radius - self.radius
+                } else {
+                    radius + self.radius
+                };
+                if new_radius <= 0. {
+                    return Err(CompError::ToolRadiusTooLarge(center));
+                }
+                let move_to = |p: Vec3<Scalar>| {
+                    let u = (p.plane() - center).as_unit_vec();
+                    Vec3::new_from_plane(center + u * new_radius, p.2)
+                };
+                Ok(Segment::Arc {
+                    start: move_to(start),
+                    end: move_to(end),
+                    center,
+                    turn_ccw,
+                })
+            }
+        }
+    }
+
+    /// add next programmed segment, output the compensated segments that are decided
+    pub fn push(&mut self, seg: Segment, data: D) -> Result<Vec<(Segment, D)>, CompError> {
+        let mut out = Vec::new();
+
+        if seg.is_linear_only() {
+            match self.pending.as_mut() {
+                Some(p) => p.linear_moves.push((seg.end().2, data)),
+                None => {
+                    let end = Vec3::new_from_plane(self.start.plane(), seg.end().2);
+                    out.push((
+                        Segment::Line {
+                            start: self.start,
+                            end,
+                        },
+                        data,
+                    ));
+                    self.start = end;
+                }
+            }
+            return Ok(out);
+        }
+
+        let mut offset = match self.pending {
+            Some(_) => self.offset(&seg)?,
+            None => self.entry(&seg)?,
+        };
+        if let Some(prev) = self.pending.as_ref() {
+            let (prev_offset, next_offset) = self.join(prev, &seg, offset)?;
+            // keep the state unchanged on error, so take it after join
+            let prev = self.pending.take().unwrap();
+            offset = next_offset;
+            let mut last = prev_offset.end();
+            out.push((prev_offset, prev.data));
+            for (linear, d) in prev.linear_moves {
+                let end = Vec3::new_from_plane(last.plane(), linear);
+                out.push((Segment::Line { start: last, end }, d));
+                last = end;
+            }
+            // outside corner
+            if (offset.start().plane() - last.plane()).distance() > TANGENT_EPSILON {
+                let corner = Segment::Arc {
+                    start: last,
+                    end: Vec3::new_from_plane(offset.start().plane(), last.2),
+                    center: seg.start().plane(),
+                    turn_ccw: self.side == Side::Right,
+                };
+                out.push((corner, data));
+            }
+        }
+        self.pending = Some(Pending {
+            orig: seg,
+            offset,
+            data,
+            linear_moves: Vec::new(),
+        });
+        Ok(out)
+    }
+
+    /// turn off compensation, output the rest segments.
+    ///
+    /// exit is the target of the exit move(G40 with axis words), it is not compensated.
+    pub fn finish(self, exit: Option<Vec3<Scalar>>, data: D) -> Vec<(Segment, D)> {
+        let mut out = Vec::new();
+        let mut last = self.start;
+        if let Some(prev) = self.pending {
+            last = prev.offset.end();
+            out.push((prev.offset, prev.data));
+            for (linear, d) in prev.linear_moves {
+                let end = Vec3::new_from_plane(last.plane(), linear);
+                out.push((Segment::Line { start: last, end }, d));
+                last = end;
+            }
+        }
+        if let Some(end) = exit {
+            out.push((Segment::Line { start: last, end }, data));
+        }
+        out
+    }
+
+    /// the entry move starts at the uncompensated position and ends at the offset of its end point
+    fn entry(&self, seg: &Segment) -> Result<Segment, CompError> {
+        match *seg {
+            Segment::Line { end, .. } => {
+                let d = end.plane() - self.start.plane();
+                if d.distance() <= self.radius {
+                    return Err(CompError::EntryTooShort);
+                }
+                let n = self.side_normal(d.as_unit_vec()) * self.radius;
+                Ok(Segment::Line {
+                    start: self.start,
+                    end: Vec3::new_from_plane(end.plane() + n, end.2),
+                })
+            }
+            Segment::Arc { .. } => Err(CompError::ArcEntry),
+        }
+    }
+
+    /// trim both offset segments at an inside corner. outside corners are left as they are
+    fn join(
+        &self,
+        prev: &Pending<D>,
+        next: &Segment,
+        next_offset: Segment,
+    ) -> Result<(Segment, Segment), CompError> {
+        let cross = prev.orig.end_tangent().wedge(next.start_tangent());
+        let inside = match self.side {
+            Side::Left => cross > TANGENT_EPSILON,
+            Side::Right => cross < -TANGENT_EPSILON,
+        };
+        if !inside {
+            return Ok((prev.offset, next_offset));
+        }
+
+        let corner = next.start().plane();
+        let nearest = prev
+            .offset
+            .intersections(&next_offset)
+            .into_iter()
+            .min_by(|a, b| {
+                let (da, db) = ((*a - corner).distance(), (*b - corner).distance());
+                da.partial_cmp(&db).unwrap_or(core::cmp::Ordering::Equal)
+            })
+            .ok_or(CompError::Gouge(corner))?;
+
+        let u_prev = prev.offset.param_of(nearest);
+        let u_next = next_offset.param_of(nearest);
+        if u_prev <= 0. || u_next >= 1. {
+            return Err(CompError::Gouge(corner));
+        }
+        Ok((
+            prev.offset.with_end(nearest, u_prev),
+            next_offset.with_start(nearest, u_next),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CompError, CutterComp, Segment, Side, Vec2, Vec3};
+    use crate::Scalar;
+
+    fn line(s: (Scalar, Scalar), e: (Scalar, Scalar)) -> Segment {
+        Segment::Line {
+            start: Vec3::new(s.0, s.1, 0.),
+            end: Vec3::new(e.0, e.1, 0.),
+        }
+    }
+
+    fn is_near(a: Vec3<Scalar>, b: Vec3<Scalar>) -> bool {
+        (a - b).distance() < 1e-4
+    }
+
+    /// square 10x10 from (0,0), counterclockwise
+    fn square() -> [Segment; 5] {
+        [
+            line((-5., -5.), (0., 0.)),
+            line((0., 0.), (10., 0.)),
+            line((10., 0.), (10., 10.)),
+            line((10., 10.), (0., 10.)),
+            line((0., 10.), (0., 0.)),
+        ]
+    }
+
+    #[test]
+    fn test_outside_corners() {
+        // ccw square with tool on the right is an outside profile
+        let mut comp = CutterComp::new(1., Side::Right, Vec3::new(-5., -5., 0.));
+        let mut out = Vec::new();
+        for seg in square() {
+            out.extend(comp.push(seg, ()).unwrap());
+        }
+        out.extend(comp.finish(Some(Vec3::new(-5., -5., 0.)), ()));
+        let segs: Vec<_> = out.into_iter().map(|(s, _)| s).collect();
+
+        // lead-in, 4 sides, 3 corner arcs, lead-out
+        assert_eq!(segs.len(), 9);
+        // the diagonal lead-in meets the offset of the first side
+        assert!((segs[1].start().1 + 1.).abs() < 1e-4);
+        assert!(segs[1].start().0 > 0. && segs[1].start().0 < 0.5);
+        assert!(is_near(segs[1].end(), Vec3::new(10., -1., 0.)));
+        assert_eq!(
+            segs[2],
+            Segment::Arc {
+                start: Vec3::new(10., -1., 0.),
+                end: Vec3::new(11., 0., 0.),
+                center: Vec2::new(10., 0.),
+                turn_ccw: true
+            }
+        );
+        assert!(is_near(segs[3].end(), Vec3::new(11., 10., 0.)));
+        for w in segs.windows(2) {
+            assert!(is_near(w[0].end(), w[1].start()));
+        }
+    }
+
+    #[test]
+    fn test_inside_corners() {
+        // ccw square with tool on the left is a pocket
+        let mut comp = CutterComp::new(1., Side::Left, Vec3::new(5., 5., 0.));
+        let mut out = Vec::new();
+        let mut program = square();
+        program[0] = line((5., 5.), (0., 0.));
+        for seg in program {
+            out.extend(comp.push(seg, ()).unwrap());
+        }
+        out.extend(comp.finish(None, ()));
+        let segs: Vec<_> = out.into_iter().map(|(s, _)| s).collect();
+
+        assert_eq!(segs.len(), 5);
+        assert!((segs[1].start().1 - 1.).abs() < 1e-4);
+        assert!(is_near(segs[1].end(), Vec3::new(9., 1., 0.)));
+        assert!(is_near(segs[2].end(), Vec3::new(9., 9., 0.)));
+        assert!(is_near(segs[4].end(), Vec3::new(1., 0., 0.)));
+
+        // line then a ccw arc with the tool inside, the arc radius is reduced
+        let mut comp = CutterComp::new(1., Side::Left, Vec3::new(-5., 0., 0.));
+        comp.push(line((-5., 0.), (0., 0.)), ()).unwrap();
+        let arc = Segment::Arc {
+            start: Vec3::new(0., 0., 0.),
+            end: Vec3::new(10., 0., 0.),
+            center: Vec2::new(5., 0.),
+            turn_ccw: true,
+        };
+        let out = comp.push(arc, ()).unwrap();
+        // lead-in and the corner arc, the arc starts downward
+        assert_eq!(out.len(), 2);
+        assert!(is_near(out[1].0.end(), Vec3::new(1., 0., 0.)));
+        let segs = comp.finish(None, ());
+        if let (Segment::Arc { start, end, .. }, _) = segs[0] {
+            assert!(((start.plane() - Vec2::new(5., 0.)).distance() - 4.).abs() < 1e-4);
+            assert!(is_near(end, Vec3::new(9., 0., 0.)));
+        } else {
+            panic!("arc expected");
+        }
+    }
+
+    #[test]
+    fn test_gouge() {
+        let mut comp = CutterComp::new(1., Side::Left, Vec3::new(0., -5., 0.));
+        assert_eq!(
+            comp.push(line((0., -5.), (0., -4.5)), ()),
+            Err(CompError::EntryTooShort)
+        );
+        comp.push(line((0., -5.), (0., 0.)), ()).unwrap();
+        comp.push(line((0., 0.), (5., 0.)), ()).unwrap();
+        // a step shorter than the tool radius at an inside corner
+        assert_eq!(
+            comp.push(line((5., 0.), (5., 0.5)), ()),
+            Err(CompError::Gouge(Vec2::new(5., 0.)))
+        );
+        // state is kept, the program can go on
+        assert_eq!(comp.push(line((5., 0.), (5., 5.)), ()).unwrap().len(), 1);
+
+        let comp = CutterComp::<()>::new(3., Side::Left, Vec3::new(0., 0., 0.));
+        let arc = Segment::Arc {
+            start: Vec3::new(0., 0., 0.),
+            end: Vec3::new(4., 0., 0.),
+            center: Vec2::new(2., 0.),
+            turn_ccw: true,
+        };
+        assert_eq!(
+            comp.offset(&arc),
+            Err(CompError::ToolRadiusTooLarge(Vec2::new(2., 0.)))
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! elliptical arc interpolation
+//!  an ellipse in a canon plane, with an optional helix along the linear axis
+//!
+use super::arc::{ArcError, ArcSettings};
+use super::vecx::{CanonPlane, Vec2, Vec3};
+use crate::Scalar;
+
+/// Elliptical arc, the positions are `center + R(rotation) * (a * cos(t), b * sin(t))`.
+///
+/// the angles are the parameter t of this formula (eccentric anomaly), they are the polar angles only on a circle.
+/// it outputs the segment ends like [`crate::Arc`]: the start is not included, the last one is the target.
+/// the steps follow the curvature, so that every chord is within `ArcSettings::tolerance`.
+///
+/// example, half an ellipse of 20x10 rotated by 90 degrees, going down 2mm
+/// ```
+/// use ngc_geometry::{Ellipse, Vec2, Vec3};
+/// use ngc_geometry::consts::{FRAC_PI_2, PI};
+/// let ellipse = Ellipse::new(Vec3::new(0., 0., 0.), Vec2::new(20., 10.), FRAC_PI_2, 0., PI, true)
+///     .unwrap()
+///     .with_helix(-2.);
+/// assert!((ellipse.start() - Vec3::new(0., 20., 0.)).distance() < 1e-5);
+/// let target = ellipse.target();
+/// assert!((target - Vec3::new(0., -20., -2.)).distance() < 1e-5);
+/// assert_eq!(ellipse.last(), Some(target));
+/// ```
+pub struct Ellipse {
+    /// center in plane, the linear axis is at the start
+    center: Vec3<Scalar>,
+    /// a, b - semi axes along the rotated axis0 and axis1
+    semi_axes: Vec2<Scalar>,
+    /// radians, ccw from axis0 to the first semi axis
+    rotation: Scalar,
+    /// (sin, cos) of rotation
+    rotation_sin_cos: (Scalar, Scalar),
+    /// parameter of the start point
+    start_angle: Scalar,
+    /// signed parameter travel, positive is ccw
+    angular_travel: Scalar,
+    /// travel of the linear axis, 0 is a planar ellipse
+    linear_travel: Scalar,
+    /// the plane that the positions are represented in, outputs are converted to XYZ from it
+    plane: CanonPlane,
+    settings: ArcSettings,
+
+    /// fraction of the path of the last output, None when it is done
+    fraction: Option<Scalar>,
+}
+
+impl Ellipse {
+    /// Ellipse in XY plane.
+    /// center is the center in XY, and its Z is the start height.
+    /// semi_axes (a, b) are along axis0 and axis1 before the ellipse is rotated ccw by rotation.
+    /// start_angle and end_angle are the parameters of the formula, the ellipse goes from start to end in
+    /// the direction, it is a full ellipse if they are the same.
+    ///
+    /// error if a semi axis is not positive
+    pub fn new(
+        center: Vec3<Scalar>,
+        semi_axes: Vec2<Scalar>,
+        rotation: Scalar,
+        start_angle: Scalar,
+        end_angle: Scalar,
+        turn_ccw: bool,
+    ) -> Result<Self, ArcError> {
+        if semi_axes.0 <= 0. || semi_axes.1 <= 0. {
+            return Err(ArcError::ZeroRadius);
+        }
+        let settings = ArcSettings::default();
+        Ok(Self {
+            center,
+            semi_axes,
+            rotation,
+            rotation_sin_cos: num_traits::Float::sin_cos(rotation),
+            start_angle,
+            angular_travel: travel_by_direction(end_angle - start_angle, turn_ccw, &settings),
+            linear_travel: 0.,
+            plane: CanonPlane::CanonPlaneXY,
+            settings,
+            fraction: Some(0.),
+        })
+    }
+
+    /// Ellipse in a plane, center is XYZ and the arc outputs XYZ positions.
+    ///
+    /// rotation and turn_ccw are viewed from the positive end of the axis perpendicular to the plane, as
+    /// [`crate::Arc::new_center_mode_in_plane`].
+    pub fn new_in_plane(
+        plane: CanonPlane,
+        center: Vec3<Scalar>,
+        semi_axes: Vec2<Scalar>,
+        rotation: Scalar,
+        start_angle: Scalar,
+        end_angle: Scalar,
+        turn_ccw: bool,
+    ) -> Result<Self, ArcError> {
+        let xy = CanonPlane::CanonPlaneXY;
+        let mut ellipse = Self::new(
+            xy.to_plane(&center, &plane),
+            semi_axes,
+            rotation,
+            start_angle,
+            end_angle,
+            turn_ccw,
+        )?;
+        ellipse.plane = plane;
+        Ok(ellipse)
+    }
+
+    /// the linear axis moves linear_travel mm evenly with the angle, the iteration restarts
+    pub fn with_helix(mut self, linear_travel: Scalar) -> Self {
+        self.linear_travel = linear_travel;
+        self.fraction = Some(0.);
+        self
+    }
+
+    /// use settings instead of the default, the iteration restarts from the start point
+    pub fn with_settings(mut self, settings: ArcSettings) -> Self {
+        let turn_ccw = self.turn_ccw();
+        self.settings = settings;
+        self.angular_travel = travel_by_direction(self.angular_travel, turn_ccw, &settings);
+        self.fraction = Some(0.);
+        self
+    }
+
+    pub fn settings(&self) -> &ArcSettings {
+        &self.settings
+    }
+
+    /// the plane of the ellipse, outputs of [`Ellipse::new_in_plane`] are XYZ positions
+    #[inline]
+    pub fn plane(&self) -> CanonPlane {
+        self.plane
+    }
+
+    /// ellipse center in plane, it is (axis0, axis1) of [`Ellipse::plane`]
+    #[inline]
+    pub fn center(&self) -> Vec2<Scalar> {
+        self.center.plane()
+    }
+
+    /// semi axes (a, b) in mm
+    #[inline]
+    pub fn semi_axes(&self) -> Vec2<Scalar> {
+        self.semi_axes
+    }
+
+    /// radians, ccw from axis0 of the plane to the first semi axis
+    #[inline]
+    pub fn rotation(&self) -> Scalar {
+        self.rotation
+    }
+
+    /// true means counter clockwise direction
+    #[inline]
+    pub fn turn_ccw(&self) -> bool {
+        self.angular_travel > 0.
+    }
+
+    /// signed travel of the parameter, positive is ccw
+    #[inline]
+    pub fn angular_travel(&self) -> Scalar {
+        self.angular_travel
+    }
+
+    /// start position, in XYZ
+    pub fn start(&self) -> Vec3<Scalar> {
+        self.point_at(0.)
+    }
+
+    /// target position, in XYZ
+    pub fn target(&self) -> Vec3<Scalar> {
+        self.point_at(1.)
+    }
+
+    /// point at the fraction of the parameter travel, 0 is the start and 1 is the target, in XYZ
+    pub fn point_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let t = self.start_angle + self.angular_travel * fraction;
+        let (sin, cos) = num_traits::Float::sin_cos(t);
+        let p = self.rotate(self.semi_axes.0 * cos, self.semi_axes.1 * sin);
+        let linear = self.center.linear() + self.linear_travel * fraction;
+        self.to_xyz(&Vec3::new_from_plane(self.center.plane() + p, linear))
+    }
+
+    /// unit vector of the moving direction at the fraction of the parameter travel, in XYZ
+    pub fn tangent_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        self.to_xyz(&self.derivative(fraction).as_unit_vec())
+    }
+
+    /// unit vector of the moving direction at the start, in XYZ
+    pub fn start_tangent(&self) -> Vec3<Scalar> {
+        self.tangent_at(0.)
+    }
+
+    /// unit vector of the moving direction at the target, in XYZ
+    pub fn end_tangent(&self) -> Vec3<Scalar> {
+        self.tangent_at(1.)
+    }
+
+    /// length of the path in mm, including the linear axis of a helix.
+    /// the ellipse perimeter has no closed form, it is integrated by simpson's rule
+    pub fn length(&self) -> Scalar {
+        let travel = num_traits::Float::abs(self.angular_travel);
+        // 16 intervals per quarter keep the error far below the chord tolerance
+        let n = 2 * (num_traits::Float::ceil(travel * 16. / crate::consts::PI) as u32).max(2);
+        let h = 1. / n as Scalar;
+        let speed = |i: u32| self.derivative(i as Scalar * h).distance();
+        let mut sum = speed(0) + speed(n);
+        for i in 1..n {
+            sum += speed(i) * if i % 2 == 1 { 4. } else { 2. };
+        }
+        sum * h / 3.
+    }
+
+    /// axis-aligned bounding box (min, max) of the path, in XYZ
+    pub fn bounding_box(&self) -> (Vec3<Scalar>, Vec3<Scalar>) {
+        use crate::consts::{PI, TAU};
+        let start = self.to_plane(&self.start());
+        let end = self.to_plane(&self.target());
+        let mut min = Vec3::new(start.0.min(end.0), start.1.min(end.1), start.2.min(end.2));
+        let mut max = Vec3::new(start.0.max(end.0), start.1.max(end.1), start.2.max(end.2));
+        // the parameters where axis0 or axis1 is extreme are included if the path goes through them
+        let (a, b) = (self.semi_axes.0, self.semi_axes.1);
+        let (sin_r, cos_r) = self.rotation_sin_cos;
+        let extreme0 = num_traits::Float::atan2(-b * sin_r, a * cos_r);
+        let extreme1 = num_traits::Float::atan2(b * cos_r, a * sin_r);
+        let travel = num_traits::Float::abs(self.angular_travel);
+        for t in [extreme0, extreme0 + PI, extreme1, extreme1 + PI] {
+            let swept = if self.angular_travel >= 0. {
+                t - self.start_angle
+            } else {
+                self.start_angle - t
+            };
+            let swept = swept - TAU * num_traits::Float::floor(swept / TAU);
+            if swept <= travel {
+                let p = self.to_plane(&self.point_at(swept / travel));
+                min = Vec3::new(min.0.min(p.0), min.1.min(p.1), min.2);
+                max = Vec3::new(max.0.max(p.0), max.1.max(p.1), max.2);
+            }
+        }
+        (self.to_xyz(&min), self.to_xyz(&max))
+    }
+
+    /// derivative of the point by fraction, in plane
+    fn derivative(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let t = self.start_angle + self.angular_travel * fraction;
+        let (sin, cos) = num_traits::Float::sin_cos(t);
+        let d = self.rotate(-self.semi_axes.0 * sin, self.semi_axes.1 * cos);
+        Vec3::new(
+            d.0 * self.angular_travel,
+            d.1 * self.angular_travel,
+            self.linear_travel,
+        )
+    }
+
+    /// rotate a vector of the ellipse frame into the plane
+    #[inline]
+    fn rotate(&self, x: Scalar, y: Scalar) -> Vec2<Scalar> {
+        let (sin, cos) = self.rotation_sin_cos;
+        Vec2::new(x * cos - y * sin, x * sin + y * cos)
+    }
+
+    /// XYZ to plane coordinates
+    #[inline]
+    fn to_plane(&self, p: &Vec3<Scalar>) -> Vec3<Scalar> {
+        CanonPlane::CanonPlaneXY.to_plane(p, &self.plane)
+    }
+
+    /// plane coordinates to XYZ
+    #[inline]
+    fn to_xyz(&self, p: &Vec3<Scalar>) -> Vec3<Scalar> {
+        self.plane.to_plane(p, &CanonPlane::CanonPlaneXY)
+    }
+
+    /// parameter step from t whose chord is within the tolerance and max_segment_length.
+    ///
+    /// the sagitta of a step dt is about `k * v^2 * dt^2 / 8`, the curvature k is `a * b / v^3` where v is the
+    /// speed `|dp/dt|`, so `dt = sqrt(8 * tolerance * v / (a * b))`. it is computed again at the middle of the
+    /// step, the smaller one is taken.
+    fn step(&self, t: Scalar) -> Scalar {
+        let (a, b) = (self.semi_axes.0, self.semi_axes.1);
+        let tolerance = self.settings.tolerance.min(a.min(b));
+        let speed = |t: Scalar| {
+            let (sin, cos) = num_traits::Float::sin_cos(t);
+            num_traits::Float::hypot(a * sin, b * cos)
+        };
+        let dir = num_traits::Float::signum(self.angular_travel);
+        let by_speed = |v: Scalar| {
+            let mut dt = num_traits::Float::sqrt(8. * tolerance * v / (a * b));
+            if self.settings.max_segment_length > 0. {
+                dt = dt.min(self.settings.max_segment_length / v);
+            }
+            dt.min(crate::consts::FRAC_PI_4)
+        };
+        let dt = by_speed(speed(t));
+        dt.min(by_speed(speed(t + dir * 0.5 * dt)))
+    }
+}
+
+/// parameter travel of end - start in the direction, a travel within the epsilon is a full turn
+fn travel_by_direction(travel: Scalar, turn_ccw: bool, settings: &ArcSettings) -> Scalar {
+    use crate::consts::TAU;
+    let travel = travel - TAU * num_traits::Float::floor(travel / TAU);
+    if turn_ccw {
+        if travel <= settings.angular_travel_epsilon {
+            TAU
+        } else {
+            travel
+        }
+    } else if travel >= TAU - settings.angular_travel_epsilon
+        || travel <= settings.angular_travel_epsilon
+    {
+        -TAU
+    } else {
+        travel - TAU
+    }
+}
+
+impl core::iter::Iterator for Ellipse {
+    type Item = Vec3<Scalar>;
+
+    /// output the segment ends, not include start point
+    fn next(&mut self) -> Option<Self::Item> {
+        let fraction = self.fraction?;
+        let travel = num_traits::Float::abs(self.angular_travel);
+        let t = self.start_angle + self.angular_travel * fraction;
+        let next = fraction + self.step(t) / travel;
+        if next >= 1. {
+            self.fraction = None;
+            return Some(self.target());
+        }
+        self.fraction = Some(next);
+        Some(self.point_at(next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::consts::{FRAC_PI_2, PI, TAU};
+    use crate::Scalar;
+    use crate::{Arc, ArcSettings, CanonPlane, Ellipse, RadiusTolerance, Vec2, Vec3};
+
+    /// distance from p to the ellipse of (a, b) at the origin without rotation, to the first order
+    fn deviation(p: Vec2<Scalar>, a: Scalar, b: Scalar) -> Scalar {
+        let f = (p.0 / a) * (p.0 / a) + (p.1 / b) * (p.1 / b) - 1.;
+        let grad = Vec2::new(2. * p.0 / (a * a), 2. * p.1 / (b * b));
+        num_traits::Float::abs(f) / grad.distance()
+    }
+
+    #[test]
+    fn test_ellipse() {
+        // full ellipse of 20x5, the chords are within the tolerance
+        let settings = ArcSettings {
+            tolerance: 0.01,
+            ..ArcSettings::default()
+        };
+        let ellipse = Ellipse::new(Vec3::zero(), Vec2::new(20., 5.), 0., 0., 0., false)
+            .unwrap()
+            .with_settings(settings);
+        assert_eq!(ellipse.angular_travel(), -TAU);
+        assert_eq!(ellipse.start_tangent(), Vec3::new(0., -1., 0.));
+        let (min, max) = ellipse.bounding_box();
+        assert!((min - Vec3::new(-20., -5., 0.)).distance() < 1e-4);
+        assert!((max - Vec3::new(20., 5., 0.)).distance() < 1e-4);
+        // ramanujan's approximation of the perimeter
+        let (a, b) = (20. as Scalar, 5. as Scalar);
+        let h = (a - b) * (a - b) / ((a + b) * (a + b));
+        let perimeter = PI * (a + b) * (1. + 3. * h / (10. + (4. - 3. * h).sqrt()));
+        assert!((ellipse.length() - perimeter).abs() < 1e-3);
+
+        let (mut last, target) = (ellipse.start(), ellipse.target());
+        let mut polyline = 0.;
+        let all: Vec<_> = ellipse.collect();
+        for p in all.iter() {
+            assert!(deviation(p.plane(), a, b) < 1e-4);
+            let mid = Vec2::new((last.0 + p.0) * 0.5, (last.1 + p.1) * 0.5);
+            assert!(deviation(mid, a, b) <= 0.0105);
+            polyline += (*p - last).distance();
+            last = *p;
+        }
+        assert_eq!(all.last(), Some(&target));
+        assert!((target - Vec3::new(20., 0., 0.)).distance() < 1e-5);
+        assert!((polyline - perimeter).abs() < 0.05);
+
+        // a circle is the same with the arc
+        let ellipse =
+            Ellipse::new(Vec3::zero(), Vec2::new(10., 10.), 0., FRAC_PI_2, PI, true).unwrap();
+        let arc = Arc::new_center_mode(
+            Vec3::new(0., 10., 0.),
+            Vec3::new(-10., 0., 0.),
+            Vec2::new(0., -10.),
+            true,
+            1,
+            RadiusTolerance::Rs274ngc,
+        )
+        .unwrap();
+        assert!((ellipse.length() - arc.length()).abs() < 1e-4);
+        assert!((ellipse.tangent_at(0.5) - arc.tangent_at(0.5)).distance() < 1e-5);
+    }
+
+    #[test]
+    fn test_ellipse_in_plane() {
+        // G18, plane axes are (Z, X): a 10x4 ellipse rotated by 90 degrees has its long axis along X.
+        // half of it from +X to -X ccw goes through Z-4 as G3 in G18, with Y going up 3mm
+        let plane = CanonPlane::CanonPlaneXZ;
+        let center = Vec3::new(1., 2., 3.);
+        let ellipse =
+            Ellipse::new_in_plane(plane, center, Vec2::new(10., 4.), FRAC_PI_2, 0., PI, true)
+                .unwrap()
+                .with_helix(3.);
+        let near = |a: Vec3<Scalar>, b: Vec3<Scalar>| (a - b).distance() < 1e-4;
+        assert!(near(ellipse.start(), Vec3::new(11., 2., 3.)));
+        assert!(near(ellipse.target(), Vec3::new(-9., 5., 3.)));
+        assert!(near(ellipse.point_at(0.5), Vec3::new(1., 3.5, -1.)));
+        let (min, max) = ellipse.bounding_box();
+        assert!(near(min, Vec3::new(-9., 2., -1.)));
+        assert!(near(max, Vec3::new(11., 5., 3.)));
+        // moving towards -Z and +Y at the start
+        let d = ellipse.start_tangent();
+        assert!(near(d, Vec3::new(0., 3., -4. * PI).as_unit_vec()));
+        let all: Vec<_> = ellipse.collect();
+        assert!(all.iter().all(|p| p.2 <= 3. + 1e-4));
+        assert!(near(*all.last().unwrap(), Vec3::new(-9., 5., 3.)));
+    }
+}
@@ -0,0 +1,379 @@
+//! Q16.16 fixed point scalar, for microcontrollers without a float unit.
+//!
+//! all operations are integer: the products and quotients take an i64 middle, sin/cos are polynomials after a
+//! reduction by pi/2, and atan2 is CORDIC, all with 30 fraction bits inside.
+use super::traits::{Real, Sqrt};
+
+/// fraction bits of the inner precision of trig
+const TRIG_BITS: u32 = 30;
+/// pi/2 in Q2.30
+const HALF_PI_Q30: i64 = 1_686_629_713;
+/// atan(2^-i) in Q2.30
+const CORDIC_ATAN_Q30: [i64; 31] = [
+    843_314_857,
+    497_837_829,
+    263_043_837,
+    133_525_159,
+    67_021_687,
+    33_543_516,
+    16_775_851,
+    8_388_437,
+    4_194_283,
+    2_097_149,
+    1_048_576,
+    524_288,
+    262_144,
+    131_072,
+    65_536,
+    32_768,
+    16_384,
+    8_192,
+    4_096,
+    2_048,
+    1_024,
+    512,
+    256,
+    128,
+    64,
+    32,
+    16,
+    8,
+    4,
+    2,
+    1,
+];
+
+/// signed fixed point number with 16 integer bits and 16 fraction bits, the resolution is 1/65536.
+///
+/// the range is about +-32768, so positions are in mm up to 32m. the arithmetic saturates instead of overflow,
+/// and a division by zero saturates to the signed maximum. a squared length saturates first:
+/// [`crate::Vec3::distance`] is only right up to about 181 mm, sqrt(32768), longer vectors give about 181.
+///
+/// example
+/// ```
+/// use ngc_geometry::{Q16_16, Real, Vec3};
+/// let v = Vec3::new(Q16_16::from_int(3), Q16_16::from_int(4), Q16_16::from_int(12));
+/// assert_eq!(v.distance(), Q16_16::from_int(13));
+/// let (sin, _) = Q16_16::from_f32(core::f32::consts::FRAC_PI_6).sin_cos();
+/// assert!((sin.to_f32() - 0.5).abs() < 1e-4);
+/// ```
+#[allow(non_camel_case_types)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Debug, Default, Hash)]
+pub struct Q16_16(i32);
+
+impl Q16_16 {
+    pub const FRACTION_BITS: u32 = 16;
+    pub const MAX: Self = Self(i32::MAX);
+    pub const MIN: Self = Self(i32::MIN);
+
+    /// from the raw bits, the value is raw / 65536
+    #[inline]
+    pub const fn from_raw(raw: i32) -> Self {
+        Self(raw)
+    }
+
+    #[inline]
+    pub const fn raw(self) -> i32 {
+        self.0
+    }
+
+    /// from an integer, it saturates out of the range
+    #[inline]
+    pub fn from_int(v: i32) -> Self {
+        Self::saturate((v as i64) << Self::FRACTION_BITS)
+    }
+
+    /// the integer part, towards negative infinity
+    #[inline]
+    pub fn to_int(self) -> i32 {
+        self.0 >> Self::FRACTION_BITS
+    }
+
+    #[inline]
+    fn saturate(v: i64) -> Self {
+        Self(v.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+
+    /// value in Q2.30 of the trig functions
+    #[inline]
+    fn to_q30(self) -> i64 {
+        (self.0 as i64) << (TRIG_BITS - Self::FRACTION_BITS)
+    }
+
+    /// rounded from Q2.30
+    #[inline]
+    fn from_q30(v: i64) -> Self {
+        let shift = TRIG_BITS - Self::FRACTION_BITS;
+        Self::saturate((v + (1 << (shift - 1))) >> shift)
+    }
+}
+
+/// (sin, cos) in Q2.30 of |x| <= pi/4 in Q2.30, taylor series to x^9 are within 2^-30 there
+fn sin_cos_q30(x: i64) -> (i64, i64) {
+    let mul = |a: i64, b: i64| (a * b) >> TRIG_BITS;
+    let one = 1_i64 << TRIG_BITS;
+    let x2 = mul(x, x);
+    // horner form, each step divides by the next two factorial factors
+    let sin = mul(
+        x,
+        one - mul(x2, one - mul(x2, one - mul(x2, one - x2 / 72) / 42) / 20) / 6,
+    );
+    let cos = one
+        - mul(
+            x2,
+            one - mul(x2, one - mul(x2, one - mul(x2, one - x2 / 90) / 56) / 30) / 12,
+        ) / 2;
+    (sin, cos)
+}
+
+impl core::ops::Add for Q16_16 {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl core::ops::Sub for Q16_16 {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl core::ops::Mul for Q16_16 {
+    type Output = Self;
+    /// rounded to nearest
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        let p = self.0 as i64 * rhs.0 as i64;
+        Self::saturate((p + (1 << (Self::FRACTION_BITS - 1))) >> Self::FRACTION_BITS)
+    }
+}
+
+impl core::ops::Div for Q16_16 {
+    type Output = Self;
+    /// towards zero
+    #[inline]
+    fn div(self, rhs: Self) -> Self {
+        if rhs.0 == 0 {
+            return if self.0 < 0 { Self::MIN } else { Self::MAX };
+        }
+        Self::saturate(((self.0 as i64) << Self::FRACTION_BITS) / rhs.0 as i64)
+    }
+}
+
+impl core::ops::Neg for Q16_16 {
+    type Output = Self;
+    #[inline]
+    fn neg(self) -> Self {
+        Self(self.0.saturating_neg())
+    }
+}
+
+impl num_traits::Zero for Q16_16 {
+    #[inline]
+    fn zero() -> Self {
+        Self(0)
+    }
+    #[inline]
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl num_traits::One for Q16_16 {
+    #[inline]
+    fn one() -> Self {
+        Self(1 << Self::FRACTION_BITS)
+    }
+}
+
+impl num_traits::Inv for Q16_16 {
+    type Output = Self;
+    #[inline]
+    fn inv(self) -> Self {
+        <Self as num_traits::One>::one() / self
+    }
+}
+
+impl Sqrt for Q16_16 {
+    /// rounded down, zero for a negative value
+    fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Self(0);
+        }
+        // sqrt(raw / 2^16) * 2^16 == sqrt(raw * 2^16), bit by bit
+        let v = (self.0 as u64) << Self::FRACTION_BITS;
+        let (mut rest, mut root) = (v, 0_u64);
+        let mut bit = 1_u64 << 62;
+        while bit > v {
+            bit >>= 2;
+        }
+        while bit != 0 {
+            if rest >= root + bit {
+                rest -= root + bit;
+                root = (root >> 1) + bit;
+            } else {
+                root >>= 1;
+            }
+            bit >>= 2;
+        }
+        Self(root as i32)
+    }
+}
+
+impl Real for Q16_16 {
+    fn from_f32(v: f32) -> Self {
+        let v = num_traits::Float::round(v * (1 << Self::FRACTION_BITS) as f32);
+        Self(v.clamp(i32::MIN as f32, i32::MAX as f32) as i32)
+    }
+
+    fn from_f64(v: f64) -> Self {
+        let v = num_traits::Float::round(v * (1 << Self::FRACTION_BITS) as f64);
+        Self(v.clamp(i32::MIN as f64, i32::MAX as f64) as i32)
+    }
+
+    fn to_f32(self) -> f32 {
+        self.0 as f32 / (1 << Self::FRACTION_BITS) as f32
+    }
+
+    #[inline]
+    fn pi() -> Self {
+        Self(205_887)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        // x = q * pi/2 + r, |r| <= pi/4
+        let x = self.to_q30();
+        let q = (x + HALF_PI_Q30 / 2).div_euclid(HALF_PI_Q30);
+        let (sin, cos) = sin_cos_q30(x - q * HALF_PI_Q30);
+        let (sin, cos) = match q.rem_euclid(4) {
+            0 => (sin, cos),
+            1 => (cos, -sin),
+            2 => (-sin, -cos),
+            _ => (-cos, sin),
+        };
+        (Self::from_q30(sin), Self::from_q30(cos))
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        let (mut x, mut y) = (other.0 as i64, self.0 as i64);
+        if x == 0 && y == 0 {
+            return Self(0);
+        }
+        // rotate into the right half plane, then rotate onto axis0 by CORDIC
+        let mut angle = 0;
+        if x < 0 {
+            angle = if y >= 0 {
+                2 * HALF_PI_Q30
+            } else {
+                -2 * HALF_PI_Q30
+            };
+            (x, y) = (-x, -y);
+        }
+        (x, y) = (x << TRIG_BITS, y << TRIG_BITS);
+        for (i, a) in CORDIC_ATAN_Q30.iter().enumerate() {
+            let (dx, dy) = (x >> i, y >> i);
+            if y > 0 {
+                (x, y) = (x + dy, y - dx);
+                angle += a;
+            } else {
+                (x, y) = (x - dy, y + dx);
+                angle -= a;
+            }
+        }
+        Self::from_q30(angle)
+    }
+
+    #[inline]
+    fn abs(self) -> Self {
+        Self(self.0.saturating_abs())
+    }
+
+    #[inline]
+    fn floor(self) -> Self {
+        Self(self.0 & !((1 << Self::FRACTION_BITS) - 1))
+    }
+
+    #[inline]
+    fn ceil(self) -> Self {
+        Self::saturate(
+            ((self.0 as i64) + (1 << Self::FRACTION_BITS) - 1) & !((1 << Self::FRACTION_BITS) - 1),
+        )
+    }
+
+    fn round(self) -> Self {
+        let half = Self(1 << (Self::FRACTION_BITS - 1));
+        if self.0 < 0 {
+            -(-self + half).floor()
+        } else {
+            (self + half).floor()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Q16_16;
+    use crate::{Mat3, Real, Sqrt, Vec3};
+
+    /// within a few steps of the resolution
+    fn near(q: Q16_16, f: f32) -> bool {
+        (q.to_f32() - f).abs() <= 4. / 65536. + f.abs() * 1e-6
+    }
+
+    #[test]
+    fn test_q16_16_against_f32() {
+        for i in -400..=400 {
+            let f = i as f32 * 0.0437;
+            let q = Q16_16::from_f32(f);
+            let f = q.to_f32();
+            let (sin, cos) = q.sin_cos();
+            assert!(near(sin, f.sin()) && near(cos, f.cos()), "{}", f);
+            let g = Q16_16::from_f32(1.7 - i as f32 * 0.0113).to_f32();
+            assert!(
+                near(q.atan2(Q16_16::from_f32(g)), f.atan2(g)),
+                "{} {}",
+                f,
+                g
+            );
+            assert!(near(q.abs().sqrt(), f.abs().sqrt()), "{}", f);
+            assert!(near(q * Q16_16::from_f32(g), f * g));
+            assert!(near(q / Q16_16::from_f32(g), f / g));
+            assert_eq!(q.floor().to_f32(), f.floor());
+            assert_eq!(q.ceil().to_f32(), f.ceil());
+            assert_eq!(q.round().to_f32(), f.round());
+        }
+        assert_eq!(Q16_16::from_int(30000) * Q16_16::from_int(2), Q16_16::MAX);
+        assert_eq!(Q16_16::from_int(1) / Q16_16::from_int(0), Q16_16::MAX);
+        assert_eq!(Q16_16::from_int(-7).to_int(), -7);
+    }
+
+    #[test]
+    fn test_q16_16_geometry() {
+        let q = |v: Vec3<f32>| {
+            Vec3::new(
+                Q16_16::from_f32(v.0),
+                Q16_16::from_f32(v.1),
+                Q16_16::from_f32(v.2),
+            )
+        };
+        let f = |v: Vec3<Q16_16>| Vec3::new(v.0.to_f32(), v.1.to_f32(), v.2.to_f32());
+        let (a, b) = (Vec3::new(12.5_f32, -3.25, 40.), Vec3::new(-0.5_f32, 7., 2.));
+        assert!(near(q(a).distance(), a.distance()));
+        // the squared sum saturates past about 181 mm
+        let d = q(Vec3::new(120., 90., 80.)).distance();
+        assert!(near(d, 170.));
+        let d = q(Vec3::new(150., 120., 0.)).distance();
+        assert!((d.to_f32() - 181.02).abs() < 0.01);
+        assert_eq!(f(q(a).cross(q(b))), a.cross(b));
+        // as_unit_vec multiplies by the inverse of the length, its error is relative to the 16 fraction bits
+        assert!((f(q(a).as_unit_vec()) - a.as_unit_vec()).distance() < 5e-4);
+        // the error of a rotation grows with the length of the vector
+        let m = Mat3::from_axis_angle(q(b.as_unit_vec()), Q16_16::from_f32(0.9));
+        let r = Mat3::from_axis_angle(b.as_unit_vec(), 0.9);
+        assert!((f(m * q(a)) - r * a).distance() < 2e-3);
+    }
+}
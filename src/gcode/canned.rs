@@ -0,0 +1,502 @@
+//! canned cycles G73 and G81 ~ G89, one hole is expanded to rapid and feed moves.
+//!
+//! all positions are in plane coordinates, see [`crate::CanonPlane::to_plane`]: the third component is
+//! the drill axis, e.g. Z of G17, and the hole goes to its negative direction.
+//!
+//! see "3.5.16 Canned Cycles" in rs274ngc.pdf
+use crate::{PlanCondition, PlanLineData, Scalar, Vec2, Vec3};
+use alloc::vec::Vec;
+
+/// G83 stops this distance above the bottom of the last peck, G73 retracts this distance to break chips.
+pub const PECK_CLEARANCE: Scalar = 0.254; // mm, 0.010 inch
+
+/// group 1 canned cycles
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CycleKind {
+    /// G73 high speed peck drilling, retract a little after each peck
+    ChipBreak,
+    /// G81
+    Drill,
+    /// G82, dwell at the bottom
+    DrillDwell,
+    /// G83, retract to R plane after each peck
+    Peck,
+    /// G84 right hand tapping, feed out with reversed spindle
+    Tap,
+    /// G85, feed in and feed out
+    Bore,
+    /// G86, stop spindle at the bottom and rapid out
+    BoreSpindleStop,
+    /// G87, bore from the back side with I, J offset and K top
+    BackBore,
+    /// G88, stop spindle at the bottom and wait for manual retraction
+    BoreManual,
+    /// G89, dwell at the bottom and feed out
+    BoreDwell,
+}
+
+impl CycleKind {
+    /// from the G word, e.g. (83, 0) is G83
+    pub fn from_code(code: (u16, u8)) -> Option<Self> {
+        let kind = match code {
+            (73, 0) => Self::ChipBreak,
+            (81, 0) => Self::Drill,
+            (82, 0) => Self::DrillDwell,
+            (83, 0) => Self::Peck,
+            (84, 0) => Self::Tap,
+            (85, 0) => Self::Bore,
+            (86, 0) => Self::BoreSpindleStop,
+            (87, 0) => Self::BackBore,
+            (88, 0) => Self::BoreManual,
+            (89, 0) => Self::BoreDwell,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Q word is required
+    pub fn is_peck(&self) -> bool {
+        matches!(self, Self::ChipBreak | Self::Peck)
+    }
+}
+
+/// group 10
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RetractMode {
+    /// G98, retract to the higher of the initial level and R plane
+    Initial,
+    /// G99, retract to R plane
+    RPlane,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CycleError {
+    /// no R word since the cycle started
+    MissingR,
+    /// no Z word(drill axis) since the cycle started
+    MissingDepth,
+    /// the bottom of the hole is above R plane
+    RBelowBottom,
+    /// G73/G83 without a positive Q word
+    InvalidPeck,
+    /// L word is not a positive integer
+    InvalidRepeat,
+    /// G84/G86/G87/G88 while the spindle is off
+    SpindleOff,
+    /// G87 top K is not between the bottom and R plane
+    InvalidBackBore,
+    /// canned cycles are not allowed in G93 mode
+    InverseTime,
+    /// canned cycles are not allowed while cutter radius compensation is on
+    CutterComp,
+}
+
+/// one step of a canned cycle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CycleStep {
+    /// rapid or feed move to the position, it is the same with the flags of pl_data
+    Line(Vec3<Scalar>, PlanLineData),
+    /// dwell in seconds
+    Dwell(Scalar),
+    /// stop until the operator resumes, G88 waits here for manual retraction
+    Pause,
+}
+
+/// parameters of a canned cycle, the same for all holes of the cycle
+///
+/// example
+/// ```
+/// use ngc_geometry::gcode::{CannedCycle, CycleKind, CycleStep, RetractMode};
+/// use ngc_geometry::{PlanLineData, Vec2, Vec3};
+/// // G99 G81 X10 Y0 Z-5 R2, from X0 Y0 Z10
+/// let cycle = CannedCycle::new(CycleKind::Drill, RetractMode::RPlane, 2., -5.);
+/// let steps = cycle
+///     .expand(&Vec3::new(0., 0., 10.), Vec2::new(10., 0.), &PlanLineData::default())
+///     .unwrap();
+/// let ends: Vec<_> = steps
+///     .iter()
+///     .map(|s| match s {
+///         CycleStep::Line(p, _) => p.2,
+///         _ => unreachable!(),
+///     })
+///     .collect();
+/// assert_eq!(ends, vec![10., 2., -5., 2.]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CannedCycle {
+    pub kind: CycleKind,
+    pub retract: RetractMode,
+    /// R plane on the drill axis
+    pub r: Scalar,
+    /// bottom of the hole on the drill axis
+    pub bottom: Scalar,
+    /// Q word, depth of each peck of G73/G83
+    pub peck: Scalar,
+    /// P word, dwell in seconds at the bottom
+    pub dwell: Scalar,
+    /// G87 (I, J) offset to pass the hole with the spindle stopped
+    pub back_bore_offset: Vec2<Scalar>,
+    /// G87 K, top of the back bore on the drill axis
+    pub back_bore_top: Scalar,
+}
+
+impl CannedCycle {
+    pub fn new(kind: CycleKind, retract: RetractMode, r: Scalar, bottom: Scalar) -> Self {
+        Self {
+            kind,
+            retract,
+            r,
+            bottom,
+            peck: 0.,
+            dwell: 0.,
+            back_bore_offset: Vec2::new(0., 0.),
+            back_bore_top: bottom,
+        }
+    }
+
+    /// where the tool goes after a hole, initial is the drill axis position before the hole
+    pub fn clear_level(&self, initial: Scalar) -> Scalar {
+        match self.retract {
+            RetractMode::Initial => initial.max(self.r),
+            RetractMode::RPlane => self.r,
+        }
+    }
+
+    /// moves of one hole from current position, pl_data is the data of feed moves.
+    ///
+    /// the last step ends at the hole at the clear level.
+    pub fn expand(
+        &self,
+        current: &Vec3<Scalar>,
+        hole: Vec2<Scalar>,
+        pl_data: &PlanLineData,
+    ) -> Result<Vec<CycleStep>, CycleError> {
+        self.check(pl_data)?;
+        let clear = self.clear_level(current.2);
+        let mut s = Steps::new(*current, pl_data);
+
+        // preliminary motion, "3.5.16.1" in rs274ngc.pdf
+        if current.2 < self.r {
+            s.rapid_to(self.r);
+        }
+        s.rapid_xy(hole);
+        s.rapid_to(self.r);
+
+        match self.kind {
+            CycleKind::Drill => {
+                s.feed_to(self.bottom);
+                s.rapid_to(clear);
+            }
+            CycleKind::DrillDwell => {
+                s.feed_to(self.bottom);
+                s.dwell(self.dwell);
+                s.rapid_to(clear);
+            }
+            CycleKind::Peck | CycleKind::ChipBreak => {
+                let mut depth = self.r;
+                loop {
+                    depth = (depth - self.peck).max(self.bottom);
+                    s.feed_to(depth);
+                    if depth <= self.bottom {
+                        break;
+                    }
+                    if self.kind == CycleKind::Peck {
+                        s.rapid_to(self.r);
+                    }
+                    s.rapid_to((depth + PECK_CLEARANCE).min(self.r));
+                }
+                s.rapid_to(clear);
+            }
+            CycleKind::Tap => {
+                s.no_feed_override = true;
+                s.feed_to(self.bottom);
+                s.dwell(self.dwell);
+                s.reverse_spindle();
+                s.feed_to(self.r);
+                s.reverse_spindle();
+                s.no_feed_override = false;
+                s.rapid_to(clear);
+            }
+            CycleKind::Bore => {
+                s.feed_to(self.bottom);
+                s.feed_to(self.r);
+                s.rapid_to(clear);
+            }
+            CycleKind::BoreSpindleStop => {
+                s.feed_to(self.bottom);
+                s.dwell(self.dwell);
+                s.stop_spindle();
+                s.rapid_to(clear);
+            }
+            CycleKind::BackBore => {
+                let offset = hole + self.back_bore_offset;
+                s.stop_spindle();
+                s.rapid_xy(offset);
+                s.rapid_to(self.bottom);
+                s.rapid_xy(hole);
+                s.start_spindle();
+                s.feed_to(self.back_bore_top);
+                s.feed_to(self.bottom);
+                s.stop_spindle();
+                s.rapid_xy(offset);
+                s.rapid_to(clear);
+                s.rapid_xy(hole);
+            }
+            CycleKind::BoreManual => {
+                s.feed_to(self.bottom);
+                s.dwell(self.dwell);
+                s.stop_spindle();
+                s.steps.push(CycleStep::Pause);
+                s.rapid_to(clear);
+            }
+            CycleKind::BoreDwell => {
+                s.feed_to(self.bottom);
+                s.dwell(self.dwell);
+                s.feed_to(self.r);
+                s.rapid_to(clear);
+            }
+        }
+        Ok(s.steps)
+    }
+
+    fn check(&self, pl_data: &PlanLineData) -> Result<(), CycleError> {
+        if self.bottom > self.r {
+            return Err(CycleError::RBelowBottom);
+        }
+        if pl_data
+            .condition
+            .contains(PlanCondition::PL_COND_FLAG_INVERSE_TIME)
+        {
+            return Err(CycleError::InverseTime);
+        }
+        if self.kind.is_peck() && self.peck <= 0. {
+            return Err(CycleError::InvalidPeck);
+        }
+        let spindle_on = pl_data.condition.intersects(
+            PlanCondition::PL_COND_FLAG_SPINDLE_CW | PlanCondition::PL_COND_FLAG_SPINDLE_CCW,
+        );
+        let needs_spindle = matches!(
+            self.kind,
+            CycleKind::Tap
+                | CycleKind::BoreSpindleStop
+                | CycleKind::BackBore
+                | CycleKind::BoreManual
+        );
+        if needs_spindle && !spindle_on {
+            return Err(CycleError::SpindleOff);
+        }
+        if self.kind == CycleKind::BackBore
+            && !(self.bottom < self.back_bore_top && self.back_bore_top <= self.r)
+        {
+            return Err(CycleError::InvalidBackBore);
+        }
+        Ok(())
+    }
+}
+
+/// collect steps and track the position and spindle
+struct Steps {
+    steps: Vec<CycleStep>,
+    position: Vec3<Scalar>,
+    condition: PlanCondition,
+    pl_data: PlanLineData,
+    /// taps must not follow the feed override
+    no_feed_override: bool,
+}
+
+impl Steps {
+    fn new(position: Vec3<Scalar>, pl_data: &PlanLineData) -> Self {
+        Self {
+            steps: Vec::new(),
+            position,
+            condition: pl_data.condition,
+            pl_data: *pl_data,
+            no_feed_override: false,
+        }
+    }
+
+    fn line(&mut self, target: Vec3<Scalar>, is_rapid: bool) {
+        if target == self.position {
+            return;
+        }
+        let mut pl_data = self.pl_data;
+        pl_data.condition = self.condition;
+        if is_rapid {
+            pl_data.condition |= PlanCondition::PL_COND_FLAG_RAPID_MOTION;
+        } else if self.no_feed_override {
+            pl_data.condition |= PlanCondition::PL_COND_FLAG_NO_FEED_OVERRIDE;
+        }
+        self.steps.push(CycleStep::Line(target, pl_data));
+        self.position = target;
+    }
+
+    fn rapid_to(&mut self, c: Scalar) {
+        let p = self.position;
+        self.line(Vec3::new(p.0, p.1, c), true);
+    }
+
+    fn rapid_xy(&mut self, xy: Vec2<Scalar>) {
+        self.line(Vec3::new(xy.0, xy.1, self.position.2), true);
+    }
+
+    fn feed_to(&mut self, c: Scalar) {
+        let p = self.position;
+        self.line(Vec3::new(p.0, p.1, c), false);
+    }
+
+    fn dwell(&mut self, seconds: Scalar) {
+        if seconds > 0. {
+            self.steps.push(CycleStep::Dwell(seconds));
+        }
+    }
+
+    fn stop_spindle(&mut self) {
+        self.condition -=
+            PlanCondition::PL_COND_FLAG_SPINDLE_CW | PlanCondition::PL_COND_FLAG_SPINDLE_CCW;
+    }
+
+    fn start_spindle(&mut self) {
+        self.condition = self.pl_data.condition;
+    }
+
+    fn reverse_spindle(&mut self) {
+        self.condition.toggle(
+            PlanCondition::PL_COND_FLAG_SPINDLE_CW | PlanCondition::PL_COND_FLAG_SPINDLE_CCW,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CannedCycle, CycleError, CycleKind, CycleStep, RetractMode};
+    use crate::Scalar;
+    use crate::{PlanCondition, PlanLineData, Vec2, Vec3};
+
+    fn feed() -> PlanLineData {
+        PlanLineData {
+            feed_rate: 100.,
+            spindle_speed: 1000.,
+            condition: PlanCondition::PL_COND_FLAG_SPINDLE_CW,
+        }
+    }
+
+    /// drill axis value and condition of each line, dwell is (-1, empty)
+    fn lines(steps: &[CycleStep]) -> Vec<(Scalar, PlanCondition)> {
+        steps
+            .iter()
+            .map(|s| match s {
+                CycleStep::Line(p, d) => (p.2, d.condition),
+                _ => (-1., PlanCondition::empty()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_peck() {
+        let rapid =
+            PlanCondition::PL_COND_FLAG_RAPID_MOTION | PlanCondition::PL_COND_FLAG_SPINDLE_CW;
+        let cw = PlanCondition::PL_COND_FLAG_SPINDLE_CW;
+        let current = Vec3::new(0., 0., 20.);
+        let hole = Vec2::new(5., 5.);
+
+        // G98 G83 Z-5 R1 Q3
+        let mut cycle = CannedCycle::new(CycleKind::Peck, RetractMode::Initial, 1., -5.);
+        assert_eq!(
+            cycle.expand(&current, hole, &feed()),
+            Err(CycleError::InvalidPeck)
+        );
+        cycle.peck = 3.;
+        let steps = cycle.expand(&current, hole, &feed()).unwrap();
+        assert_eq!(
+            steps[0],
+            CycleStep::Line(Vec3::new(5., 5., 20.), {
+                let mut d = feed();
+                d.condition = rapid;
+                d
+            })
+        );
+        let c = 1. - 3. + super::PECK_CLEARANCE;
+        assert_eq!(
+            lines(&steps[1..]),
+            vec![
+                (1., rapid),
+                (-2., cw),
+                (1., rapid),
+                (c, rapid),
+                (-5., cw),
+                (20., rapid)
+            ]
+        );
+
+        // G99 G73, no retract to R
+        cycle.kind = CycleKind::ChipBreak;
+        cycle.retract = RetractMode::RPlane;
+        let steps = cycle.expand(&current, hole, &feed()).unwrap();
+        assert_eq!(
+            lines(&steps[1..]),
+            vec![(1., rapid), (-2., cw), (c, rapid), (-5., cw), (1., rapid)]
+        );
+    }
+
+    #[test]
+    fn test_tap_and_bore() {
+        let current = Vec3::new(0., 0., 1.);
+        let hole = Vec2::new(0., 0.);
+        let cw = PlanCondition::PL_COND_FLAG_SPINDLE_CW;
+        let ccw = PlanCondition::PL_COND_FLAG_SPINDLE_CCW;
+        let nfo = PlanCondition::PL_COND_FLAG_NO_FEED_OVERRIDE;
+        let rapid = PlanCondition::PL_COND_FLAG_RAPID_MOTION;
+
+        let mut cycle = CannedCycle::new(CycleKind::Tap, RetractMode::RPlane, 1., -10.);
+        let steps = cycle.expand(&current, hole, &feed()).unwrap();
+        assert_eq!(lines(&steps), vec![(-10., cw | nfo), (1., ccw | nfo)]);
+        assert_eq!(
+            cycle.expand(&current, hole, &PlanLineData::default()),
+            Err(CycleError::SpindleOff)
+        );
+
+        // G86 with dwell, spindle stops at the bottom
+        cycle.kind = CycleKind::BoreSpindleStop;
+        cycle.dwell = 0.5;
+        let steps = cycle.expand(&current, hole, &feed()).unwrap();
+        assert_eq!(steps[1], CycleStep::Dwell(0.5));
+        assert_eq!(
+            lines(&steps),
+            vec![(-10., cw), (-1., PlanCondition::empty()), (1., rapid)]
+        );
+
+        // G88 pauses for manual retraction
+        cycle.kind = CycleKind::BoreManual;
+        let steps = cycle.expand(&current, hole, &feed()).unwrap();
+        assert_eq!(steps[2], CycleStep::Pause);
+
+        // G87 goes down through the offset with the spindle stopped
+        cycle.kind = CycleKind::BackBore;
+        cycle.back_bore_offset = Vec2::new(2., 0.);
+        assert_eq!(
+            cycle.expand(&current, hole, &feed()),
+            Err(CycleError::InvalidBackBore)
+        );
+        cycle.back_bore_top = -8.;
+        let steps = cycle.expand(&current, hole, &feed()).unwrap();
+        assert_eq!(
+            lines(&steps),
+            vec![
+                (1., rapid),
+                (-10., rapid),
+                (-10., rapid),
+                (-8., cw),
+                (-10., cw),
+                (-10., rapid),
+                (1., rapid),
+                (1., rapid)
+            ]
+        );
+
+        cycle.bottom = 2.;
+        assert_eq!(
+            cycle.expand(&current, hole, &feed()),
+            Err(CycleError::RBelowBottom)
+        );
+    }
+}
@@ -0,0 +1,210 @@
+//! coordinate systems, convert program position to the machine position that the planner expects.
+//!
+//! see "3.2.2 Coordinate Systems" in rs274ngc.pdf
+//!
+//! ```text
+//!     machine = program + work offset(G54..G59.3) + G92 offset + tool length offset
+//! ```
+use crate::{Scalar, Vec3};
+
+/// G54 ~ G59.3, with its index in [`WorkCoordinates`], e.g. `G10 L2 P1` means G54
+#[allow(non_camel_case_types)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CoordSystem {
+    G54 = 0,
+    G55 = 1,
+    G56 = 2,
+    G57 = 3,
+    G58 = 4,
+    G59 = 5,
+    G59_1 = 6,
+    G59_2 = 7,
+    G59_3 = 8,
+}
+
+impl CoordSystem {
+    /// number of the work coordinate systems
+    pub const COUNT: usize = 9;
+
+    /// from the P word of G10, 1 is G54 ... 9 is G59.3
+    pub fn from_p_number(p: u8) -> Option<Self> {
+        let cs = match p {
+            1 => Self::G54,
+            2 => Self::G55,
+            3 => Self::G56,
+            4 => Self::G57,
+            5 => Self::G58,
+            6 => Self::G59,
+            7 => Self::G59_1,
+            8 => Self::G59_2,
+            9 => Self::G59_3,
+            _ => return None,
+        };
+        Some(cs)
+    }
+
+    /// from the G word, e.g. (59, 1) is G59.1
+    pub fn from_code(code: (u16, u8)) -> Option<Self> {
+        match code {
+            (54..=58, 0) => Self::from_p_number((code.0 - 53) as u8),
+            (59, 0..=3) => Self::from_p_number(6 + code.1),
+            _ => None,
+        }
+    }
+}
+
+/// holds the work offsets, G92 offset and tool length offset. all values are in mm
+#[derive(Clone, Debug)]
+pub struct WorkCoordinates {
+    /// origin of each work coordinate system, in machine position
+    offsets: [Vec3<Scalar>; CoordSystem::COUNT],
+    active: CoordSystem,
+    /// G92 offset, applied on top of the active work offset
+    g92: Vec3<Scalar>,
+    /// false after G92.2, the G92 offset is kept but not applied
+    g92_applied: bool,
+    tool_length: Vec3<Scalar>,
+}
+
+impl core::default::Default for WorkCoordinates {
+    fn default() -> Self {
+        Self {
+            offsets: [Vec3::zero(); CoordSystem::COUNT],
+            active: CoordSystem::G54,
+            g92: Vec3::zero(),
+            g92_applied: true,
+            tool_length: Vec3::zero(),
+        }
+    }
+}
+
+impl WorkCoordinates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn active(&self) -> CoordSystem {
+        self.active
+    }
+
+    /// G54 ~ G59.3
+    pub fn select(&mut self, cs: CoordSystem) {
+        self.active = cs;
+    }
+
+    pub fn work_offset(&self, cs: CoordSystem) -> &Vec3<Scalar> {
+        &self.offsets[cs as usize]
+    }
+
+    /// G10 L2, origin of cs in machine position
+    pub fn set_work_offset(&mut self, cs: CoordSystem, origin: Vec3<Scalar>) {
+        self.offsets[cs as usize] = origin;
+    }
+
+    /// current G92 offset, zero if it is suspended
+    pub fn g92_offset(&self) -> Vec3<Scalar> {
+        if self.g92_applied {
+            self.g92
+        } else {
+            Vec3::zero()
+        }
+    }
+
+    /// G92, so that the machine position is the program position
+    ///
+    /// axes that are None keep their G92 offset
+    pub fn set_g92(&mut self, machine: &Vec3<Scalar>, program: [Option<Scalar>; 3]) {
+        let base = *machine - *self.work_offset(self.active) - self.tool_length;
+        let g92 = self.g92_offset();
+        let axis = |p: Option<Scalar>, base: Scalar, old: Scalar| p.map_or(old, |p| base - p);
+        self.g92 = Vec3::new(
+            axis(program[0], base.0, g92.0),
+            axis(program[1], base.1, g92.1),
+            axis(program[2], base.2, g92.2),
+        );
+        self.g92_applied = true;
+    }
+
+    /// G92.1, reset G92 offset to zero
+    pub fn clear_g92(&mut self) {
+        self.g92 = Vec3::zero();
+        self.g92_applied = true;
+    }
+
+    /// G92.2, G92 offset is kept but not applied
+    pub fn suspend_g92(&mut self) {
+        self.g92_applied = false;
+    }
+
+    /// G92.3, apply the kept G92 offset again
+    pub fn restore_g92(&mut self) {
+        self.g92_applied = true;
+    }
+
+    pub fn tool_length_offset(&self) -> &Vec3<Scalar> {
+        &self.tool_length
+    }
+
+    /// tool length offset, normally only its linear axis(Z) is not zero
+    pub fn set_tool_length_offset(&mut self, offset: Vec3<Scalar>) {
+        self.tool_length = offset;
+    }
+
+    /// sum of all offsets
+    pub fn total_offset(&self) -> Vec3<Scalar> {
+        *self.work_offset(self.active) + self.g92_offset() + self.tool_length
+    }
+
+    /// program position to machine position
+    #[inline]
+    pub fn to_machine(&self, program: &Vec3<Scalar>) -> Vec3<Scalar> {
+        *program + self.total_offset()
+    }
+
+    /// machine position to program position
+    #[inline]
+    pub fn to_program(&self, machine: &Vec3<Scalar>) -> Vec3<Scalar> {
+        *machine - self.total_offset()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CoordSystem, Vec3, WorkCoordinates};
+
+    #[test]
+    fn test_coord_system_code() {
+        assert_eq!(CoordSystem::from_code((54, 0)), Some(CoordSystem::G54));
+        assert_eq!(CoordSystem::from_code((58, 0)), Some(CoordSystem::G58));
+        assert_eq!(CoordSystem::from_code((59, 0)), Some(CoordSystem::G59));
+        assert_eq!(CoordSystem::from_code((59, 3)), Some(CoordSystem::G59_3));
+        assert_eq!(CoordSystem::from_code((59, 4)), None);
+        assert_eq!(CoordSystem::from_code((54, 1)), None);
+        assert_eq!(CoordSystem::from_p_number(7), Some(CoordSystem::G59_1));
+    }
+
+    #[test]
+    fn test_offsets() {
+        let mut wcs = WorkCoordinates::new();
+        wcs.set_work_offset(CoordSystem::G55, Vec3::new(100., 50., -10.));
+        wcs.select(CoordSystem::G55);
+        wcs.set_tool_length_offset(Vec3::new(0., 0., 20.));
+        assert_eq!(
+            wcs.to_machine(&Vec3::new(1., 2., 3.)),
+            Vec3::new(101., 52., 13.)
+        );
+
+        // current machine position becomes program (0, 0, _)
+        let machine = Vec3::new(110., 60., 10.);
+        wcs.set_g92(&machine, [Some(0.), Some(0.), None]);
+        assert_eq!(wcs.g92_offset(), Vec3::new(10., 10., 0.));
+        assert_eq!(wcs.to_program(&machine), Vec3::new(0., 0., 0.));
+
+        wcs.suspend_g92();
+        assert_eq!(wcs.to_program(&machine), Vec3::new(10., 10., 0.));
+        wcs.restore_g92();
+        assert_eq!(wcs.to_program(&machine), Vec3::new(0., 0., 0.));
+        wcs.clear_g92();
+        assert_eq!(wcs.g92_offset(), Vec3::zero());
+    }
+}
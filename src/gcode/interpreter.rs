@@ -0,0 +1,1879 @@
+//! modal-state interpreter, it executes parsed blocks and drives the planner.
+//!
+//! see "3.4 Modal Groups" and "3.8 Order of Execution" in rs274ngc.pdf
+use super::canned::{CannedCycle, CycleError, CycleKind, CycleStep, RetractMode};
+use super::coordinate::{CoordSystem, WorkCoordinates};
+use super::parser::{Block, Letter, ParseError, Parser};
+use crate::{
+    Arc, ArcError, ArcSettings, Bezier, CanonPlane, CompError, CutterComp, Length, Nurbs,
+    NurbsError, PlanCondition, PlanError, PlanLineData, Planer, RadiusTolerance, Scalar, Segment,
+    Side, SplinePoint, Tool, Vec2, Vec3, Velocity,
+};
+use alloc::{vec, vec::Vec};
+
+/// where the interpreter sends its motions. normally it is [`Planer`].
+pub trait CanonMotion {
+    /// straight move to target, target is machine position in mm
+    fn line(&mut self, target: &Vec3<Scalar>, pl_data: &PlanLineData) -> Result<(), GCodeError>;
+
+    /// G53 move, the target is the axis words as they are. the default is [`Self::line`]
+    fn machine_line(
+        &mut self,
+        target: &Vec3<Scalar>,
+        pl_data: &PlanLineData,
+    ) -> Result<(), GCodeError> {
+        self.line(target, pl_data)
+    }
+
+    /// tool of the tool table, used by G43 and G41/G42. tool 0 is no tool
+    fn tool(&self, number: u16) -> Option<Tool> {
+        (number == 0).then(Tool::default)
+    }
+
+    /// G4 and the dwell of canned cycles, in seconds. the default ignores it, [`Planer`] has no dwell
+    fn dwell(&mut self, _seconds: Scalar) -> Result<(), GCodeError> {
+        Ok(())
+    }
+
+    /// wait until the operator resumes, e.g. the manual retraction of G88
+    fn pause(&mut self) -> Result<(), GCodeError> {
+        Ok(())
+    }
+
+    /// how arcs and splines are split into lines
+    fn arc_settings(&self) -> ArcSettings {
+        ArcSettings::default()
+    }
+
+    /// max feed rate on a curve with the curvature (1/mm), the default has no limit
+    fn curve_velocity(&self, _curvature: Scalar) -> Velocity {
+        Velocity::from_mm_per_min(Scalar::MAX)
+    }
+
+    /// reject a motion by its bounding box (min, max) in machine position before any of its lines, e.g. an arc
+    /// that bulges out of the travel. the default accepts all
+    fn check_travel(&self, _min: &Vec3<Scalar>, _max: &Vec3<Scalar>) -> Result<(), GCodeError> {
+        Ok(())
+    }
+}
+
+impl CanonMotion for Planer {
+    fn line(&mut self, target: &Vec3<Scalar>, pl_data: &PlanLineData) -> Result<(), GCodeError> {
+        match self.push_normal_motion(&Vec3::<Length>::from_mm(*target), pl_data) {
+            // a zero-length block is not an error in g-code
+            Ok(()) | Err(PlanError::ZeroLength) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn tool(&self, number: u16) -> Option<Tool> {
+        self.cfg.tools.get(number)
+    }
+
+    fn arc_settings(&self) -> ArcSettings {
+        self.cfg.arc
+    }
+
+    fn curve_velocity(&self, curvature: Scalar) -> Velocity {
+        self.cfg.get_max_curve_velocity(curvature)
+    }
+
+    fn check_travel(&self, min: &Vec3<Scalar>, max: &Vec3<Scalar>) -> Result<(), GCodeError> {
+        Ok(self.cfg.check_soft_limits(min, max)?)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModalGroup {
+    /// group 0: G4 G10 G53 G92 G92.1 G92.2 G92.3
+    NonModal,
+    /// group 1: G0 G1 G2 G3 G5 G5.1 G73 G80 ~ G89
+    Motion,
+    /// group 2: G17 G18 G19
+    Plane,
+    /// group 3: G90 G91
+    Distance,
+    /// group 5: G93 G94
+    FeedMode,
+    /// group 6: G20 G21
+    Units,
+    /// group 7: G40 G41 G42 G41.1 G42.1
+    CutterComp,
+    /// group 8: G43 G43.1 G49
+    ToolLength,
+    /// group 10: G98 G99
+    RetractMode,
+    /// group 12: G54 ~ G59.3
+    CoordSystem,
+    /// M group 4: M0 M1 M2 M30
+    Stopping,
+    /// M group 6: M6
+    ToolChange,
+    /// M group 7: M3 M4 M5
+    Spindle,
+    /// M group 8: M7 M8 M9
+    Coolant,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GCodeError {
+    Parse(ParseError),
+    /// the G word is not supported
+    UnsupportedGCode(Scalar),
+    /// the M word is not supported
+    UnsupportedMCode(Scalar),
+    /// two words of the same modal group in one block
+    ModalGroupConflict(ModalGroup),
+    /// the word is not used by any command of the block
+    UnusedWord(Letter),
+    /// feed motion without a (positive) feed rate, in G93 mode F must be in every feed motion block
+    MissingFeedRate,
+    /// arc without an axis word of the plane, in radius format
+    MissingAxis,
+    /// arc without offset words of the plane nor R word
+    MissingArcOffset,
+    /// arc with both R word and offset words
+    MixedArcFormat,
+    Arc(ArcError),
+    /// P word of an arc is not a positive integer
+    InvalidTurns,
+    /// axis words are used by G10/G92 and a motion in the same block
+    AxisWordConflict,
+    /// G10 without L2/L20, or P is not in [0,9]
+    InvalidG10,
+    /// G53 is used without G0 or G1 being active, while cutter radius compensation is on, or in a
+    /// [`super::WorkPlaneMotion`]
+    InvalidG53Motion,
+    CutterComp(CompError),
+    /// cutter radius compensation is only supported in XY plane
+    CutterCompPlane,
+    /// cutter radius compensation is turned on while it is already on
+    CutterCompAlreadyOn,
+    /// G41.1/G42.1 without D word
+    MissingCompDiameter,
+    /// the T, H or D word is not a tool number, or the tool is not in the tool table
+    UnknownTool(Scalar),
+    CannedCycle(CycleError),
+    /// G4 without a non-negative P word
+    InvalidDwell,
+    /// G5/G5.1 is only supported in XY plane
+    SplinePlane,
+    /// G5 without P and Q words, or without I and J words when the previous motion is not G5.
+    /// G5.1 without I or J word
+    MissingSplineOffset,
+    /// G5/G5.1/G5.2 while cutter radius compensation is on
+    SplineCutterComp,
+    /// G5.2 with L not an integer above 1, a weight not positive, G5.3 without G5.2,
+    /// or a block between G5.2 and G5.3 with a G word
+    InvalidNurbs,
+    Nurbs(NurbsError),
+    /// the motion is rejected by the planner, e.g. by its soft limits
+    Plan(PlanError),
+}
+
+impl From<ParseError> for GCodeError {
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl From<CompError> for GCodeError {
+    fn from(e: CompError) -> Self {
+        Self::CutterComp(e)
+    }
+}
+
+impl From<ArcError> for GCodeError {
+    fn from(e: ArcError) -> Self {
+        Self::Arc(e)
+    }
+}
+
+impl From<NurbsError> for GCodeError {
+    fn from(e: NurbsError) -> Self {
+        Self::Nurbs(e)
+    }
+}
+
+impl From<PlanError> for GCodeError {
+    fn from(e: PlanError) -> Self {
+        Self::Plan(e)
+    }
+}
+
+impl From<CycleError> for GCodeError {
+    fn from(e: CycleError) -> Self {
+        Self::CannedCycle(e)
+    }
+}
+
+/// group 1
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MotionMode {
+    /// G0
+    Rapid,
+    /// G1
+    Linear,
+    /// G2
+    ArcCw,
+    /// G3
+    ArcCcw,
+    /// G5
+    CubicSpline,
+    /// G5.1
+    QuadraticSpline,
+    /// G80, axis words are not allowed
+    Off,
+    /// G73 G81 ~ G89
+    Canned(CycleKind),
+}
+
+/// group 3
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DistanceMode {
+    /// G90
+    Absolute,
+    /// G91
+    Incremental,
+}
+
+/// group 5
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FeedMode {
+    /// G93, F means the move should be completed in 1/F minutes
+    InverseTime,
+    /// G94
+    UnitsPerMinute,
+}
+
+/// group 6
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Units {
+    /// G20
+    Inches,
+    /// G21
+    Millimeters,
+}
+
+impl Units {
+    /// a length word in self unit
+    #[inline]
+    pub fn length(&self, v: Scalar) -> Length {
+        match self {
+            Self::Inches => Length::from_inch(v),
+            Self::Millimeters => Length::from_mm(v),
+        }
+    }
+
+    /// a feed rate word in self unit per minute
+    #[inline]
+    pub fn velocity(&self, v: Scalar) -> Velocity {
+        match self {
+            Self::Inches => Velocity::from_inch_per_min(v),
+            Self::Millimeters => Velocity::from_mm_per_min(v),
+        }
+    }
+
+    /// convert a length in self unit to mm
+    #[inline]
+    pub fn to_mm(&self, v: Scalar) -> Scalar {
+        self.length(v).mm()
+    }
+}
+
+/// M group 7
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SpindleState {
+    /// M3
+    Cw,
+    /// M4
+    Ccw,
+    /// M5
+    Off,
+}
+
+/// group 8
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ToolLengthMode {
+    /// G49
+    Off,
+    /// G43 Hn, offset is the length of tool n
+    Tool(u16),
+    /// G43.1, offset is given by the Z word
+    Dynamic,
+}
+
+/// current modal state, default is the state at program start
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModalState {
+    pub motion: MotionMode,
+    pub plane: CanonPlane,
+    pub distance: DistanceMode,
+    pub feed_mode: FeedMode,
+    pub units: Units,
+    pub spindle: SpindleState,
+    /// M7
+    pub coolant_mist: bool,
+    /// M8
+    pub coolant_flood: bool,
+    /// group 7, None is G40
+    pub cutter_comp: Option<Side>,
+    pub tool_length: ToolLengthMode,
+    /// group 10
+    pub retract: RetractMode,
+    /// mm/min in G94 mode, 1/min in G93 mode
+    pub feed_rate: Scalar,
+    pub spindle_speed: Scalar,
+}
+
+impl core::default::Default for ModalState {
+    fn default() -> Self {
+        Self {
+            motion: MotionMode::Rapid,
+            plane: CanonPlane::CanonPlaneXY,
+            distance: DistanceMode::Absolute,
+            feed_mode: FeedMode::UnitsPerMinute,
+            units: Units::Millimeters,
+            spindle: SpindleState::Off,
+            coolant_mist: false,
+            coolant_flood: false,
+            cutter_comp: None,
+            tool_length: ToolLengthMode::Off,
+            retract: RetractMode::Initial,
+            feed_rate: 0.,
+            spindle_speed: 0.,
+        }
+    }
+}
+
+impl ModalState {
+    /// planner conditions for a motion under this state
+    pub fn condition(&self, is_rapid: bool) -> PlanCondition {
+        let mut condition = PlanCondition::default();
+        if is_rapid {
+            condition |= PlanCondition::PL_COND_FLAG_RAPID_MOTION;
+        } else if self.feed_mode == FeedMode::InverseTime {
+            condition |= PlanCondition::PL_COND_FLAG_INVERSE_TIME;
+        }
+        match self.spindle {
+            SpindleState::Cw => condition |= PlanCondition::PL_COND_FLAG_SPINDLE_CW,
+            SpindleState::Ccw => condition |= PlanCondition::PL_COND_FLAG_SPINDLE_CCW,
+            SpindleState::Off => {}
+        }
+        if self.coolant_flood {
+            condition |= PlanCondition::PL_COND_FLAG_COOLANT_FLOOD;
+        }
+        if self.coolant_mist {
+            condition |= PlanCondition::PL_COND_FLAG_COOLANT_MIST;
+        }
+        condition
+    }
+
+    pub fn pl_data(&self, is_rapid: bool) -> PlanLineData {
+        PlanLineData {
+            feed_rate: self.feed_rate,
+            spindle_speed: self.spindle_speed,
+            condition: self.condition(is_rapid),
+        }
+    }
+}
+
+/// group 0 commands
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum NonModal {
+    /// G4
+    Dwell,
+    /// G10
+    SetCoordData,
+    /// G53
+    MachineCoords,
+    /// G92
+    SetG92,
+    /// G92.1
+    ClearG92,
+    /// G92.2
+    SuspendG92,
+    /// G92.3
+    RestoreG92,
+}
+
+/// keep the modal state, coordinate systems and the current position between blocks
+///
+/// example
+/// ```
+/// use ngc_geometry::{gcode::Interpreter, CNCCfgs, Planer};
+/// let mut planer = Planer::new(CNCCfgs::default());
+/// let mut interp = Interpreter::new();
+/// interp.run("G21 G90 G1 X10 Y5 F300\nG2 X20 Y5 R5", &mut planer).unwrap();
+/// assert!(planer.len() > 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Interpreter {
+    modal: ModalState,
+    coords: WorkCoordinates,
+    /// current machine position in mm, it is the programmed position when cutter radius compensation is on
+    position: Vec3<Scalar>,
+    /// cutter radius compensation engine, it exists between G41/G42 and G40
+    comp: Option<CutterComp<PlanLineData>>,
+    /// tool in the spindle
+    tool: u16,
+    /// tool selected by T word, it goes to the spindle at M6
+    selected_tool: u16,
+    /// sticky words of canned cycles
+    cycle: CycleWords,
+    /// P, Q of the last G5 in mm, the next G5 without I, J starts in this direction
+    spline_end: Option<Vec2<Scalar>>,
+    /// control points collected between G5.2 and G5.3
+    nurbs: Option<NurbsWords>,
+}
+
+/// G5.2 control points in machine position, the first one is the position at G5.2
+#[derive(Clone, Debug)]
+struct NurbsWords {
+    /// L word, degree + 1
+    order: usize,
+    points: Vec<Vec3<Scalar>>,
+    weights: Vec<Scalar>,
+}
+
+/// canned cycle words that are kept until the motion mode changes, values are in mm
+#[derive(Clone, Copy, Debug, Default)]
+struct CycleWords {
+    r: Option<Scalar>,
+    /// drill axis word
+    depth: Option<Scalar>,
+    peck: Scalar,
+    /// seconds
+    dwell: Scalar,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn modal(&self) -> &ModalState {
+        &self.modal
+    }
+
+    pub fn coordinates(&self) -> &WorkCoordinates {
+        &self.coords
+    }
+
+    /// e.g. to restore work offsets saved by the application
+    pub fn coordinates_mut(&mut self) -> &mut WorkCoordinates {
+        &mut self.coords
+    }
+
+    /// tool in the spindle
+    pub fn tool(&self) -> u16 {
+        self.tool
+    }
+
+    /// tool selected by the last T word
+    pub fn selected_tool(&self) -> u16 {
+        self.selected_tool
+    }
+
+    /// sync the tool in the spindle, e.g. at startup
+    pub fn set_tool(&mut self, tool: u16) {
+        self.tool = tool;
+    }
+
+    /// current machine position in mm
+    pub fn position(&self) -> &Vec3<Scalar> {
+        &self.position
+    }
+
+    /// current position in the active coordinate system, in mm
+    pub fn program_position(&self) -> Vec3<Scalar> {
+        self.coords.to_program(&self.position)
+    }
+
+    /// sync machine position, e.g. after homing
+    pub fn set_position(&mut self, position: Vec3<Scalar>) {
+        self.position = position;
+    }
+
+    /// parse and execute a whole program, stop at the first error
+    pub fn run<M: CanonMotion>(
+        &mut self,
+        program: &str,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        for block in Parser::new(program) {
+            self.execute(&block?, machine)?;
+        }
+        Ok(())
+    }
+
+    /// execute one block in rs274ngc order: feed mode, F, S, T, M6, spindle, coolant, plane, units,
+    /// cutter radius compensation, tool length offset, coordinate system, distance, G10/G92, motion
+    ///
+    /// on an error the modal state, the tools, the offsets and the cycle words are restored to the ones before
+    /// the block, so they match the position that the block did not reach
+    pub fn execute<M: CanonMotion>(
+        &mut self,
+        block: &Block,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        let saved = (
+            self.modal,
+            self.coords.clone(),
+            self.tool,
+            self.selected_tool,
+            self.cycle,
+            self.spline_end,
+        );
+        let res = self.execute_block(block, machine);
+        if res.is_err() {
+            (
+                self.modal,
+                self.coords,
+                self.tool,
+                self.selected_tool,
+                self.cycle,
+                self.spline_end,
+            ) = saved;
+        }
+        res
+    }
+
+    fn execute_block<M: CanonMotion>(
+        &mut self,
+        block: &Block,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        let nurbs = block
+            .g_words()
+            .any(|w| matches!(w.code(), Some((5, 2)) | Some((5, 3))));
+        if self.nurbs.is_some() || nurbs {
+            return self.nurbs_block(block, machine);
+        }
+
+        let mut modal = self.modal;
+        let mut motion: Option<MotionMode> = None;
+        let mut non_modal: Option<NonModal> = None;
+        let mut coord_system: Option<CoordSystem> = None;
+        let mut cutter_comp: Option<Option<Side>> = None;
+        // G41/G42 take the radius from the tool table, G41.1/G42.1 from D word
+        let mut comp_by_tool = false;
+        let mut tool_length: Option<ToolLengthMode> = None;
+        let mut groups = 0_u32;
+
+        for word in block.g_words() {
+            let group = match word.code() {
+                Some((0, 0)) => {
+                    motion = Some(MotionMode::Rapid);
+                    ModalGroup::Motion
+                }
+                Some((1, 0)) => {
+                    motion = Some(MotionMode::Linear);
+                    ModalGroup::Motion
+                }
+                Some((2, 0)) => {
+                    motion = Some(MotionMode::ArcCw);
+                    ModalGroup::Motion
+                }
+                Some((3, 0)) => {
+                    motion = Some(MotionMode::ArcCcw);
+                    ModalGroup::Motion
+                }
+                Some((5, 0)) => {
+                    motion = Some(MotionMode::CubicSpline);
+                    ModalGroup::Motion
+                }
+                Some((5, 1)) => {
+                    motion = Some(MotionMode::QuadraticSpline);
+                    ModalGroup::Motion
+                }
+                Some((17, 0)) => {
+                    modal.plane = CanonPlane::CanonPlaneXY;
+                    ModalGroup::Plane
+                }
+                Some((18, 0)) => {
+                    modal.plane = CanonPlane::CanonPlaneXZ;
+                    ModalGroup::Plane
+                }
+                Some((19, 0)) => {
+                    modal.plane = CanonPlane::CanonPlaneYZ;
+                    ModalGroup::Plane
+                }
+                Some((20, 0)) => {
+                    modal.units = Units::Inches;
+                    ModalGroup::Units
+                }
+                Some((21, 0)) => {
+                    modal.units = Units::Millimeters;
+                    ModalGroup::Units
+                }
+                Some((90, 0)) => {
+                    modal.distance = DistanceMode::Absolute;
+                    ModalGroup::Distance
+                }
+                Some((91, 0)) => {
+                    modal.distance = DistanceMode::Incremental;
+                    ModalGroup::Distance
+                }
+                Some((93, 0)) => {
+                    modal.feed_mode = FeedMode::InverseTime;
+                    ModalGroup::FeedMode
+                }
+                Some((94, 0)) => {
+                    modal.feed_mode = FeedMode::UnitsPerMinute;
+                    ModalGroup::FeedMode
+                }
+                Some((4, 0)) => {
+                    non_modal = Some(NonModal::Dwell);
+                    ModalGroup::NonModal
+                }
+                Some((80, 0)) => {
+                    motion = Some(MotionMode::Off);
+                    ModalGroup::Motion
+                }
+                Some((98, 0)) => {
+                    modal.retract = RetractMode::Initial;
+                    ModalGroup::RetractMode
+                }
+                Some((99, 0)) => {
+                    modal.retract = RetractMode::RPlane;
+                    ModalGroup::RetractMode
+                }
+                Some((10, 0)) => {
+                    non_modal = Some(NonModal::SetCoordData);
+                    ModalGroup::NonModal
+                }
+                Some((40, 0)) => {
+                    cutter_comp = Some(None);
+                    ModalGroup::CutterComp
+                }
+                Some((41, 0)) => {
+                    cutter_comp = Some(Some(Side::Left));
+                    comp_by_tool = true;
+                    ModalGroup::CutterComp
+                }
+                Some((42, 0)) => {
+                    cutter_comp = Some(Some(Side::Right));
+                    comp_by_tool = true;
+                    ModalGroup::CutterComp
+                }
+                Some((43, 0)) => {
+                    // tool number is resolved after T and M6
+                    tool_length = Some(ToolLengthMode::Tool(0));
+                    ModalGroup::ToolLength
+                }
+                Some((43, 1)) => {
+                    tool_length = Some(ToolLengthMode::Dynamic);
+                    ModalGroup::ToolLength
+                }
+                Some((49, 0)) => {
+                    tool_length = Some(ToolLengthMode::Off);
+                    ModalGroup::ToolLength
+                }
+                Some((41, 1)) => {
+                    cutter_comp = Some(Some(Side::Left));
+                    ModalGroup::CutterComp
+                }
+                Some((42, 1)) => {
+                    cutter_comp = Some(Some(Side::Right));
+                    ModalGroup::CutterComp
+                }
+                Some((53, 0)) => {
+                    non_modal = Some(NonModal::MachineCoords);
+                    ModalGroup::NonModal
+                }
+                Some((92, 0)) => {
+                    non_modal = Some(NonModal::SetG92);
+                    ModalGroup::NonModal
+                }
+                Some((92, 1)) => {
+                    non_modal = Some(NonModal::ClearG92);
+                    ModalGroup::NonModal
+                }
+                Some((92, 2)) => {
+                    non_modal = Some(NonModal::SuspendG92);
+                    ModalGroup::NonModal
+                }
+                Some((92, 3)) => {
+                    non_modal = Some(NonModal::RestoreG92);
+                    ModalGroup::NonModal
+                }
+                Some(code) => {
+                    if let Some(cs) = CoordSystem::from_code(code) {
+                        coord_system = Some(cs);
+                        ModalGroup::CoordSystem
+                    } else if let Some(kind) = CycleKind::from_code(code) {
+                        motion = Some(MotionMode::Canned(kind));
+                        ModalGroup::Motion
+                    } else {
+                        return Err(GCodeError::UnsupportedGCode(word.value));
+                    }
+                }
+                None => return Err(GCodeError::UnsupportedGCode(word.value)),
+            };
+            mark_group(&mut groups, group)?;
+        }
+        let mut program_end = false;
+        let mut tool_change = false;
+        for word in block.m_words() {
+            let group = match word.code() {
+                Some((2, 0)) | Some((30, 0)) => {
+                    program_end = true;
+                    ModalGroup::Stopping
+                }
+                Some((6, 0)) => {
+                    tool_change = true;
+                    ModalGroup::ToolChange
+                }
+                Some((3, 0)) => {
+                    modal.spindle = SpindleState::Cw;
+                    ModalGroup::Spindle
+                }
+                Some((4, 0)) => {
+                    modal.spindle = SpindleState::Ccw;
+                    ModalGroup::Spindle
+                }
+                Some((5, 0)) => {
+                    modal.spindle = SpindleState::Off;
+                    ModalGroup::Spindle
+                }
+                Some((7, 0)) => {
+                    modal.coolant_mist = true;
+                    ModalGroup::Coolant
+                }
+                Some((8, 0)) => {
+                    modal.coolant_flood = true;
+                    ModalGroup::Coolant
+                }
+                Some((9, 0)) => {
+                    modal.coolant_mist = false;
+                    modal.coolant_flood = false;
+                    ModalGroup::Coolant
+                }
+                _ => return Err(GCodeError::UnsupportedMCode(word.value)),
+            };
+            mark_group(&mut groups, group)?;
+        }
+
+        // F and S, F is in the units of the block
+        if let Some(f) = block.get(Letter::F) {
+            modal.feed_rate = match modal.feed_mode {
+                FeedMode::InverseTime => f,
+                FeedMode::UnitsPerMinute => modal.units.velocity(f).mm_per_min(),
+            };
+        }
+        if let Some(s) = block.get(Letter::S) {
+            modal.spindle_speed = s;
+        }
+        let selected_tool = match block.get(Letter::T) {
+            Some(t) => tool_number(t)?,
+            None => self.selected_tool,
+        };
+        let tool = if tool_change {
+            selected_tool
+        } else {
+            self.tool
+        };
+
+        let has_axis = [Letter::X, Letter::Y, Letter::Z]
+            .iter()
+            .any(|l| block.has(*l));
+        if let Some(m) = motion {
+            modal.motion = m;
+        }
+        let is_arc = matches!(modal.motion, MotionMode::ArcCw | MotionMode::ArcCcw);
+        let is_spline = matches!(
+            modal.motion,
+            MotionMode::CubicSpline | MotionMode::QuadraticSpline
+        );
+        let canned = match modal.motion {
+            MotionMode::Canned(kind) => Some(kind),
+            _ => None,
+        };
+        // G10, G92 and G43.1 use the axis words, so there is no motion
+        let dynamic_tool_length = tool_length == Some(ToolLengthMode::Dynamic);
+        let axis_consumed = dynamic_tool_length
+            || matches!(
+                non_modal,
+                Some(NonModal::SetCoordData) | Some(NonModal::SetG92)
+            );
+        if axis_consumed && motion.is_some() {
+            return Err(GCodeError::AxisWordConflict);
+        }
+        let axis_used = axis_consumed || modal.motion != MotionMode::Off;
+        let moving = !axis_consumed && (has_axis || (motion.is_some() && is_arc));
+        let machine_coords = non_modal == Some(NonModal::MachineCoords);
+        if machine_coords
+            && (is_arc || is_spline || canned.is_some() || !moving || self.comp.is_some())
+        {
+            return Err(GCodeError::InvalidG53Motion);
+        }
+        let comp_on = matches!(cutter_comp, Some(Some(_)));
+        for word in block.words.iter() {
+            let used = match word.letter {
+                Letter::G | Letter::M | Letter::F | Letter::S | Letter::T => true,
+                Letter::X | Letter::Y => axis_used && !dynamic_tool_length,
+                Letter::Z => axis_used,
+                Letter::H => matches!(tool_length, Some(ToolLengthMode::Tool(_))),
+                Letter::I | Letter::J => {
+                    ((is_arc || is_spline) && moving) || canned == Some(CycleKind::BackBore)
+                }
+                Letter::K => (is_arc && moving) || canned == Some(CycleKind::BackBore),
+                Letter::R => (is_arc && moving) || canned.is_some(),
+                Letter::Q => {
+                    canned.is_some() || (modal.motion == MotionMode::CubicSpline && moving)
+                }
+                Letter::L => non_modal == Some(NonModal::SetCoordData) || canned.is_some(),
+                Letter::P => {
+                    matches!(
+                        non_modal,
+                        Some(NonModal::SetCoordData) | Some(NonModal::Dwell)
+                    ) || canned.is_some()
+                        // turns of a helix, cutter radius compensation works on single turn arcs only
+                        || (is_arc && moving && self.comp.is_none() && !comp_on)
+                        || (modal.motion == MotionMode::CubicSpline && moving)
+                }
+                Letter::D => comp_on,
+                _ => false,
+            };
+            if !used {
+                return Err(GCodeError::UnusedWord(word.letter));
+            }
+        }
+
+        if let Some(Some(side)) = cutter_comp {
+            if self.comp.is_some() {
+                return Err(GCodeError::CutterCompAlreadyOn);
+            }
+            if modal.plane != CanonPlane::CanonPlaneXY {
+                return Err(GCodeError::CutterCompPlane);
+            }
+            let radius = if comp_by_tool {
+                let n = match block.get(Letter::D) {
+                    Some(d) => tool_number(d)?,
+                    None => tool,
+                };
+                lookup_tool(machine, n)?.radius()
+            } else {
+                let d = block
+                    .get(Letter::D)
+                    .ok_or(GCodeError::MissingCompDiameter)?;
+                modal.units.to_mm(d) * 0.5
+            };
+            self.comp = Some(CutterComp::new(radius, side, self.position));
+        }
+        if self.comp.is_some() && modal.plane != CanonPlane::CanonPlaneXY {
+            return Err(GCodeError::CutterCompPlane);
+        }
+        if let Some(side) = cutter_comp {
+            modal.cutter_comp = side;
+        }
+        let tool_length_offset = match tool_length {
+            Some(ToolLengthMode::Tool(_)) => {
+                let n = match block.get(Letter::H) {
+                    Some(h) => tool_number(h)?,
+                    None => tool,
+                };
+                modal.tool_length = ToolLengthMode::Tool(n);
+                Some(lookup_tool(machine, n)?.total_length())
+            }
+            Some(ToolLengthMode::Dynamic) => {
+                modal.tool_length = ToolLengthMode::Dynamic;
+                Some(modal.units.to_mm(block.get(Letter::Z).unwrap_or(0.)))
+            }
+            Some(ToolLengthMode::Off) => {
+                modal.tool_length = ToolLengthMode::Off;
+                Some(0.)
+            }
+            None => None,
+        };
+
+        if canned.is_some() {
+            let mm = |l: Letter| block.get(l).map(|v| modal.units.to_mm(v));
+            let words = &mut self.cycle;
+            words.r = mm(Letter::R).or(words.r);
+            words.depth = mm(drill_axis(modal.plane)).or(words.depth);
+            words.peck = mm(Letter::Q).unwrap_or(words.peck);
+            words.dwell = block.get(Letter::P).unwrap_or(words.dwell);
+        } else {
+            self.cycle = CycleWords::default();
+        }
+
+        self.modal = modal;
+        self.selected_tool = selected_tool;
+        self.tool = tool;
+        if let Some(length) = tool_length_offset {
+            self.coords
+                .set_tool_length_offset(Vec3::new(0., 0., length));
+        }
+        if let Some(cs) = coord_system {
+            self.coords.select(cs);
+        }
+        match non_modal {
+            Some(NonModal::Dwell) => match block.get(Letter::P) {
+                Some(p) if p >= 0. => machine.dwell(p)?,
+                _ => return Err(GCodeError::InvalidDwell),
+            },
+            Some(NonModal::SetCoordData) => self.set_coord_data(block)?,
+            Some(NonModal::SetG92) => {
+                let axes = self.axis_words(block);
+                self.coords.set_g92(&self.position, axes);
+            }
+            Some(NonModal::ClearG92) => self.coords.clear_g92(),
+            Some(NonModal::SuspendG92) => self.coords.suspend_g92(),
+            Some(NonModal::RestoreG92) => self.coords.restore_g92(),
+            Some(NonModal::MachineCoords) | None => {}
+        }
+
+        let comp_off = cutter_comp == Some(None);
+        if let (Some(kind), true) = (canned, moving) {
+            self.canned_cycle(kind, block, machine)?;
+        } else if moving {
+            let target = self.target(block, machine_coords);
+            let is_rapid = modal.motion == MotionMode::Rapid;
+            if !is_rapid {
+                self.check_feed_rate(block)?;
+            }
+            let pl_data = self.modal.pl_data(is_rapid);
+            // only a G5 right after a G5 may omit I, J
+            let spline_end = self.spline_end.take();
+            match (self.comp.take(), comp_off) {
+                (Some(comp), _) if is_spline => {
+                    self.comp = Some(comp);
+                    return Err(GCodeError::SplineCutterComp);
+                }
+                (None, _) if is_spline => {
+                    let curve = self.spline(target, spline_end, block)?;
+                    if modal.motion == MotionMode::CubicSpline {
+                        let [.., c2, end] = curve.control_points();
+                        self.spline_end = Some((c2 - end).plane());
+                    }
+                    self.emit_spline(curve, &pl_data, machine)?;
+                }
+                (Some(comp), true) => {
+                    // exit move
+                    if is_arc {
+                        self.comp = Some(comp);
+                        return Err(GCodeError::CutterComp(CompError::ArcExit));
+                    }
+                    for (seg, data) in comp.finish(Some(target), pl_data) {
+                        self.emit(seg, &data, machine)?;
+                    }
+                }
+                (Some(mut comp), false) => {
+                    let seg = if is_arc {
+                        let arc =
+                            self.arc(target, modal.motion == MotionMode::ArcCcw, false, block)?;
+                        Segment::Arc {
+                            start: self.position,
+                            end: target,
+                            center: arc.center(),
+                            turn_ccw: arc.turn_ccw(),
+                        }
+                    } else {
+                        Segment::Line {
+                            start: self.position,
+                            end: target,
+                        }
+                    };
+                    let out = comp.push(seg, pl_data);
+                    self.comp = Some(comp);
+                    for (seg, data) in out? {
+                        self.emit(seg, &data, machine)?;
+                    }
+                }
+                (None, _) if is_arc => {
+                    let spiral = machine.arc_settings().spiral;
+                    let arc =
+                        self.arc(target, modal.motion == MotionMode::ArcCcw, spiral, block)?;
+                    self.emit_arc(arc, &pl_data, machine)?;
+                }
+                (None, _) if machine_coords => machine.machine_line(&target, &pl_data)?,
+                (None, _) => machine.line(&target, &pl_data)?,
+            }
+            self.position = target;
+        } else if comp_off || program_end {
+            if let Some(comp) = self.comp.take() {
+                for (seg, data) in comp.finish(None, self.modal.pl_data(false)) {
+                    self.emit(seg, &data, machine)?;
+                }
+            }
+        }
+
+        if program_end {
+            // rs274ngc "3.6.1 Program Stop and Program End"
+            self.modal = ModalState {
+                feed_rate: self.modal.feed_rate,
+                spindle_speed: self.modal.spindle_speed,
+                units: self.modal.units,
+                tool_length: self.modal.tool_length,
+                motion: MotionMode::Linear,
+                ..ModalState::default()
+            };
+            self.comp = None;
+            self.cycle = CycleWords::default();
+            self.coords.select(CoordSystem::G54);
+            self.coords.suspend_g92();
+        }
+        Ok(())
+    }
+
+    /// X, Y, Z words converted to mm
+    fn axis_words(&self, block: &Block) -> [Option<Scalar>; 3] {
+        [Letter::X, Letter::Y, Letter::Z].map(|l| block.get(l).map(|v| self.modal.units.to_mm(v)))
+    }
+
+    /// target machine position in mm, axis words not in the block keep current value.
+    ///
+    /// machine_coords means G53, the axis words are absolute machine position
+    fn target(&self, block: &Block, machine_coords: bool) -> Vec3<Scalar> {
+        let words = self.axis_words(block);
+        let axis = |word: Option<Scalar>, current: Scalar, absolute: bool| -> Scalar {
+            match word {
+                Some(v) if absolute => v,
+                Some(v) => current + v,
+                None => current,
+            }
+        };
+        let target = |p: &Vec3<Scalar>, absolute: bool| {
+            Vec3::new(
+                axis(words[0], p.0, absolute),
+                axis(words[1], p.1, absolute),
+                axis(words[2], p.2, absolute),
+            )
+        };
+        if machine_coords {
+            return target(&self.position, true);
+        }
+        let absolute = self.modal.distance == DistanceMode::Absolute;
+        self.coords
+            .to_machine(&target(&self.coords.to_program(&self.position), absolute))
+    }
+
+    /// G10 L2 sets the origin of a coordinate system in machine position, G10 L20 sets it
+    /// so that the current position becomes the axis words. P0 means the active one.
+    fn set_coord_data(&mut self, block: &Block) -> Result<(), GCodeError> {
+        let l = block.get(Letter::L).ok_or(GCodeError::InvalidG10)?;
+        let p = block.get(Letter::P).ok_or(GCodeError::InvalidG10)?;
+        if num_traits::Float::fract(p) != 0. || !(0. ..=9.).contains(&p) {
+            return Err(GCodeError::InvalidG10);
+        }
+        let cs = match CoordSystem::from_p_number(p as u8) {
+            Some(cs) => cs,
+            None => self.coords.active(),
+        };
+        let words = self.axis_words(block);
+        let origin = if l == 2. {
+            words
+        } else if l == 20. {
+            // origin = machine - other offsets - program
+            let base = self.position - self.coords.g92_offset() - *self.coords.tool_length_offset();
+            [
+                words[0].map(|v| base.0 - v),
+                words[1].map(|v| base.1 - v),
+                words[2].map(|v| base.2 - v),
+            ]
+        } else {
+            return Err(GCodeError::InvalidG10);
+        };
+        let old = *self.coords.work_offset(cs);
+        self.coords.set_work_offset(
+            cs,
+            Vec3::new(
+                origin[0].unwrap_or(old.0),
+                origin[1].unwrap_or(old.1),
+                origin[2].unwrap_or(old.2),
+            ),
+        );
+        Ok(())
+    }
+
+    fn check_feed_rate(&self, block: &Block) -> Result<(), GCodeError> {
+        if self.modal.feed_mode == FeedMode::InverseTime && !block.has(Letter::F) {
+            return Err(GCodeError::MissingFeedRate);
+        }
+        if self.modal.feed_rate <= 0. {
+            return Err(GCodeError::MissingFeedRate);
+        }
+        Ok(())
+    }
+
+    /// G73, G81 ~ G89 with L repeats. in G91 mode R is relative to the current position, the drill axis
+    /// word is relative to R, and each repeat moves by the increments of the plane axes.
+    fn canned_cycle<M: CanonMotion>(
+        &mut self,
+        kind: CycleKind,
+        block: &Block,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        let modal = self.modal;
+        if self.comp.is_some() {
+            return Err(CycleError::CutterComp.into());
+        }
+        if modal.feed_mode == FeedMode::InverseTime {
+            return Err(CycleError::InverseTime.into());
+        }
+        self.check_feed_rate(block)?;
+        let r = self.cycle.r.ok_or(CycleError::MissingR)?;
+        let depth = self.cycle.depth.ok_or(CycleError::MissingDepth)?;
+        let repeat = match block.get(Letter::L) {
+            None => 1,
+            Some(l) if l >= 1. && num_traits::Float::fract(l) == 0. => l as u32,
+            Some(_) => return Err(CycleError::InvalidRepeat.into()),
+        };
+
+        let xy = CanonPlane::CanonPlaneXY;
+        let plane = modal.plane;
+        let mut current = xy.to_plane(&self.position, &plane);
+        let target = xy.to_plane(&self.target(block, false), &plane);
+        let offset = xy.to_plane(&self.coords.total_offset(), &plane).2;
+        let incremental = modal.distance == DistanceMode::Incremental;
+        let (r, bottom) = if incremental {
+            (current.2 + r, current.2 + r + depth)
+        } else {
+            (offset + r, offset + depth)
+        };
+        let mut cycle = CannedCycle::new(kind, modal.retract, r, bottom);
+        cycle.peck = self.cycle.peck;
+        cycle.dwell = self.cycle.dwell;
+        if kind == CycleKind::BackBore {
+            // the same letters as the arc center offsets, and the third one is the top
+            let letters = match plane {
+                CanonPlane::CanonPlaneXY => [Letter::I, Letter::J, Letter::K],
+                CanonPlane::CanonPlaneXZ => [Letter::K, Letter::I, Letter::J],
+                CanonPlane::CanonPlaneYZ => [Letter::J, Letter::K, Letter::I],
+            };
+            let mm = |l: Letter| block.get(l).map(|v| modal.units.to_mm(v));
+            cycle.back_bore_offset =
+                Vec2::new(mm(letters[0]).unwrap_or(0.), mm(letters[1]).unwrap_or(0.));
+            cycle.back_bore_top = match mm(letters[2]) {
+                Some(k) if incremental => cycle.r + k,
+                Some(k) => offset + k,
+                None => cycle.bottom,
+            };
+        }
+
+        let mut hole = target.plane();
+        let step = if incremental {
+            hole - current.plane()
+        } else {
+            Vec2::new(0., 0.)
+        };
+        let pl_data = modal.pl_data(false);
+        for _ in 0..repeat {
+            for s in cycle.expand(&current, hole, &pl_data)? {
+                match s {
+                    CycleStep::Line(p, d) => machine.line(&plane.to_plane(&p, &xy), &d)?,
+                    CycleStep::Dwell(seconds) => machine.dwell(seconds)?,
+                    CycleStep::Pause => machine.pause()?,
+                }
+            }
+            current = Vec3::new(hole.0, hole.1, cycle.clear_level(current.2));
+            self.position = plane.to_plane(&current, &xy);
+            hole += step;
+        }
+        Ok(())
+    }
+
+    /// build the arc of the current plane, spiral allows a center format arc to change its radius
+    fn arc(
+        &self,
+        target: Vec3<Scalar>,
+        turn_ccw: bool,
+        spiral: bool,
+        block: &Block,
+    ) -> Result<Arc, GCodeError> {
+        let modal = self.modal;
+        let plane = modal.plane;
+        // axis and offset letters of (axis0, axis1) in the plane
+        let (axes, offsets) = match plane {
+            CanonPlane::CanonPlaneXY => ([Letter::X, Letter::Y], [Letter::I, Letter::J]),
+            CanonPlane::CanonPlaneXZ => ([Letter::Z, Letter::X], [Letter::K, Letter::I]),
+            CanonPlane::CanonPlaneYZ => ([Letter::Y, Letter::Z], [Letter::J, Letter::K]),
+        };
+        let has_offset = offsets.iter().any(|l| block.has(*l));
+        let turns = match block.get(Letter::P) {
+            None => 1,
+            Some(p) if p >= 1. && num_traits::Float::fract(p) == 0. => p as u32,
+            Some(_) => return Err(GCodeError::InvalidTurns),
+        };
+
+        let arc = match block.get(Letter::R) {
+            Some(_) if has_offset => return Err(GCodeError::MixedArcFormat),
+            Some(r) => {
+                if !axes.iter().any(|l| block.has(*l)) {
+                    return Err(GCodeError::MissingAxis);
+                }
+                let r = modal.units.to_mm(r);
+                Arc::new_radius_mode_in_plane(plane, self.position, target, r, turn_ccw, turns)?
+            }
+            None if has_offset => {
+                let offset = |l: Letter| modal.units.to_mm(block.get(l).unwrap_or(0.));
+                let center_offset =
+                    Vec3::new(offset(Letter::I), offset(Letter::J), offset(Letter::K));
+                let tolerance = match modal.units {
+                    _ if spiral => RadiusTolerance::Spiral,
+                    Units::Millimeters => RadiusTolerance::Rs274ngc,
+                    Units::Inches => RadiusTolerance::Rs274ngcInch,
+                };
+                Arc::new_center_mode_in_plane(
+                    plane,
+                    self.position,
+                    target,
+                    center_offset,
+                    turn_ccw,
+                    turns,
+                    tolerance,
+                )?
+            }
+            None => return Err(GCodeError::MissingArcOffset),
+        };
+        Ok(arc)
+    }
+
+    /// build the G5/G5.1 curve in XY plane, the linear axis moves evenly.
+    ///
+    /// I, J are the offsets of the first control point from the start. P, Q of G5 are the offsets
+    /// of the second control point from the target, spline_end is P, Q of the previous G5.
+    fn spline(
+        &self,
+        target: Vec3<Scalar>,
+        spline_end: Option<Vec2<Scalar>>,
+        block: &Block,
+    ) -> Result<Bezier, GCodeError> {
+        let modal = self.modal;
+        if modal.plane != CanonPlane::CanonPlaneXY {
+            return Err(GCodeError::SplinePlane);
+        }
+        let mm = |l: Letter| block.get(l).map(|v| modal.units.to_mm(v));
+        let start = self.position;
+        let dz = target.2 - start.2;
+        let control = |base: Vec3<Scalar>, offset: Vec2<Scalar>, z: Scalar| {
+            Vec3::new(base.0 + offset.0, base.1 + offset.1, z)
+        };
+        let has_ij = block.has(Letter::I) || block.has(Letter::J);
+        let ij = Vec2::new(mm(Letter::I).unwrap_or(0.), mm(Letter::J).unwrap_or(0.));
+        let curve = if modal.motion == MotionMode::CubicSpline {
+            let ij = match (has_ij, spline_end) {
+                (true, _) => ij,
+                // continue in the direction the previous curve ended
+                (false, Some(pq)) => -pq,
+                (false, None) => return Err(GCodeError::MissingSplineOffset),
+            };
+            let pq = match (mm(Letter::P), mm(Letter::Q)) {
+                (Some(p), Some(q)) => Vec2::new(p, q),
+                _ => return Err(GCodeError::MissingSplineOffset),
+            };
+            Bezier::new_cubic(
+                start,
+                control(start, ij, start.2 + dz / 3.),
+                control(target, pq, start.2 + dz * 2. / 3.),
+                target,
+            )
+        } else {
+            if !has_ij {
+                return Err(GCodeError::MissingSplineOffset);
+            }
+            Bezier::new_quadratic(start, control(start, ij, start.2 + dz * 0.5), target)
+        };
+        Ok(curve)
+    }
+
+    /// send spline points to the machine
+    fn emit_spline<M: CanonMotion>(
+        &self,
+        curve: Bezier,
+        pl_data: &PlanLineData,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        let mut curve = curve.with_settings(machine.arc_settings());
+        let curvature = curve.curvature_at(0.);
+        let points: Vec<_> = core::iter::from_fn(|| curve.next_point()).collect();
+        self.emit_curve(curvature, &points, pl_data, machine)
+    }
+
+    /// send curve points to the machine, the feed rate of each segment is limited by the curvature
+    /// at its ends, start_curvature is the curvature at the start of the curve
+    fn emit_curve<M: CanonMotion>(
+        &self,
+        start_curvature: Scalar,
+        points: &[SplinePoint],
+        pl_data: &PlanLineData,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        let mut pl_data = *pl_data;
+        let inverse_time = pl_data
+            .condition
+            .contains(PlanCondition::PL_COND_FLAG_INVERSE_TIME);
+        if inverse_time {
+            // the whole curve should complete in 1/F minutes, so each segment takes its share.
+            pl_data.feed_rate *= points.len() as Scalar;
+        }
+        let feed_rate = pl_data.feed_rate;
+        let mut curvature = start_curvature;
+        for p in points {
+            if !inverse_time {
+                // the tighter end of the segment
+                let limit = machine.curve_velocity(curvature.max(p.curvature));
+                pl_data.feed_rate = feed_rate.min(limit.mm_per_min());
+            }
+            curvature = p.curvature;
+            machine.line(&p.position, &pl_data)?;
+        }
+        Ok(())
+    }
+
+    /// G5.2 starts a NURBS curve at the current position, each block until G5.3 adds a control point by
+    /// X, Y and the weight P. G5.3 cuts the curve with uniform clamped knots of order L (default 3).
+    /// the control points are in XY plane, the position follows the control points.
+    fn nurbs_block<M: CanonMotion>(
+        &mut self,
+        block: &Block,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        let mut code = None;
+        for word in block.g_words() {
+            match word.code() {
+                Some((5, c)) if (c == 2 || c == 3) && code.is_none() => code = Some(c),
+                _ => return Err(GCodeError::InvalidNurbs),
+            }
+        }
+        if block.m_words().next().is_some() {
+            return Err(GCodeError::InvalidNurbs);
+        }
+        match (code, self.nurbs.is_some()) {
+            (Some(2), false) => {
+                if self.modal.plane != CanonPlane::CanonPlaneXY {
+                    return Err(GCodeError::SplinePlane);
+                }
+                if self.comp.is_some() {
+                    return Err(GCodeError::SplineCutterComp);
+                }
+                let order = match block.get(Letter::L) {
+                    None => 3,
+                    Some(l) if l >= 2. && num_traits::Float::fract(l) == 0. => l as usize,
+                    Some(_) => return Err(GCodeError::InvalidNurbs),
+                };
+                self.nurbs = Some(NurbsWords {
+                    order,
+                    points: vec![self.position],
+                    weights: vec![1.],
+                });
+            }
+            (Some(3), true) => {
+                if let Some(word) = block.words.iter().find(|w| w.letter != Letter::G) {
+                    return Err(GCodeError::UnusedWord(word.letter));
+                }
+                self.check_feed_rate(block)?;
+                let words = self.nurbs.take().unwrap_or_else(|| unreachable!());
+                let mut curve = Nurbs::new_clamped(words.order - 1, words.points, words.weights)?
+                    .with_settings(machine.arc_settings());
+                let curvature = curve.curvature_at(0.);
+                let points: Vec<_> = core::iter::from_fn(|| curve.next_point()).collect();
+                return self.emit_curve(curvature, &points, &self.modal.pl_data(false), machine);
+            }
+            (None, true) => {}
+            _ => return Err(GCodeError::InvalidNurbs),
+        }
+
+        for word in block.words.iter() {
+            let used = match word.letter {
+                Letter::G | Letter::X | Letter::Y | Letter::P | Letter::F => true,
+                Letter::L => code == Some(2),
+                _ => false,
+            };
+            if !used {
+                return Err(GCodeError::UnusedWord(word.letter));
+            }
+        }
+        if let Some(f) = block.get(Letter::F) {
+            self.modal.feed_rate = match self.modal.feed_mode {
+                FeedMode::InverseTime => f,
+                FeedMode::UnitsPerMinute => self.modal.units.velocity(f).mm_per_min(),
+            };
+        }
+        if block.has(Letter::X) || block.has(Letter::Y) {
+            let weight = block.get(Letter::P).unwrap_or(1.);
+            if weight <= 0. {
+                return Err(GCodeError::InvalidNurbs);
+            }
+            let point = self.target(block, false);
+            if let Some(words) = self.nurbs.as_mut() {
+                words.points.push(point);
+                words.weights.push(weight);
+            }
+            self.position = point;
+        } else if block.has(Letter::P) {
+            return Err(GCodeError::UnusedWord(Letter::P));
+        }
+        Ok(())
+    }
+
+    /// send arc points to the machine
+    fn emit_arc<M: CanonMotion>(
+        &self,
+        arc: Arc,
+        pl_data: &PlanLineData,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        let arc = arc.with_settings(machine.arc_settings());
+        let (min, max) = arc.bounding_box();
+        machine.check_travel(&min, &max)?;
+        let mut pl_data = *pl_data;
+        if pl_data
+            .condition
+            .contains(PlanCondition::PL_COND_FLAG_INVERSE_TIME)
+        {
+            // the whole arc should complete in 1/F minutes, so each segment takes its share.
+            let (segments, _) = arc.get_segments_and_angular_travel();
+            pl_data.feed_rate *= segments.max(1) as Scalar;
+        }
+        for p in arc {
+            machine.line(&p, &pl_data)?;
+        }
+        Ok(())
+    }
+
+    /// send a compensated segment, it is in XY plane
+    fn emit<M: CanonMotion>(
+        &self,
+        seg: Segment,
+        pl_data: &PlanLineData,
+        machine: &mut M,
+    ) -> Result<(), GCodeError> {
+        match seg {
+            Segment::Line { end, .. } => machine.line(&end, pl_data),
+            Segment::Arc {
+                start,
+                end,
+                center,
+                turn_ccw,
+            } => {
+                // the offset arc has the same center, so its radius mismatch is only round-off
+                let arc = Arc::new_center_mode(
+                    start,
+                    end,
+                    center - start.plane(),
+                    turn_ccw,
+                    1,
+                    RadiusTolerance::Grbl,
+                )?;
+                self.emit_arc(arc, pl_data, machine)
+            }
+        }
+    }
+}
+
+/// the axis that canned cycles drill along
+fn drill_axis(plane: CanonPlane) -> Letter {
+    match plane {
+        CanonPlane::CanonPlaneXY => Letter::Z,
+        CanonPlane::CanonPlaneXZ => Letter::Y,
+        CanonPlane::CanonPlaneYZ => Letter::X,
+    }
+}
+
+/// T, H and D words are tool numbers
+fn tool_number(v: Scalar) -> Result<u16, GCodeError> {
+    if v >= 0. && v <= u16::MAX as Scalar && v == num_traits::Float::round(v) {
+        Ok(v as u16)
+    } else {
+        Err(GCodeError::UnknownTool(v))
+    }
+}
+
+fn lookup_tool<M: CanonMotion>(machine: &M, number: u16) -> Result<Tool, GCodeError> {
+    machine
+        .tool(number)
+        .ok_or(GCodeError::UnknownTool(number as Scalar))
+}
+
+/// a block can not have two words of the same modal group
+fn mark_group(seen: &mut u32, group: ModalGroup) -> Result<(), GCodeError> {
+    let bit = 1_u32 << (group as u32);
+    if *seen & bit != 0 {
+        return Err(GCodeError::ModalGroupConflict(group));
+    }
+    *seen |= bit;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CanonMotion, CoordSystem, CycleError, DistanceMode, GCodeError, Interpreter, Letter,
+        ModalGroup, ToolLengthMode, Units,
+    };
+    use crate::Scalar;
+    use crate::{ArcError, CNCCfgs, PlanCondition, PlanLineData, Planer, Tool, ToolTable, Vec3};
+    use crate::{Axis, Bound, Length, PlanError, Velocity};
+
+    /// record all motions, a dwell is recorded as a line to its seconds with empty data
+    #[derive(Default)]
+    struct Recorder(Vec<(Vec3<Scalar>, PlanLineData)>, ToolTable);
+    impl CanonMotion for Recorder {
+        fn dwell(&mut self, seconds: Scalar) -> Result<(), GCodeError> {
+            self.0
+                .push((Vec3::new(seconds, 0., 0.), PlanLineData::default()));
+            Ok(())
+        }
+
+        fn line(
+            &mut self,
+            target: &Vec3<Scalar>,
+            pl_data: &PlanLineData,
+        ) -> Result<(), GCodeError> {
+            self.0.push((*target, *pl_data));
+            Ok(())
+        }
+
+        fn tool(&self, number: u16) -> Option<Tool> {
+            self.1.get(number)
+        }
+
+        fn curve_velocity(&self, curvature: Scalar) -> Velocity {
+            Velocity::from_mm_per_min(100. / curvature)
+        }
+    }
+
+    #[test]
+    fn test_modal_state() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        let program = "G0 X1 Y2 Z3\nG20 G91 G1 X1 F10 M3 M8\nG90 G21 G93 G1 Y0 F2";
+        interp.run(program, &mut rec).unwrap();
+
+        assert_eq!(rec.0.len(), 3);
+        let (target, pl_data) = &rec.0[0];
+        assert_eq!(*target, Vec3::new(1., 2., 3.));
+        assert_eq!(pl_data.condition, PlanCondition::PL_COND_FLAG_RAPID_MOTION);
+
+        let (target, pl_data) = &rec.0[1];
+        assert_eq!(*target, Vec3::new(26.4, 2., 3.));
+        assert_eq!(pl_data.feed_rate, 254.);
+        assert_eq!(
+            pl_data.condition,
+            PlanCondition::PL_COND_FLAG_SPINDLE_CW | PlanCondition::PL_COND_FLAG_COOLANT_FLOOD
+        );
+
+        let (target, pl_data) = &rec.0[2];
+        assert_eq!(*target, Vec3::new(26.4, 0., 3.));
+        assert_eq!(pl_data.feed_rate, 2.);
+        assert!(pl_data
+            .condition
+            .contains(PlanCondition::PL_COND_FLAG_INVERSE_TIME));
+    }
+
+    #[test]
+    fn test_arc_in_planes() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        // XZ plane, half circle around (5,0,0)
+        interp.run("G18 G2 X10 Z0 I5 K0 F100", &mut rec).unwrap();
+        assert_eq!(rec.0.last().unwrap().0, Vec3::new(10., 0., 0.));
+        for (p, _) in rec.0.iter() {
+            let r = num_traits::Float::hypot(p.0 - 5., p.2);
+            assert!(num_traits::Float::abs(r - 5.) < 1e-3);
+            assert_eq!(p.1, 0.);
+        }
+        // G2 in XZ plane is clockwise when viewed from +Y, it passes through negative Z
+        assert!(rec.0.iter().any(|(p, _)| p.2 < -4.9));
+
+        let mut rec = Recorder::default();
+        interp.run("G17 G3 X0 Y0 R5", &mut rec).unwrap();
+        assert_eq!(rec.0.last().unwrap().0, Vec3::new(0., 0., 0.));
+        assert!(rec.0.iter().any(|(p, _)| p.1 > 4.9));
+
+        // a different end radius is a spiral only when the machine allows it
+        let program = "G0 X10 Y0\nG3 X0 Y5 I-10 J0 F100";
+        assert!(matches!(
+            Interpreter::new().run(program, &mut rec),
+            Err(GCodeError::Arc(ArcError::RadiusMismatch(_)))
+        ));
+        let mut cfg = CNCCfgs::default();
+        cfg.arc.spiral = true;
+        let mut planer = Planer::new(cfg);
+        let mut interp = Interpreter::new();
+        interp.run(program, &mut planer).unwrap();
+        assert!(planer.len() > 2);
+        assert_eq!(*interp.position(), Vec3::new(0., 5., 0.));
+    }
+
+    #[test]
+    fn test_splines() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        // S shape from two G5, the second continues the first
+        interp
+            .run(
+                "G1 F1000\nG5 X10 Y0 I0 J5 P0 Q5\nG5 X20 Y0 P0 Q-5",
+                &mut rec,
+            )
+            .unwrap();
+        let ends: Vec<_> = rec.0.iter().map(|(p, _)| *p).collect();
+        assert!(ends.contains(&Vec3::new(10., 0., 0.)));
+        assert_eq!(ends.last(), Some(&Vec3::new(20., 0., 0.)));
+        assert!(ends.iter().any(|p| p.1 > 3.7) && ends.iter().any(|p| p.1 < -3.7));
+        // curvature 2/15 at the top of the curve limits the feed to 100 / (2/15)
+        let top = ends.iter().position(|p| p.0 >= 5.).unwrap();
+        assert!((rec.0[top].1.feed_rate - 750.).abs() < 1.);
+        assert!(rec.0.iter().all(|(_, d)| d.feed_rate <= 1000.));
+
+        let mut rec = Recorder::default();
+        interp.run("G5.1 X30 I5 J5 Z-1", &mut rec).unwrap();
+        assert_eq!(rec.0.last().unwrap().0, Vec3::new(30., 0., -1.));
+        assert!(rec.0.iter().all(|(p, _)| p.1 >= 0. && p.1 <= 2.5));
+
+        assert_eq!(
+            interp.run("G1 X0\nG5 X10 P0 Q5", &mut rec),
+            Err(GCodeError::MissingSplineOffset)
+        );
+        assert_eq!(
+            interp.run("G5.1 X0 P1", &mut rec),
+            Err(GCodeError::UnusedWord(Letter::P))
+        );
+        assert_eq!(
+            interp.run("G18 G5.1 X10 I1", &mut rec),
+            Err(GCodeError::SplinePlane)
+        );
+    }
+
+    #[test]
+    fn test_nurbs() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        // quarter circle of radius 10 around (0,10), from the origin to (10,10)
+        let program = "G1 X0 Y0 F1000\nG5.2 X10 Y0 P0.70710678 L3\nX10 Y10 P1\nG5.3";
+        interp.run(program, &mut rec).unwrap();
+        assert_eq!(rec.0[0].0, Vec3::new(0., 0., 0.));
+        assert!((rec.0.last().unwrap().0 - Vec3::new(10., 10., 0.)).distance() < 1e-5);
+        assert_eq!(*interp.position(), Vec3::new(10., 10., 0.));
+        for (p, data) in rec.0[1..].iter() {
+            let r = (*p - Vec3::new(0., 10., 0.)).distance();
+            assert!((r - 10.).abs() < 1e-3);
+            // curvature 0.1 limits the feed to 100 / 0.1
+            assert!((data.feed_rate - 1000.).abs() < 1.);
+        }
+
+        // straight lines are not limited
+        let mut rec = Recorder::default();
+        interp
+            .run("G5.2 X20 Y10 L2\nX20 Y20 P2\nG5.3", &mut rec)
+            .unwrap();
+        assert_eq!(rec.0.last().unwrap().0, Vec3::new(20., 20., 0.));
+
+        assert_eq!(
+            interp.run("G5.2 X0\nG1 X1", &mut rec),
+            Err(GCodeError::InvalidNurbs)
+        );
+        assert_eq!(
+            interp.run("G5.3", &mut rec),
+            Err(GCodeError::Nurbs(crate::NurbsError::TooFewPoints))
+        );
+        assert_eq!(interp.run("G5.3", &mut rec), Err(GCodeError::InvalidNurbs));
+        assert_eq!(
+            interp.run("G5.2 X0 Z1", &mut rec),
+            Err(GCodeError::UnusedWord(Letter::Z))
+        );
+    }
+
+    #[test]
+    fn test_coordinate_systems() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        let program =
+            "G10 L2 P2 X100 Y50\nG55 G0 X1 Y1\nG92 X0 Y0\nG0 X2\nG53 G0 X0 Y0\nG92.1 G54 G0 X5";
+        interp.run(program, &mut rec).unwrap();
+
+        let targets: Vec<_> = rec.0.iter().map(|(t, _)| *t).collect();
+        assert_eq!(
+            targets,
+            [
+                Vec3::new(101., 51., 0.),
+                Vec3::new(103., 51., 0.),
+                Vec3::new(0., 0., 0.),
+                Vec3::new(5., 0., 0.),
+            ]
+        );
+        // G10 L20 makes current position (5,0,0) to be X0 in G54
+        interp.run("G10 L20 P1 X0", &mut rec).unwrap();
+        assert_eq!(interp.program_position(), Vec3::new(0., 0., 0.));
+        assert_eq!(
+            interp.run("G92 G0 X1", &mut rec),
+            Err(GCodeError::AxisWordConflict)
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        assert_eq!(
+            interp.run("G0 G1 X1", &mut rec),
+            Err(GCodeError::ModalGroupConflict(ModalGroup::Motion))
+        );
+        assert_eq!(
+            interp.run("G1 X1", &mut rec),
+            Err(GCodeError::MissingFeedRate)
+        );
+        assert_eq!(
+            interp.run("G0 X1 I2", &mut rec),
+            Err(GCodeError::UnusedWord(Letter::I))
+        );
+        assert_eq!(
+            interp.run("G2 X10 R2 F100", &mut rec),
+            Err(GCodeError::Arc(ArcError::RadiusTooSmall))
+        );
+        assert_eq!(
+            interp.run("G2 X20 F100", &mut rec),
+            Err(GCodeError::MissingArcOffset)
+        );
+        assert_eq!(
+            interp.run("G2 X0 Y0 I5 P1.5 F100", &mut rec),
+            Err(GCodeError::InvalidTurns)
+        );
+        assert!(rec.0.is_empty());
+
+        // a block that fails keeps none of its modal, tool and offset changes
+        interp.run("G10 L2 P2 X100", &mut rec).unwrap();
+        assert_eq!(
+            interp.run("G20 G55 G91 T2 G2 X10 R0.01 F100", &mut rec),
+            Err(GCodeError::Arc(ArcError::RadiusTooSmall))
+        );
+        assert_eq!(interp.modal().units, Units::Millimeters);
+        assert_eq!(interp.modal().distance, DistanceMode::Absolute);
+        assert_eq!(interp.coordinates().active(), CoordSystem::G54);
+        assert_eq!(interp.selected_tool(), 0);
+        interp.run("G0 X1", &mut rec).unwrap();
+        assert_eq!(rec.0.last().unwrap().0, Vec3::new(1., 0., 0.));
+    }
+
+    #[test]
+    fn test_cutter_comp() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        // pocket a 10x10 square with a 2mm tool, tool on left side is inside
+        let program = "G0 X5 Y-5\nG41.1 D2 G1 X5 Y0 F100\nX10\nY10\nX0\nY0\nX5\nG40 X5 Y-5";
+        interp.run(program, &mut rec).unwrap();
+        let targets: Vec<_> = rec.0.iter().map(|(t, _)| (t.0, t.1)).collect();
+        for corner in [(9., 1.), (9., 9.), (1., 9.), (1., 1.)] {
+            assert!(targets
+                .iter()
+                .any(|t| (t.0 - corner.0).abs() < 1e-4 && (t.1 - corner.1).abs() < 1e-4));
+        }
+        assert_eq!(targets.last(), Some(&(5., -5.)));
+        assert_eq!(interp.modal().cutter_comp, None);
+
+        assert_eq!(
+            interp.run("G18 G41.1 D2", &mut rec),
+            Err(GCodeError::CutterCompPlane)
+        );
+        assert_eq!(
+            interp.run("G41.1 X1", &mut rec),
+            Err(GCodeError::MissingCompDiameter)
+        );
+    }
+
+    #[test]
+    fn test_tool_length_offset() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        rec.1.set(1, Tool::new(50., 6.));
+        rec.1.set(2, Tool::new(30., 10.));
+        let program =
+            "T1 M6 G43 G0 X1 Z10\nT2\nZ20\nM6 G43 H2 Z10\nG43.1 Z0.2 G20\nG21 G0 Z0\nG49 Z0";
+        interp.run(program, &mut rec).unwrap();
+        let z: Vec<_> = rec.0.iter().map(|(t, _)| t.2).collect();
+        assert_eq!(z, vec![60., 70., 40., 5.08, 0.]);
+        assert_eq!(interp.tool(), 2);
+        assert_eq!(interp.modal().tool_length, ToolLengthMode::Off);
+
+        assert_eq!(
+            interp.run("G43 H9", &mut rec),
+            Err(GCodeError::UnknownTool(9.))
+        );
+        assert_eq!(
+            interp.run("T1.5", &mut rec),
+            Err(GCodeError::UnknownTool(1.5))
+        );
+        assert_eq!(
+            interp.run("G41 D3", &mut rec),
+            Err(GCodeError::UnknownTool(3.))
+        );
+        assert_eq!(
+            interp.run("G49 H1", &mut rec),
+            Err(GCodeError::UnusedWord(Letter::H))
+        );
+    }
+
+    #[test]
+    fn test_canned_cycles() {
+        let mut interp = Interpreter::new();
+        let mut rec = Recorder::default();
+        // two holes along X in G91, then one more with the sticky R and Z
+        let program = "G0 Z10\nG91 G99 G81 X10 Z-4 R-8 L2 F100\nG90 X30 Y5 R2 G4 P0.5\nG80";
+        interp.run(program, &mut rec).unwrap();
+        let targets: Vec<_> = rec.0.iter().map(|(t, _)| *t).collect();
+        assert_eq!(
+            targets,
+            vec![
+                Vec3::new(0., 0., 10.),
+                Vec3::new(10., 0., 10.),
+                Vec3::new(10., 0., 2.),
+                Vec3::new(10., 0., -2.),
+                Vec3::new(10., 0., 2.),
+                Vec3::new(20., 0., 2.),
+                Vec3::new(20., 0., -2.),
+                Vec3::new(20., 0., 2.),
+                Vec3::new(0.5, 0., 0.),
+                Vec3::new(30., 5., 2.),
+                Vec3::new(30., 5., -4.),
+                Vec3::new(30., 5., 2.),
+            ]
+        );
+        assert!(rec.0[2]
+            .1
+            .condition
+            .contains(PlanCondition::PL_COND_FLAG_RAPID_MOTION));
+        assert!(rec.0[3].1.condition.is_empty());
+
+        assert_eq!(
+            interp.run("X1", &mut rec),
+            Err(GCodeError::UnusedWord(Letter::X))
+        );
+        assert_eq!(
+            interp.run("G83 X1 Z-1 R1", &mut rec),
+            Err(GCodeError::CannedCycle(CycleError::InvalidPeck))
+        );
+        assert_eq!(
+            interp.run("G80\nG81 X1 Z-1", &mut rec),
+            Err(GCodeError::CannedCycle(CycleError::MissingR))
+        );
+    }
+
+    #[test]
+    fn test_soft_limits() {
+        let cfg = CNCCfgs {
+            soft_limits: true,
+            ..CNCCfgs::default()
+        };
+        let mut planer = Planer::new(cfg);
+        let mut interp = Interpreter::new();
+        interp.run("G0 X5 Y10", &mut planer).unwrap();
+        // a G2 from Y10 to Y30 around X5 Y20 bulges to X-5, it is rejected before any of its lines
+        assert!(matches!(
+            interp.run("G2 X5 Y30 I0 J10 F100", &mut planer),
+            Err(GCodeError::Plan(PlanError::SoftLimit { axis: Axis::X, .. }))
+        ));
+        assert_eq!(planer.len(), 1);
+        interp.run("G3 X5 Y30 I0 J10 F100", &mut planer).unwrap();
+        assert_eq!(
+            interp.run("G1 Y310", &mut planer),
+            Err(GCodeError::Plan(PlanError::SoftLimit {
+                axis: Axis::Y,
+                bound: Bound::Max,
+                overshoot: Length::from_mm(10.)
+            }))
+        );
+    }
+}
@@ -0,0 +1,17 @@
+//! rs274ngc g-code layer: turns program text into blocks, and blocks into planner motions.
+//!
+//! see "3 Language of the RS274/NGC Interpreter" section in rs274ngc.pdf
+mod canned;
+mod coordinate;
+mod interpreter;
+mod parser;
+mod work_plane;
+
+pub use canned::{CannedCycle, CycleError, CycleKind, CycleStep, RetractMode, PECK_CLEARANCE};
+pub use coordinate::{CoordSystem, WorkCoordinates};
+pub use interpreter::{
+    CanonMotion, DistanceMode, FeedMode, GCodeError, Interpreter, ModalGroup, ModalState,
+    MotionMode, SpindleState, ToolLengthMode, Units,
+};
+pub use parser::{Block, Letter, ParseError, ParseErrorKind, Parser, Word};
+pub use work_plane::{WorkPlane, WorkPlaneMotion};
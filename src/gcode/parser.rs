@@ -0,0 +1,470 @@
+//! rs274ngc block parser
+//!
+//! a block(line) is made of: optional block delete `/`, optional line number `N`, any number of words,
+//! comments, and an optional checksum `*nn` at the end. e.g.
+//!
+//! ```text
+//! /N10 G1 X1.5 Y-2 F300 (feed to start) ; trailing comment
+//! ```
+use crate::Scalar;
+use alloc::vec::Vec;
+
+/// letters that can start a word, see "3.3.1 Words" in rs274ngc.pdf
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Letter {
+    A,
+    B,
+    C,
+    /// tool radius compensation number
+    D,
+    /// feedrate
+    F,
+    /// general function
+    G,
+    /// tool length offset index
+    H,
+    /// X-axis offset for arcs
+    I,
+    /// Y-axis offset for arcs
+    J,
+    /// Z-axis offset for arcs
+    K,
+    /// number of repetitions in canned cycles, key used with G10
+    L,
+    /// miscellaneous function
+    M,
+    /// line number
+    N,
+    /// dwell time in canned cycles, dwell time with G4, key used with G10, turns of G2/G3
+    P,
+    /// feed increment in G83 canned cycle
+    Q,
+    /// arc radius, canned cycle plane
+    R,
+    /// spindle speed
+    S,
+    /// tool selection
+    T,
+    X,
+    Y,
+    Z,
+}
+
+impl Letter {
+    /// case insensitive
+    pub fn from_char(c: char) -> Option<Self> {
+        let letter = match c.to_ascii_uppercase() {
+            'A' => Self::A,
+            'B' => Self::B,
+            'C' => Self::C,
+            'D' => Self::D,
+            'F' => Self::F,
+            'G' => Self::G,
+            'H' => Self::H,
+            'I' => Self::I,
+            'J' => Self::J,
+            'K' => Self::K,
+            'L' => Self::L,
+            'M' => Self::M,
+            'N' => Self::N,
+            'P' => Self::P,
+            'Q' => Self::Q,
+            'R' => Self::R,
+            'S' => Self::S,
+            'T' => Self::T,
+            'X' => Self::X,
+            'Y' => Self::Y,
+            'Z' => Self::Z,
+            _ => return None,
+        };
+        Some(letter)
+    }
+}
+
+/// a letter followed by a real value. e.g. `X-1.5`, `G59.3`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Word {
+    pub letter: Letter,
+    pub value: Scalar,
+}
+
+impl Word {
+    pub fn new(letter: Letter, value: Scalar) -> Self {
+        Self { letter, value }
+    }
+
+    /// for G and M words, split the value as (major, minor). e.g. `G59.3` is (59, 3), `G1` is (1, 0).
+    ///
+    /// None means the value is negative or has more than one decimal digit
+    pub fn code(&self) -> Option<(u16, u8)> {
+        let tenth = self.value * 10.;
+        let rounded = num_traits::Float::round(tenth);
+        if rounded < 0. || num_traits::Float::abs(tenth - rounded) > 1e-3 {
+            return None;
+        }
+        let rounded = rounded as u32;
+        Some(((rounded / 10) as u16, (rounded % 10) as u8))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// the character can not start a word, comment or checksum
+    UnexpectedCharacter(char),
+    /// the letter is not followed by a number
+    MissingValue(Letter),
+    /// the number can not be parsed
+    InvalidNumber,
+    /// `(` without `)`
+    UnclosedComment,
+    /// `(` inside a comment
+    NestedComment,
+    /// `N` is not the first word of the block
+    MisplacedLineNumber,
+    /// line number is not a non-negative integer
+    InvalidLineNumber,
+    /// the same axis/parameter letter appears twice. only G and M words may repeat
+    DuplicateWord(Letter),
+    /// `*` is not followed by an integer in [0,255], or is followed by something other than a comment
+    InvalidChecksum,
+    /// checksum is the xor of all bytes before `*`
+    ChecksumMismatch { expected: u8, computed: u8 },
+}
+
+/// precise error position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParseError {
+    /// line number in program text, start from 1. 0 means it is unknown, e.g. from [`Block::parse`]
+    pub line: usize,
+    /// byte column in the line, start from 1
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+/// a parsed line
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Block<'a> {
+    /// true if the line starts with `/`
+    pub block_delete: bool,
+    /// value of the `N` word
+    pub line_number: Option<u32>,
+    /// words in the order they appear, `N` is not included
+    pub words: Vec<Word>,
+    /// text of each comment, without the delimiters
+    pub comments: Vec<&'a str>,
+    /// verified checksum
+    pub checksum: Option<u8>,
+}
+
+impl<'a> Block<'a> {
+    /// parse one line of g-code
+    pub fn parse(line: &'a str) -> Result<Self, ParseError> {
+        Lexer::new(line).parse_block()
+    }
+
+    /// value of the first word with the letter
+    pub fn get(&self, letter: Letter) -> Option<Scalar> {
+        self.words
+            .iter()
+            .find(|w| w.letter == letter)
+            .map(|w| w.value)
+    }
+
+    pub fn has(&self, letter: Letter) -> bool {
+        self.words.iter().any(|w| w.letter == letter)
+    }
+
+    /// all G words in the block
+    pub fn g_words(&self) -> impl Iterator<Item = &Word> {
+        self.words.iter().filter(|w| w.letter == Letter::G)
+    }
+
+    /// all M words in the block
+    pub fn m_words(&self) -> impl Iterator<Item = &Word> {
+        self.words.iter().filter(|w| w.letter == Letter::M)
+    }
+
+    /// true if there is no word in it. e.g. a comment only line
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+struct Lexer<'a> {
+    line: &'a str,
+    bytes: &'a [u8],
+    /// current byte index
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(line: &'a str) -> Self {
+        Self {
+            line,
+            bytes: line.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn error(&self, at: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: 0,
+            column: at + 1,
+            kind,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_ascii_whitespace() {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn parse_block(&mut self) -> Result<Block<'a>, ParseError> {
+        let mut block = Block::default();
+
+        self.skip_whitespace();
+        if self.peek() == Some(b'/') {
+            block.block_delete = true;
+            self.pos += 1;
+        }
+
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            let c = match self.peek() {
+                Some(c) => c,
+                None => break,
+            };
+            match c {
+                b'(' => block.comments.push(self.parse_paren_comment()?),
+                b';' => {
+                    block.comments.push(&self.line[start + 1..]);
+                    self.pos = self.bytes.len();
+                }
+                b'*' => {
+                    block.checksum = Some(self.parse_checksum()?);
+                }
+                _ => {
+                    let ch = self.line[start..].chars().next().unwrap();
+                    let letter = Letter::from_char(ch).ok_or_else(|| {
+                        self.error(start, ParseErrorKind::UnexpectedCharacter(ch))
+                    })?;
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    let value = self.parse_number(letter)?;
+
+                    if letter == Letter::N {
+                        if block.line_number.is_some() || !block.words.is_empty() {
+                            return Err(self.error(start, ParseErrorKind::MisplacedLineNumber));
+                        }
+                        if value < 0. || num_traits::Float::fract(value) != 0. {
+                            return Err(self.error(start, ParseErrorKind::InvalidLineNumber));
+                        }
+                        block.line_number = Some(value as u32);
+                        continue;
+                    }
+                    if letter != Letter::G
+                        && letter != Letter::M
+                        && block.words.iter().any(|w| w.letter == letter)
+                    {
+                        return Err(self.error(start, ParseErrorKind::DuplicateWord(letter)));
+                    }
+                    block.words.push(Word::new(letter, value));
+                }
+            }
+        }
+
+        Ok(block)
+    }
+
+    /// `[+-]digits[.digits]`, at least one digit is required
+    fn parse_number(&mut self, letter: Letter) -> Result<Scalar, ParseError> {
+        let start = self.pos;
+        if let Some(b'+' | b'-') = self.peek() {
+            self.pos += 1;
+        }
+        let mut digits = 0;
+        let mut dot = false;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() {
+                digits += 1;
+            } else if c == b'.' && !dot {
+                dot = true;
+            } else {
+                break;
+            }
+            self.pos += 1;
+        }
+        if digits == 0 {
+            return Err(if self.pos == start {
+                self.error(start, ParseErrorKind::MissingValue(letter))
+            } else {
+                self.error(start, ParseErrorKind::InvalidNumber)
+            });
+        }
+        self.line[start..self.pos]
+            .parse::<Scalar>()
+            .map_err(|_| self.error(start, ParseErrorKind::InvalidNumber))
+    }
+
+    fn parse_paren_comment(&mut self) -> Result<&'a str, ParseError> {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            match c {
+                b')' => {
+                    self.pos += 1;
+                    return Ok(&self.line[start + 1..self.pos - 1]);
+                }
+                b'(' => return Err(self.error(self.pos, ParseErrorKind::NestedComment)),
+                _ => self.pos += 1,
+            }
+        }
+        Err(self.error(start, ParseErrorKind::UnclosedComment))
+    }
+
+    /// checksum is the xor of all bytes before `*`, only whitespace and comments may follow it
+    fn parse_checksum(&mut self) -> Result<u8, ParseError> {
+        let star = self.pos;
+        self.pos += 1;
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        let expected = self.line[start..self.pos]
+            .parse::<u8>()
+            .map_err(|_| self.error(star, ParseErrorKind::InvalidChecksum))?;
+
+        self.skip_whitespace();
+        match self.peek() {
+            None | Some(b';') => {}
+            Some(b'(') => {
+                self.parse_paren_comment()?;
+                self.skip_whitespace();
+                if self.peek().is_some() {
+                    return Err(self.error(self.pos, ParseErrorKind::InvalidChecksum));
+                }
+            }
+            Some(_) => return Err(self.error(self.pos, ParseErrorKind::InvalidChecksum)),
+        }
+        self.pos = self.bytes.len();
+
+        let computed = self.bytes[..star].iter().fold(0_u8, |acc, b| acc ^ b);
+        if computed != expected {
+            return Err(self.error(
+                star,
+                ParseErrorKind::ChecksumMismatch { expected, computed },
+            ));
+        }
+        Ok(expected)
+    }
+}
+
+/// iterate blocks of a program text.
+///
+/// blank lines and `%` program delimiters are skipped; errors report the line in the text.
+pub struct Parser<'a> {
+    lines: core::iter::Enumerate<core::str::Lines<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(program: &'a str) -> Self {
+        Self {
+            lines: program.lines().enumerate(),
+        }
+    }
+}
+
+impl<'a> core::iter::Iterator for Parser<'a> {
+    type Item = Result<Block<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, line) in self.lines.by_ref() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed == "%" {
+                continue;
+            }
+            return Some(Block::parse(line).map_err(|e| ParseError {
+                line: index + 1,
+                ..e
+            }));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Block, Letter, ParseError, ParseErrorKind, Parser, Word};
+
+    #[test]
+    fn test_parse_block() {
+        let block = Block::parse("/n10 g1x1.5 Y -2 f300. (feed to start) ; done").unwrap();
+        assert!(block.block_delete);
+        assert_eq!(block.line_number, Some(10));
+        assert_eq!(
+            block.words,
+            [
+                Word::new(Letter::G, 1.),
+                Word::new(Letter::X, 1.5),
+                Word::new(Letter::Y, -2.),
+                Word::new(Letter::F, 300.),
+            ]
+        );
+        assert_eq!(block.comments, ["feed to start", " done"]);
+        assert_eq!(Word::new(Letter::G, 59.3).code(), Some((59, 3)));
+        assert_eq!(Word::new(Letter::G, 1.).code(), Some((1, 0)));
+        assert_eq!(Word::new(Letter::G, 1.25).code(), None);
+
+        // xor of "N1 G0" is 40
+        let block = Block::parse("N1 G0*40").unwrap();
+        assert_eq!(block.checksum, Some(40));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |line: &str| Block::parse(line).unwrap_err();
+        assert_eq!(
+            err("G1 X1 X2"),
+            ParseError {
+                line: 0,
+                column: 7,
+                kind: ParseErrorKind::DuplicateWord(Letter::X)
+            }
+        );
+        assert_eq!(err("G1 X").kind, ParseErrorKind::MissingValue(Letter::X));
+        assert_eq!(err("G1 X-.").kind, ParseErrorKind::InvalidNumber);
+        assert_eq!(err("G1 (abc").column, 4);
+        assert_eq!(err("G1 ( a ( b )").kind, ParseErrorKind::NestedComment);
+        assert_eq!(err("G1 N2").kind, ParseErrorKind::MisplacedLineNumber);
+        assert_eq!(err("G1 &").kind, ParseErrorKind::UnexpectedCharacter('&'));
+        assert_eq!(
+            err("N1 G0*41").kind,
+            ParseErrorKind::ChecksumMismatch {
+                expected: 41,
+                computed: 40
+            }
+        );
+
+        let program = "%\nG21\n\nG1 X1 E2\n%";
+        let all: Vec<_> = Parser::new(program).collect();
+        assert_eq!(all.len(), 2);
+        assert_eq!(
+            all[1],
+            Err(ParseError {
+                line: 4,
+                column: 7,
+                kind: ParseErrorKind::UnexpectedCharacter('E')
+            })
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! tilted work plane for 3+2 machining, as G68.2.
+//!
+//! the program runs in the work plane frame: its XY plane is the tilted plane and its Z is the tool axis, so
+//! G17 arcs, canned cycles and cutter compensation work in the tilted plane. [`WorkPlaneMotion`] transforms
+//! every line into machine position before it reaches the planner.
+//!
+//! ```text
+//!     machine = origin + rotation * work
+//! ```
+use super::interpreter::{CanonMotion, GCodeError};
+use crate::{ArcSettings, Mat3, Mat4, PlanLineData, Scalar, Tool, Vec3, Velocity};
+#[allow(unused_imports)]
+use num_traits::Float;
+
+/// origin and rotation of the work plane frame in machine position
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorkPlane {
+    transform: Mat4<Scalar>,
+    inverse: Mat4<Scalar>,
+}
+
+impl core::default::Default for WorkPlane {
+    fn default() -> Self {
+        Self::new(Vec3::zero(), Mat3::identity())
+    }
+}
+
+impl WorkPlane {
+    /// rotation must be a rotation matrix, its columns are the axes of the work plane frame
+    pub fn new(origin: Vec3<Scalar>, rotation: Mat3<Scalar>) -> Self {
+        Self {
+            transform: Mat4::from_rotation_translation(rotation, origin),
+            inverse: Mat4::from_rotation_translation(
+                rotation.transpose(),
+                -(rotation.transpose() * origin),
+            ),
+        }
+    }
+
+    /// euler angles in radians as `G68.2 P0 I J K`: rotate around Z by i, then around the new X by j, then
+    /// around the new Z by k
+    pub fn from_euler(origin: Vec3<Scalar>, i: Scalar, j: Scalar, k: Scalar) -> Self {
+        let rotation = Mat3::rotation_z(i) * Mat3::rotation_x(j) * Mat3::rotation_z(k);
+        Self::new(origin, rotation)
+    }
+
+    /// the tool axis is the normal of the plane, the X axis of the plane is machine X projected onto it,
+    /// or machine Y if the normal is along X. None if the normal is zero
+    pub fn from_normal(origin: Vec3<Scalar>, normal: Vec3<Scalar>) -> Option<Self> {
+        if normal.distance_sqr() == 0. {
+            return None;
+        }
+        let z = normal.as_unit_vec();
+        let mut x = Vec3::new(1., 0., 0.);
+        if z.0.abs() > 1. - 1e-3 {
+            x = Vec3::new(0., 1., 0.);
+        }
+        let x = (x - z * x.dot(z)).as_unit_vec();
+        Some(Self::new(origin, Mat3::from_cols(x, z.cross(x), z)))
+    }
+
+    /// origin of the work plane frame in machine position
+    pub fn origin(&self) -> Vec3<Scalar> {
+        self.transform.translation()
+    }
+
+    pub fn rotation(&self) -> Mat3<Scalar> {
+        self.transform.rotation()
+    }
+
+    /// Z axis of the work plane frame in machine position, the tool points along it
+    pub fn normal(&self) -> Vec3<Scalar> {
+        self.transform.rotation().col(2)
+    }
+
+    /// machine position of a position in the work plane frame
+    pub fn to_machine(&self, p: &Vec3<Scalar>) -> Vec3<Scalar> {
+        self.transform.transform_point(*p)
+    }
+
+    /// position in the work plane frame of a machine position, e.g. to sync [`super::Interpreter::set_position`]
+    pub fn to_work(&self, p: &Vec3<Scalar>) -> Vec3<Scalar> {
+        self.inverse.transform_point(*p)
+    }
+}
+
+/// run a program in the work plane, the lines go to the machine in machine position.
+///
+/// a rotation keeps the lengths, so the feed rates and the curve velocities are the same in both frames.
+///
+/// G53 is an error in the work plane, as G53 in G68.2 on most controls: its words would be machine position
+/// while the position of the interpreter is in the work plane frame. cancel the plane to move in machine
+/// position.
+///
+/// example, the XY plane tilted by 90 degrees around X, so a G2 in it is in machine XZ plane
+/// ```
+/// use ngc_geometry::gcode::{Interpreter, WorkPlane, WorkPlaneMotion};
+/// use ngc_geometry::{CNCCfgs, Planer, Vec3};
+/// let mut planer = Planer::new(CNCCfgs::default());
+/// let plane = WorkPlane::from_euler(Vec3::new(0., 0., 50.), 0., ngc_geometry::consts::FRAC_PI_2, 0.);
+/// let mut interp = Interpreter::new();
+/// interp.set_position(plane.to_work(&Vec3::zero()));
+/// interp
+///     .run("G1 X10 Y0 F300\nG2 X20 Y0 R5", &mut WorkPlaneMotion::new(plane, &mut planer))
+///     .unwrap();
+/// assert!((plane.to_machine(interp.position()) - Vec3::new(20., 0., 50.)).distance() < 1e-4);
+/// ```
+pub struct WorkPlaneMotion<'a, M: CanonMotion> {
+    plane: WorkPlane,
+    machine: &'a mut M,
+}
+
+impl<'a, M: CanonMotion> WorkPlaneMotion<'a, M> {
+    pub fn new(plane: WorkPlane, machine: &'a mut M) -> Self {
+        Self { plane, machine }
+    }
+
+    pub fn plane(&self) -> &WorkPlane {
+        &self.plane
+    }
+}
+
+impl<M: CanonMotion> CanonMotion for WorkPlaneMotion<'_, M> {
+    fn line(&mut self, target: &Vec3<Scalar>, pl_data: &PlanLineData) -> Result<(), GCodeError> {
+        self.machine.line(&self.plane.to_machine(target), pl_data)
+    }
+
+    fn machine_line(&mut self, _: &Vec3<Scalar>, _: &PlanLineData) -> Result<(), GCodeError> {
+        Err(GCodeError::InvalidG53Motion)
+    }
+
+    fn tool(&self, number: u16) -> Option<Tool> {
+        self.machine.tool(number)
+    }
+
+    fn dwell(&mut self, seconds: Scalar) -> Result<(), GCodeError> {
+        self.machine.dwell(seconds)
+    }
+
+    fn pause(&mut self) -> Result<(), GCodeError> {
+        self.machine.pause()
+    }
+
+    fn arc_settings(&self) -> ArcSettings {
+        self.machine.arc_settings()
+    }
+
+    fn curve_velocity(&self, curvature: Scalar) -> Velocity {
+        self.machine.curve_velocity(curvature)
+    }
+
+    /// the machine box is the box of the 8 transformed corners, it holds the tilted box
+    fn check_travel(&self, min: &Vec3<Scalar>, max: &Vec3<Scalar>) -> Result<(), GCodeError> {
+        let corner = |i: usize| {
+            let pick = |bit: usize, lo: Scalar, hi: Scalar| if i & bit == 0 { lo } else { hi };
+            let p = Vec3::new(
+                pick(1, min.0, max.0),
+                pick(2, min.1, max.1),
+                pick(4, min.2, max.2),
+            );
+            self.plane.to_machine(&p)
+        };
+        let (mut lo, mut hi) = (corner(0), corner(0));
+        for p in (1..8).map(corner) {
+            lo = lo.min(p);
+            hi = hi.max(p);
+        }
+        self.machine.check_travel(&lo, &hi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WorkPlane, WorkPlaneMotion};
+    use crate::consts::{FRAC_1_SQRT_2, FRAC_PI_2};
+    use crate::gcode::{CanonMotion, GCodeError, Interpreter};
+    use crate::Scalar;
+    use crate::{PlanLineData, Vec3};
+
+    #[derive(Default)]
+    struct Recorder(Vec<Vec3<Scalar>>);
+    impl CanonMotion for Recorder {
+        fn line(&mut self, target: &Vec3<Scalar>, _: &PlanLineData) -> Result<(), GCodeError> {
+            self.0.push(*target);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_work_plane_frames() {
+        let origin = Vec3::new(10., 20., 30.);
+        // tilted by 90 degrees around X: work Y is machine Z, the tool axis is machine -Y
+        let plane = WorkPlane::from_euler(origin, 0., FRAC_PI_2, 0.);
+        let p = Vec3::new(1., 2., 3.);
+        assert!((plane.to_machine(&p) - Vec3::new(11., 17., 32.)).distance() < 1e-5);
+        assert!((plane.to_work(&plane.to_machine(&p)) - p).distance() < 1e-5);
+        assert!((plane.normal() - Vec3::new(0., -1., 0.)).distance() < 1e-6);
+
+        let normal = Vec3::new(0., -1., 0.);
+        let same = WorkPlane::from_normal(origin, normal).unwrap();
+        assert!((same.to_machine(&p) - plane.to_machine(&p)).distance() < 1e-5);
+        // a compound angle, the normal is kept and the frame is right handed
+        let normal = Vec3::new(1., 1., 1.);
+        let plane = WorkPlane::from_normal(origin, normal).unwrap();
+        assert!((plane.normal() - normal.as_unit_vec()).distance() < 1e-6);
+        assert!((plane.rotation().determinant() - 1.).abs() < 1e-5);
+        assert_eq!(WorkPlane::from_normal(origin, Vec3::zero()), None);
+    }
+
+    #[test]
+    fn test_work_plane_program() {
+        // a half circle and a drill in a plane tilted by 45 degrees around Y
+        let s = FRAC_1_SQRT_2;
+        let plane = WorkPlane::from_normal(Vec3::new(0., 0., 100.), Vec3::new(s, 0., s)).unwrap();
+        let mut rec = Recorder::default();
+        let mut interp = Interpreter::new();
+        let program = "G0 X0 Y0 Z5\nG1 Z0 F100\nG2 X10 Y0 I5 J0\nG81 X5 Y5 Z-3 R2";
+        interp
+            .run(program, &mut WorkPlaneMotion::new(plane, &mut rec))
+            .unwrap();
+
+        // the arc stays in the tilted plane, at the radius around its center
+        let center = plane.to_machine(&Vec3::new(5., 0., 0.));
+        let arc: Vec<_> = rec
+            .0
+            .iter()
+            .skip(2)
+            .take_while(|p| (**p - center).dot(plane.normal()).abs() < 1e-4)
+            .collect();
+        assert!(arc.len() > 4);
+        for p in arc.iter() {
+            assert!(((**p - center).distance() - 5.).abs() < 1e-3);
+        }
+        // the drill goes down the tool axis to the hole bottom
+        let bottom = plane.to_machine(&Vec3::new(5., 5., -3.));
+        assert!(rec.0.iter().any(|p| (*p - bottom).distance() < 1e-4));
+        assert!((plane.to_machine(interp.position()) - *rec.0.last().unwrap()).distance() < 1e-4);
+
+        // G53 does not reach the machine, and the position stays
+        let (lines, position) = (rec.0.len(), *interp.position());
+        let res = interp.run("G53 G0 X0 Y0", &mut WorkPlaneMotion::new(plane, &mut rec));
+        assert_eq!(res, Err(GCodeError::InvalidG53Motion));
+        assert_eq!((rec.0.len(), *interp.position()), (lines, position));
+    }
+}
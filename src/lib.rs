@@ -4,30 +4,66 @@
 #![cfg_attr(test, feature(test))]
 
 mod arc;
+mod arc_fit;
+mod cutter_comp;
+mod ellipse;
+mod fixed;
+pub mod gcode;
 mod line;
 mod lookahead;
+mod matrix;
 mod pid;
 pub mod profile;
+mod spline;
 mod traits;
+mod units;
 mod vecx;
 extern crate alloc;
 
-pub use arc::Arc;
-pub use line::{Line2D, Line3D};
-pub use lookahead::{CNCCfgs, Direction, PlanBlock, PlanCondition, PlanLineData, Planer};
+pub use arc::{Arc, ArcError, ArcSegments, ArcSettings, RadiusTolerance};
+pub use arc_fit::{ArcFitSettings, ArcFitter};
+pub use cutter_comp::{CompError, CutterComp, Segment, Side};
+pub use ellipse::Ellipse;
+pub use fixed::Q16_16;
+pub use line::{Arc3D, Line2D, Line3D, LineN};
+pub use lookahead::{
+    Axis, Bound, CNCCfgs, Direction, PlanBlock, PlanCondition, PlanError, PlanLineData, Planer,
+    Tool, ToolTable,
+};
+pub use matrix::{Mat3, Mat4};
 pub use pid::PID;
-pub use vecx::{CanonPlane, Point, Point3, Vec2, Vec3, Vec5};
+pub use spline::{Bezier, Nurbs, NurbsError, SplinePoint};
+pub use traits::{Real, Sqrt};
+pub use units::{Acceleration, Angle, Jerk, Length, Steps, Velocity, MM_PER_INCH};
+pub use vecx::{CanonPlane, Point, Point3, Vec2, Vec3, Vec5, VecN};
+
+/// float type of positions, lengths, speeds and times. it is `f32`, or `f64` with the cargo feature "f64" for
+/// long travels with fine steps
+#[cfg(not(feature = "f64"))]
+pub type Scalar = f32;
+/// float type of positions, lengths, speeds and times. it is `f32`, or `f64` with the cargo feature "f64" for
+/// long travels with fine steps
+#[cfg(feature = "f64")]
+pub type Scalar = f64;
+/// constants of [`Scalar`]
+#[cfg(not(feature = "f64"))]
+pub use core::f32::consts;
+/// constants of [`Scalar`]
+#[cfg(feature = "f64")]
+pub use core::f64::consts;
 
 #[allow(unused_imports)]
 use num_traits::Float;
 
 /// after compensaton, the orig end pos will be to the new-end pos
+///
+/// radius is normally the tool radius, see [`ToolTable::radius`]
 pub fn compensation(
-    start: &Vec2<f32>,
-    end: &Vec2<f32>,
-    radius: f32,
+    start: &Vec2<Scalar>,
+    end: &Vec2<Scalar>,
+    radius: Scalar,
     side_is_left: bool,
-) -> Vec2<f32> {
+) -> Vec2<Scalar> {
     let (cx, cy) = (start.0, start.1);
     let (px, py) = (end.0, end.1);
 
@@ -36,16 +72,16 @@ pub fn compensation(
     let theta = (radius / distance).acos();
 
     let alpha = if side_is_left {
-        (cy - py).atan2(cx - px) - theta
+        Float::atan2(cy - py, cx - px) - theta
     } else {
-        (cy - py).atan2(cx - px) + theta
+        Float::atan2(cy - py, cx - px) + theta
     };
 
     /* reset to end location */
     let cx = px + radius * alpha.cos();
     let cy = py + radius * alpha.sin();
 
-    let end = Vec2::<f32>::new(cx, cy);
+    let end = Vec2::<Scalar>::new(cx, cy);
     end
 }
 
@@ -2,7 +2,7 @@
 
 // use core::ops;
 
-use super::vecx::{Point, Point3, Vec3};
+use super::vecx::{CanonPlane, Point, Point3, VecN};
 
 // use num_traits::{clamp_max, clamp_min};
 
@@ -158,57 +158,55 @@ impl core::iter::Iterator for Line2D {
 //       z1 -= dz; if (z1 < 0) { z1 += dm; z0 += sz; }
 //    }
 // }
+/// line of N axes, e.g. the XYZAB steps of a 5 axis machine. in its core::iter::Iterator implement, all
+/// output points include end point, not include start point
 #[derive(Copy, Clone, Debug)]
-/// line3d, in its core::iter::Iterator implement, all output points include end point, not include start point
-pub struct Line3D {
+pub struct LineN<const N: usize> {
     /// absolute length on each dimensions
-    d: Vec3<i32>,
+    d: VecN<i32, N>,
     ///step 1 or -1
-    s: Vec3<i32>,
+    s: VecN<i32, N>,
     /// maximum difference
     dm: i32,
 
     //var
-    out: Vec3<i32>,
+    out: VecN<i32, N>,
     i: i32,
     /// error offset
-    err_oft: Vec3<i32>,
+    err_oft: VecN<i32, N>,
 }
 
-impl Line3D {
+impl<const N: usize> LineN<N> {
     /// Creates a new iterator.Yields intermediate points between `start`
-    /// and `end`. Does include `start` but not `end`.
-    ///
-    /// the unit in the position is based on the Maximum resolution. e.g. if
-    /// the Maximum resolution is 600X800, then x \in [0,599], y \in [0,799]
-    ///
+    /// and `end`. Does include `end` but not `start`.
     #[inline]
-    pub fn new(start: Point3, end: Point3) -> Self {
-        let s: Vec3<i32> = Vec3::new(
-            if start.0 < end.0 { 1 } else { -1 },
-            if start.1 < end.1 { 1 } else { -1 },
-            if start.2 < end.2 { 1 } else { -1 },
-        );
-        let dm = (end - start).abs().max_element();
-        let err_oft: Vec3<i32> = Vec3::new(dm / 2, dm / 2, dm / 2);
+    pub fn new(start: VecN<i32, N>, end: VecN<i32, N>) -> Self {
+        let s = VecN(core::array::from_fn(
+            |i| if start[i] < end[i] { 1 } else { -1 },
+        ));
+        let d = (end - start).abs();
+        let dm = d.max_element();
 
         Self {
-            d: (end - start).abs(),
+            d,
             s,
             dm,
             i: dm,
 
             out: start,
-            err_oft,
+            err_oft: VecN([dm / 2; N]),
         }
     }
     pub fn len(&self) -> usize {
         self.dm as usize
     }
+    pub fn is_empty(&self) -> bool {
+        self.dm == 0
+    }
 }
 
-impl core::iter::Iterator for Line3D {
-    type Item = Point3;
+impl<const N: usize> core::iter::Iterator for LineN<N> {
+    type Item = VecN<i32, N>;
 
     /// all output point include end point, not include start point
     #[inline]
@@ -225,30 +223,286 @@ impl core::iter::Iterator for Line3D {
             return None;
         }
 
-        self.err_oft = self.err_oft - self.d;
-        if self.err_oft.0 < 0 {
-            self.err_oft.0 += self.dm;
-            self.out.0 += self.s.0;
-        }
-        if self.err_oft.1 < 0 {
-            self.err_oft.1 += self.dm;
-            self.out.1 += self.s.1;
-        }
-        if self.err_oft.2 < 0 {
-            self.err_oft.2 += self.dm;
-            self.out.2 += self.s.2;
+        self.err_oft -= self.d;
+        for i in 0..N {
+            if self.err_oft[i] < 0 {
+                self.err_oft[i] += self.dm;
+                self.out[i] += self.s[i];
+            }
         }
 
-        let p = self.out.clone();
         // loop inc
         self.i -= 1;
-        Some(p)
+        Some(self.out)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.i as usize, Some(self.i as usize))
+    }
+}
+
+impl<const N: usize> core::iter::ExactSizeIterator for LineN<N> {}
+
+/// line3d, in its core::iter::Iterator implement, all output points include end point, not include start point
+#[derive(Copy, Clone, Debug)]
+pub struct Line3D(LineN<3>);
+
+impl Line3D {
+    /// Creates a new iterator.Yields intermediate points between `start`
+    /// and `end`. Does include `start` but not `end`.
+    ///
+    /// the unit in the position is based on the Maximum resolution. e.g. if
+    /// the Maximum resolution is 600X800, then x \in [0,599], y \in [0,799]
+    ///
+    #[inline]
+    pub fn new(start: Point3, end: Point3) -> Self {
+        Self(LineN::new(start.into(), end.into()))
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+impl core::iter::Iterator for Line3D {
+    type Item = Point3;
+
+    /// all output point include end point, not include start point
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(Point3::from)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl core::iter::ExactSizeIterator for Line3D {}
+
+/// midpoint stepping of a circle around `center`, in plane coordinates
+///
+/// each step moves one unit along the major axis of the tangent, and the minor axis is
+/// chosen to keep `x^2 + y^2 - r^2` closest to zero, so it works for any octant and direction.
+/// the circle goes through the start point, the last few steps go straight to the end point.
+#[derive(Copy, Clone, Debug)]
+struct ArcSteps2D {
+    center: Point,
+    /// current point, relative to center
+    p: Point,
+    /// end point, relative to center
+    e: Point,
+    r2: i64,
+    turn_ccw: bool,
+    /// how many times the end ray still has to be crossed
+    passes: u32,
+    /// straight steps to the end point
+    tail: Option<Line3D>,
+}
+
+impl ArcSteps2D {
+    fn new(start: Point, end: Point, center: Point, turn_ccw: bool, turns: u32) -> Self {
+        let p = start - center;
+        let e = end - center;
+        let r2 = p.0 as i64 * p.0 as i64 + p.1 as i64 * p.1 as i64;
+        let mut steps = Self {
+            center,
+            p,
+            e,
+            r2,
+            turn_ccw,
+            passes: turns.max(1),
+            tail: None,
+        };
+        if r2 == 0 || e == Point::new(0, 0) {
+            steps.passes = 0;
+            steps.tail = Some(Line3D::new(
+                Point3::new(start.0, start.1, 0),
+                Point3::new(end.0, end.1, 0),
+            ));
+        }
+        steps
+    }
+
+    /// the next unit step from `p`
+    #[inline]
+    fn step(&self) -> Point {
+        let (x, y) = (self.p.0 as i64, self.p.1 as i64);
+        let (dx, dy) = if self.turn_ccw { (-y, x) } else { (y, -x) };
+        let candidates = if dx.abs() >= dy.abs() {
+            let sx = dx.signum();
+            [(sx, -1), (sx, 0), (sx, 1)]
+        } else {
+            let sy = dy.signum();
+            [(-1, sy), (0, sy), (1, sy)]
+        };
+        let (sx, sy) = candidates
+            .into_iter()
+            .min_by_key(|(sx, sy)| ((x + sx) * (x + sx) + (y + sy) * (y + sy) - self.r2).abs())
+            .unwrap_or_default();
+        Point::new(sx as i32, sy as i32)
+    }
+
+    /// side of `v` against the end ray, positive is past it in the turn direction
+    #[inline]
+    fn side(&self, v: Point) -> i64 {
+        let c = self.e.0 as i64 * v.1 as i64 - self.e.1 as i64 * v.0 as i64;
+        if self.turn_ccw {
+            c
+        } else {
+            -c
+        }
     }
 }
 
+impl core::iter::Iterator for ArcSteps2D {
+    type Item = Point;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(tail) = &mut self.tail {
+            return tail.next().map(|p| Point::new(p.0, p.1));
+        }
+        if self.passes == 0 {
+            return None;
+        }
+
+        let q = self.p + self.step();
+        let dot = self.e.0 as i64 * q.0 as i64 + self.e.1 as i64 * q.1 as i64;
+        if self.side(self.p) < 0 && self.side(q) >= 0 && dot > 0 {
+            self.passes -= 1;
+            if self.passes == 0 {
+                self.tail = Some(Line3D::new(
+                    Point3::new(self.p.0 + self.center.0, self.p.1 + self.center.1, 0),
+                    Point3::new(self.e.0 + self.center.0, self.e.1 + self.center.1, 0),
+                ));
+                return self.next();
+            }
+        }
+        self.p = q;
+        Some(q + self.center)
+    }
+}
+
+/// integer arc and helix stepping, the counterpart of [`Line3D`] for arcs
+///
+/// unlike [`crate::Arc`], which cuts the arc into float chords, it steps along the true
+/// arc, so there is no chord error and no cusp between segments. all units are steps.
+/// the linear axis moves evenly over the steps of the arc.
+/// in its core::iter::Iterator implement, all output points include end point, not include start point
+///
+/// example
+/// ```
+/// use ngc_geometry::{Arc3D, Point, Point3};
+/// let arc = Arc3D::new(Point3::new(10, 0, 0), Point3::new(0, 10, 0), Point::new(0, 0), true, 1);
+/// let steps: Vec<_> = arc.collect();
+/// assert_eq!(steps.last(), Some(&Point3::new(0, 10, 0)));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Arc3D {
+    plane: CanonPlane,
+    planar: ArcSteps2D,
+    /// paces the planar steps against the linear axis, x is the planar step, y the linear axis
+    pace: Line3D,
+    pace_at: Point3,
+    linear_start: i32,
+    /// current point in plane coordinates
+    out: Point3,
+}
+
+impl Arc3D {
+    /// arc in the XY plane, see [`Arc3D::new_in_plane`]
+    #[inline]
+    pub fn new(start: Point3, end: Point3, center: Point, turn_ccw: bool, turns: u32) -> Self {
+        Self::new_in_plane(
+            CanonPlane::CanonPlaneXY,
+            start,
+            end,
+            Point3::new(center.0, center.1, 0),
+            turn_ccw,
+            turns,
+        )
+    }
+
+    /// start, end and center are XYZ, the linear axis of center is ignored.
+    ///
+    /// turns is the P word, 1 is a normal arc, each more turn adds a full circle.
+    /// start equal to end is a full circle.
+    pub fn new_in_plane(
+        plane: CanonPlane,
+        start: Point3,
+        end: Point3,
+        center: Point3,
+        turn_ccw: bool,
+        turns: u32,
+    ) -> Self {
+        let xy = CanonPlane::CanonPlaneXY;
+        let (s, e, c) = (
+            xy.to_plane(&start, &plane),
+            xy.to_plane(&end, &plane),
+            xy.to_plane(&center, &plane),
+        );
+        let planar = ArcSteps2D::new(
+            Point::new(s.0, s.1),
+            Point::new(e.0, e.1),
+            Point::new(c.0, c.1),
+            turn_ccw,
+            turns,
+        );
+        let n = planar.count() as i32;
+        let pace = Line3D::new(Point3::new(0, 0, 0), Point3::new(n, e.2 - s.2, 0));
+
+        Self {
+            plane,
+            planar,
+            pace,
+            pace_at: Point3::new(0, 0, 0),
+            linear_start: s.2,
+            out: s,
+        }
+    }
+
+    /// return how much points in the arc
+    pub fn len(&self) -> usize {
+        self.pace.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl core::iter::Iterator for Arc3D {
+    type Item = Point3;
+
+    /// all output point include end point, not include start point
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let q = self.pace.next()?;
+        if q.0 != self.pace_at.0 {
+            let p = self.planar.next()?;
+            self.out.0 = p.0;
+            self.out.1 = p.1;
+        }
+        self.out.2 = self.linear_start + q.1;
+        self.pace_at = q;
+        Some(self.plane.to_plane(&self.out, &CanonPlane::CanonPlaneXY))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pace.size_hint()
+    }
+}
+
+impl core::iter::ExactSizeIterator for Arc3D {}
+
 #[cfg(test)]
 mod tests {
-    use super::{Line2D, Line3D, Point, Point3};
+    use super::{Arc3D, CanonPlane, Line2D, Line3D, LineN, Point, Point3};
+    use crate::Scalar;
+    use crate::VecN;
 
     #[test]
     fn test_line2d_example() {
@@ -323,4 +577,93 @@ mod tests {
             ]
         )
     }
+
+    /// every step moves each axis by at most one unit, and stays within one unit of the circle
+    fn check_arc(start: Point3, arc: Arc3D, center: Point, r: Scalar) -> Vec<Point3> {
+        let len = arc.len();
+        let res: Vec<_> = arc.collect();
+        assert_eq!(res.len(), len);
+        let mut last = start;
+        for p in res.iter() {
+            let d = (*p - last).abs();
+            assert!(d.max_element() == 1, "{:?} -> {:?}", last, p);
+            let (x, y) = ((p.0 - center.0) as Scalar, (p.1 - center.1) as Scalar);
+            assert!(((x * x + y * y).sqrt() - r).abs() < 1., "{:?}", p);
+            last = *p;
+        }
+        res
+    }
+
+    #[test]
+    fn test_linen_example() {
+        // the XYZ of a 5 axis line are the line3d, the rotary axes step along
+        let (start, end) = (Point3::new(6, 4, 0), Point3::new(0, 1, 0));
+        let line = LineN::new(VecN([6, 4, 0, 0, 10]), VecN([0, 1, 0, -3, 13]));
+        assert_eq!(line.len(), 6);
+        let res: Vec<_> = line.collect();
+        for (p, q) in res.iter().zip(Line3D::new(start, end)) {
+            assert_eq!(Point3::new(p[0], p[1], p[2]), q);
+        }
+        assert_eq!(res[1], VecN([4, 3, 0, -1, 11]));
+        assert_eq!(*res.last().unwrap(), VecN([0, 1, 0, -3, 13]));
+    }
+
+    #[test]
+    fn test_arc3d_example() {
+        // quarter circle in both directions
+        let start = Point3::new(10, 0, 0);
+        let arc = Arc3D::new(start, Point3::new(0, 10, 0), Point::new(0, 0), true, 1);
+        let res = check_arc(start, arc, Point::new(0, 0), 10.);
+        assert_eq!(res.last(), Some(&Point3::new(0, 10, 0)));
+        assert!(res.iter().all(|p| p.0 >= 0 && p.1 >= 0));
+
+        let arc = Arc3D::new(start, Point3::new(0, 10, 0), Point::new(0, 0), false, 1);
+        let res = check_arc(start, arc, Point::new(0, 0), 10.);
+        assert_eq!(res.last(), Some(&Point3::new(0, 10, 0)));
+        assert!(res.iter().any(|p| p.0 == -10));
+        assert!(res.iter().any(|p| p.1 == -10));
+
+        // full circle through all octants, off center
+        let start = Point3::new(3, 20, 0);
+        let arc = Arc3D::new(start, start, Point::new(3, -5), false, 1);
+        let res = check_arc(start, arc, Point::new(3, -5), 25.);
+        assert_eq!(res.last(), Some(&start));
+        assert!(res.iter().any(|p| p.0 == 28) && res.iter().any(|p| p.0 == -22));
+        assert!(res.iter().any(|p| p.1 == -30));
+    }
+
+    #[test]
+    fn test_arc3d_helix() {
+        // two turns in XZ plane, the linear axis Y goes down 40 steps
+        let start = Point3::new(50, 0, 0);
+        let end = Point3::new(0, -40, 50);
+        let arc = Arc3D::new_in_plane(
+            CanonPlane::CanonPlaneXZ,
+            start,
+            end,
+            Point3::new(0, 0, 0),
+            true,
+            2,
+        );
+        let len = arc.len();
+        let res: Vec<_> = arc.collect();
+        assert_eq!(res.len(), len);
+        assert_eq!(res.last(), Some(&end));
+        assert!(res.iter().all(|p| p.1 <= 0 && p.1 >= -40));
+        // more than one and a quarter circle of steps
+        assert!(len > 5 * 50);
+
+        // steep helix, the linear axis has more steps than the arc
+        let arc = Arc3D::new(
+            Point3::new(2, 0, 0),
+            Point3::new(-2, 0, 100),
+            Point::new(0, 0),
+            true,
+            1,
+        );
+        assert_eq!(arc.len(), 100);
+        let res: Vec<_> = arc.collect();
+        assert_eq!(res.last(), Some(&Point3::new(-2, 0, 100)));
+        assert!(res.iter().all(|p| p.1 >= 0));
+    }
 }
@@ -1,7 +1,8 @@
 ///! look 2 ahead algorithm
+use crate::Scalar;
 use crate::{
     profile::{LinearMotionProfile, LinearMotionSCurve /* LinearMotionTrapezoidal*/},
-    CanonPlane, Vec3,
+    Arc, CanonPlane, Length, Vec3,
 };
 
 use super::{
@@ -13,6 +14,7 @@ use super::{
     // },
     Direction,
     PlanCondition,
+    PlanError,
     PlanLineData,
 };
 
@@ -25,7 +27,7 @@ use num_traits::Float;
 #[derive(Copy, Clone, Debug, Default)]
 pub struct PlanBlock {
     /// distance that mm as unit. according plan_buffer_line logic, zero length item will not exist
-    pub millimeters: f32,
+    pub millimeters: Scalar,
 
     /// Step count along each axis, flag(+/-) represent direction
     pub steps: Vec3<i32>,
@@ -35,27 +37,29 @@ pub struct PlanBlock {
     pub condition: PlanCondition,
 
     /// Fields used by the motion planner to manage acceleration.
-    pub entry_speed_sqr: f32,
+    pub entry_speed_sqr: Scalar,
 
     /// Maximum allowable entry speed based on the minimum of junction limit and
-    max_entry_speed_sqr: f32,
+    max_entry_speed_sqr: Scalar,
 
     /// Axis-limit adjusted line acceleration. Does not change.
-    pub acceleration: f32,
+    pub acceleration: Scalar,
+    /// jerk of the step profile, from the config
+    pub jerk: Scalar,
 
     /// Stored rate limiting data used by planner when changes occur.
-    max_junction_speed_sqr: f32,
+    max_junction_speed_sqr: Scalar,
 
     /// Axis-limit adjusted maximum rate for this block direction in
-    rapid_rate: f32,
-    pub nominal_speed: f32,
+    rapid_rate: Scalar,
+    pub nominal_speed: Scalar,
 
     /// Programmed rate of this block .
-    programmed_rate: f32,
+    programmed_rate: Scalar,
 
     /// Stored spindle speed data used by spindle overrides and resuming methods.
     /// Block spindle speed. Copied from pl_line_data.
-    spindle_speed: f32,
+    spindle_speed: Scalar,
 
     is_sys_motion: bool,
 }
@@ -64,7 +68,7 @@ impl PlanBlock {
     /// convert to step profile. exist_speed_sqr unit is same as Self's veclocity
     pub fn to_step_profile(
         &self,
-        exist_speed_sqr: f32,
+        exist_speed_sqr: Scalar,
     ) -> Option<(impl LinearMotionProfile, (Direction, Direction, Direction))> {
         if self.millimeters == 0. {
             return None;
@@ -102,6 +106,7 @@ impl PlanBlock {
             self.millimeters,
             &self.steps,
             self.acceleration,
+            self.jerk,
             self.nominal_speed,
             self.entry_speed_sqr.sqrt(),
             exist_speed_sqr.sqrt(),
@@ -119,25 +124,27 @@ struct PreviousVar {
     /// i.e. arcs, canned cycles, and backlash compensation.
     pub steps: Vec3<i32>,
     /// Unit vector of previous path line segment
-    pub pl_previous_unit_vec: Vec3<f32>,
+    pub pl_previous_unit_vec: Vec3<Scalar>,
     /// Nominal speed of previous path line segment
-    pub pl_previous_nominal_speed: f32,
+    pub pl_previous_nominal_speed: Scalar,
 }
 impl PreviousVar {
     pub fn zero() -> Self {
         Self {
             steps: Vec3::<i32>::zero(),
-            pl_previous_unit_vec: Vec3::<f32>::zero(),
+            pl_previous_unit_vec: Vec3::<Scalar>::zero(),
             pl_previous_nominal_speed: 0.,
         }
     }
-    pub fn update(&mut self, nominal_speed: f32, unit_vec: &Vec3<f32>, steps: &Vec3<i32>) {
+    pub fn update(&mut self, nominal_speed: Scalar, unit_vec: &Vec3<Scalar>, steps: &Vec3<i32>) {
         self.pl_previous_nominal_speed = nominal_speed;
         self.pl_previous_unit_vec = unit_vec.clone();
         self.steps = steps.clone();
     }
 }
 
+/// lookahead planner of the XYZ axes. the planner is 3 axis, a rotary axis is not planned; the step
+/// generation of more axes goes through [`crate::LineN`].
 pub struct Planer {
     block_buffer: VecDeque<PlanBlock>,
 
@@ -182,9 +189,9 @@ impl Planer {
     }
 
     /// Returns address of first planner block, and its exist speed sqr.
-    pub fn get_current_block(&self) -> Option<(&PlanBlock, f32)> {
+    pub fn get_current_block(&self) -> Option<(&PlanBlock, Scalar)> {
         if let Some(v) = self.block_buffer.get(0) {
-            let exist_speed_sqr: f32;
+            let exist_speed_sqr: Scalar;
             if let Some(v) = self.block_buffer.get(1) {
                 exist_speed_sqr = v.entry_speed_sqr;
             } else {
@@ -201,20 +208,40 @@ impl Planer {
         self.block_buffer.len()
     }
 
+    /// plan a line to target, it is checked by the soft limits
     pub fn push_normal_motion(
         &mut self,
-        target: &Vec3<f32>,
+        target: &Vec3<Length>,
         pl_data: &PlanLineData,
-    ) -> Result<(), ()> {
-        self.plan_buffer_line(target, pl_data, None)
+    ) -> Result<(), PlanError> {
+        let target = target.mm();
+        self.cfg.check_soft_limits(&target, &target)?;
+        self.plan_buffer_line(&target, pl_data, None)
     }
+
+    /// plan the lines of an arc, with the arc settings of the config. the whole arc is checked by the soft limits
+    /// before any line is planned
+    pub fn push_arc(&mut self, arc: Arc, pl_data: &PlanLineData) -> Result<(), PlanError> {
+        let (min, max) = arc.bounding_box();
+        self.cfg.check_soft_limits(&min, &max)?;
+        for p in arc.with_settings(self.cfg.arc) {
+            match self.plan_buffer_line(&p, pl_data, None) {
+                Ok(()) | Err(PlanError::ZeroLength) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// plan a system motion, e.g. homing. it is not checked by the soft limits, as in grbl: homing seeks the
+    /// switches from an unknown position, and its pull-off may end out of the travel
     pub fn push_sys_motion(
         &mut self,
-        target: &Vec3<f32>,
+        target: &Vec3<Length>,
         pl_data: &PlanLineData,
         previsous_steps: &Vec3<i32>,
-    ) -> Result<(), ()> {
-        self.plan_buffer_line(target, pl_data, Some(previsous_steps))
+    ) -> Result<(), PlanError> {
+        self.plan_buffer_line(&target.mm(), pl_data, Some(previsous_steps))
     }
     /// Add a new linear movement to the planner. target[N_AXIS] is the signed, absolute target position
     /// in millimeters. Feed rate specifies the speed of the motion. If feed rate is inverted, the feed
@@ -229,10 +256,10 @@ impl Planer {
     /// err means the input plan is empty,
     fn plan_buffer_line(
         &mut self,
-        target: &Vec3<f32>,
+        target: &Vec3<Scalar>,
         pl_data: &PlanLineData,
         previsous_steps: Option<&Vec3<i32>>,
-    ) -> Result<(), ()> {
+    ) -> Result<(), PlanError> {
         let is_sys_motion = previsous_steps.is_some();
 
         let target_steps = self
@@ -254,7 +281,7 @@ impl Planer {
         };
         // Bail if this is a zero-length block. Highly unlikely to occur.
         if distance == 0. {
-            return Err(());
+            return Err(PlanError::ZeroLength);
         }
 
         let (acceleration, rapid_rate) = (
@@ -287,6 +314,7 @@ impl Planer {
             // step_event_count: steps_abs.max_element() as usize,
             millimeters: distance,
             acceleration,
+            jerk: self.cfg.default_jerk.mm_per_min3(),
             rapid_rate,
             programmed_rate,
 
@@ -360,10 +388,10 @@ impl Planer {
     /// Computes the max entry speed (sqr) of the block, based on the minimum of the junction's
     /// previous and current nominal speeds and max junction speed.
     fn compute_profile_max_entry_speed_sqr(
-        max_junction_speed_sqr: f32,
-        nominal_speed: f32,
-        prev_nominal_speed: f32,
-    ) -> f32 {
+        max_junction_speed_sqr: Scalar,
+        nominal_speed: Scalar,
+        prev_nominal_speed: Scalar,
+    ) -> Scalar {
         // Compute the junction maximum entry based on the minimum of the junction speed and neighboring nominal speeds.
         let max_entry_speed_sqr = if nominal_speed > prev_nominal_speed {
             prev_nominal_speed * prev_nominal_speed
@@ -400,7 +428,7 @@ impl Planer {
             return;
         }
 
-        let get_end_speed_sqr = |v0_sqr: f32, accel: f32, s: f32| -> f32 {
+        let get_end_speed_sqr = |v0_sqr: Scalar, accel: Scalar, s: Scalar| -> Scalar {
             // 2as = v^2_1 -v^2_0
             2. * accel * s + v0_sqr
         };
@@ -470,7 +498,14 @@ impl Planer {
 
 #[cfg(test)]
 mod tests {
-    use super::{CNCCfgs, PlanCondition, PlanLineData, Planer, Vec3};
+    use super::{CNCCfgs, PlanCondition, PlanError, PlanLineData, Planer, Vec3};
+    use crate::profile::{SCurve, SCurveConstraints, SCurveStartConditions};
+    use crate::Scalar;
+    use crate::{Acceleration, Arc, Axis, Bound, Length, Velocity};
+
+    fn mm(x: Scalar, y: Scalar, z: Scalar) -> Vec3<Length> {
+        Vec3::<Length>::from_mm(Vec3::new(x, y, z))
+    }
 
     #[test]
     fn plan_buffer_push() {
@@ -482,9 +517,9 @@ mod tests {
             spindle_speed: 0.,
             condition: PlanCondition::default(),
         };
-        let _exist_speed_sqr: f32 = 0.;
+        let _exist_speed_sqr: Scalar = 0.;
         let sys_position: Vec3<i32> = Vec3::new(5, 5, 5);
-        let target: Vec3<f32> = Vec3::new(10., 10., 10.);
+        let target = mm(10., 10., 10.);
 
         let _ = planer.push_sys_motion(&target, &pl_data, &sys_position);
         let _ = planer.push_normal_motion(&target, &pl_data);
@@ -511,7 +546,7 @@ mod tests {
             condition: PlanCondition::default(),
         };
 
-        let target: Vec3<f32> = Vec3::new(10., 10., 10.);
+        let target = mm(10., 10., 10.);
         let _ = planer.push_normal_motion(&target, &pl_data);
         assert_eq!(planer.len(), 1);
         //zero mills will not accept
@@ -519,14 +554,14 @@ mod tests {
         assert_eq!(planer.len(), 1);
 
         let previsous_steps: Vec3<i32> = Vec3::new(0, 0, 0);
-        let target: Vec3<f32> = Vec3::new(10., 10., 10.);
+        let target = mm(10., 10., 10.);
         let _ = planer.push_sys_motion(&target, &pl_data, &previsous_steps);
         assert_eq!(planer.len(), 2);
 
-        let target: Vec3<f32> = Vec3::new(11., 10., 10.);
+        let target = mm(11., 10., 10.);
         let _ = planer.push_normal_motion(&target, &pl_data);
         assert_eq!(planer.len(), 3);
-        let target: Vec3<f32> = Vec3::new(11., 11., 10.);
+        let target = mm(11., 11., 10.);
         let _ = planer.push_normal_motion(&target, &pl_data);
         assert_eq!(planer.len(), 4);
 
@@ -536,4 +571,123 @@ mod tests {
         assert_eq!(collects[2].entry_speed_sqr, 0.);
         assert_eq!(collects[3].entry_speed_sqr, 1.0);
     }
+
+    #[test]
+    fn push_units() {
+        let cfg = CNCCfgs {
+            default_x_acceleration: Acceleration::from_mm_per_sec2(5.),
+            ..CNCCfgs::default()
+        };
+        let mut planer = Planer::new(cfg);
+        // 10 inch/min is 254 mm/min, below the max rate
+        let pl_data = PlanLineData::feed(Velocity::from_inch_per_min(10.));
+        assert_eq!(
+            pl_data.feed_velocity(),
+            Some(Velocity::from_mm_per_min(254.))
+        );
+        let _ = planer.push_normal_motion(&mm(10., 0., 0.), &pl_data);
+        let _ = planer.push_normal_motion(&mm(10., 10., 0.), &PlanLineData::rapid());
+        assert_eq!(PlanLineData::rapid().feed_velocity(), None);
+        assert_eq!(PlanLineData::inverse_time(2.).inverse_time_rate(), Some(2.));
+        assert_eq!(pl_data.inverse_time_rate(), None);
+
+        let collects = planer.dump_planers();
+        assert_eq!(collects[0].nominal_speed, 254.);
+        assert_eq!(collects[0].acceleration, 5. * 3600.);
+        assert_eq!(collects[1].nominal_speed, 500.);
+
+        // from rest to rest the jerk adds a/j to the trapezoidal time d/v + v/a, 2.5ms at 100mm/s^3
+        let b = &collects[0];
+        let scurve = SCurve::new(
+            &SCurveConstraints {
+                max_jerk: b.jerk,
+                max_acceleration: b.acceleration,
+                max_velocity: b.nominal_speed,
+            },
+            &SCurveStartConditions {
+                q0: 0.,
+                q1: b.millimeters,
+                v0: 0.,
+                v1: 0.,
+            },
+        );
+        let trapezoidal = 10. / 254. + 254. / (5. * 3600.);
+        let duration = scurve.params.time_intervals.total_duration();
+        assert!((duration - trapezoidal - 5. * 3600. / b.jerk).abs() < 1e-6);
+        assert!(duration < trapezoidal * 1.05);
+    }
+
+    #[test]
+    fn push_soft_limits() {
+        let cfg = CNCCfgs {
+            x_max_travel: Length::from_mm(100.),
+            soft_limits: true,
+            ..CNCCfgs::default()
+        };
+        let mut planer = Planer::new(cfg);
+        let pl_data = PlanLineData::feed(Velocity::from_mm_per_min(300.));
+        assert_eq!(
+            planer.push_normal_motion(&mm(102.5, 0., 0.), &pl_data),
+            Err(PlanError::SoftLimit {
+                axis: Axis::X,
+                bound: Bound::Max,
+                overshoot: Length::from_mm(2.5)
+            })
+        );
+        assert_eq!(
+            planer.push_normal_motion(&mm(10., 0., -1.), &pl_data),
+            Err(PlanError::SoftLimit {
+                axis: Axis::Z,
+                bound: Bound::Min,
+                overshoot: Length::from_mm(1.)
+            })
+        );
+        assert_eq!(planer.len(), 0);
+        planer
+            .push_normal_motion(&mm(90., 5., 0.), &pl_data)
+            .unwrap();
+
+        // the ends are in the travel, the half circle to the right bulges to X 110
+        let arc = |ccw| {
+            Arc::new_radius_mode(Vec3::new(90., 5., 0.), Vec3::new(90., 45., 0.), 20., ccw, 1)
+                .unwrap()
+        };
+        match planer.push_arc(arc(true), &pl_data) {
+            Err(PlanError::SoftLimit {
+                axis,
+                bound,
+                overshoot,
+            }) => {
+                assert_eq!((axis, bound), (Axis::X, Bound::Max));
+                assert!((overshoot.mm() - 10.).abs() < 1e-3);
+            }
+            e => panic!("{:?}", e),
+        }
+        assert_eq!(planer.len(), 1);
+        // the other way round bulges to X 70
+        planer.push_arc(arc(false), &pl_data).unwrap();
+        assert!(planer.len() > 2);
+
+        // the travel of grbl, homed to the top end
+        let cfg = CNCCfgs {
+            travel_min: mm(-400., -300., -500.),
+            soft_limits: true,
+            ..CNCCfgs::default()
+        };
+        let mut planer = Planer::new(cfg);
+        planer
+            .push_normal_motion(&mm(-10., -10., -10.), &pl_data)
+            .unwrap();
+        assert_eq!(
+            planer.push_normal_motion(&mm(-10., -310., -10.), &pl_data),
+            Err(PlanError::SoftLimit {
+                axis: Axis::Y,
+                bound: Bound::Min,
+                overshoot: Length::from_mm(10.)
+            })
+        );
+        assert!(planer
+            .push_normal_motion(&mm(1., -10., -10.), &pl_data)
+            .is_err());
+    }
 }
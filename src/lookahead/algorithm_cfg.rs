@@ -1,4 +1,5 @@
-use crate::{CanonPlane, Vec3};
+use super::{Axis, Bound, PlanError, ToolTable};
+use crate::{Acceleration, Angle, ArcSettings, CanonPlane, Jerk, Length, Scalar, Vec3, Velocity};
 #[allow(unused_imports)]
 use num_traits::Float;
 
@@ -15,19 +16,25 @@ use num_traits::Float;
 /// ```
 #[derive(Clone, Debug)]
 pub struct CNCCfgs {
-    /// mm note: must be a positive value.
-    pub x_max_travel: f32,
-    /// mm note: must be a positive value.
-    pub y_max_travel: f32, // mm note: must be a positive value.
-    /// mm note: must be a positive value.
-    pub z_max_travel: f32, // mm note: must be a positive value.
-    pub a_max_travel: f32, // °
-    pub b_max_travel: f32, // °
+    /// note: must be a positive value.
+    pub x_max_travel: Length,
+    /// note: must be a positive value.
+    pub y_max_travel: Length,
+    /// note: must be a positive value.
+    pub z_max_travel: Length,
+    /// travel of the rotary axes A and B, kept for the machine setup. the planner only plans X, Y and Z
+    pub a_max_travel: Angle,
+    pub b_max_travel: Angle,
+    /// machine position of the low end of the travel of X, Y and Z
+    pub travel_min: Vec3<Length>,
+    /// reject motions out of [travel_min, travel_min + max travel] of X, Y and Z, as grbl $20. grbl homes to the
+    /// top end, its travel is [-max travel, 0]
+    pub soft_limits: bool,
 
     /// in plane_XY coordiate, define steps per mm
-    pub x_steps_per_mm: f32,
-    pub y_steps_per_mm: f32,
-    pub z_steps_per_mm: f32,
+    pub x_steps_per_mm: Scalar,
+    pub y_steps_per_mm: Scalar,
+    pub z_steps_per_mm: Scalar,
 
     /// Minimum planner junction speed. Sets the default minimum junction speed the planner plans to at
     /// every buffer block junction, except for starting from rest and end of the buffer, which are always
@@ -35,78 +42,85 @@ pub struct CNCCfgs {
     /// limits or angle between neighboring block line move directions. This is useful for machines that can't
     /// tolerate the tool dwelling for a split second, i.e. 3d printers or laser cutters. If used, this value
     /// should not be much greater than zero or to the minimum value necessary for the machine to work.
-    pub minimum_junction_speed: f32, // (mm/min)
+    pub minimum_junction_speed: Velocity,
 
     /// Sets the minimum feed rate the planner will allow. Any value below it will be set to this minimum
     /// value. This also ensures that a planned motion always completes and accounts for any floating-point
     /// round-off errors. Although not recommended, a lower value than 1.0 mm/min will likely work in smaller
     /// machines, perhaps to 0.1mm/min, but your success may vary based on multiple factors.
-    pub minimum_feed_rate: f32, // (mm/min)
-    /// unit mm
-    pub default_junction_deviation: f32, // mm
-
-    /// unit mm/min
-    pub default_x_max_rate: f32,
-    /// unit mm/min
-    pub default_y_max_rate: f32,
-    /// unit mm/min
-    pub default_z_max_rate: f32,
-    /// 10*60*60 mm/min^2 = 10 mm/sec^2
-    pub default_x_acceleration: f32,
-    /// 10*60*60 mm/min^2 = 10 mm/sec^2
-    pub default_y_acceleration: f32,
-    /// 10*60*60 mm/min^2 = 10 mm/sec^2
-    pub default_z_acceleration: f32,
-
-    pub default_homing_feed_rate: f32, // mm/min
-    pub default_homing_seek_rate: f32, // mm/min
+    pub minimum_feed_rate: Velocity,
+    pub default_junction_deviation: Length,
+
+    pub default_x_max_rate: Velocity,
+    pub default_y_max_rate: Velocity,
+    pub default_z_max_rate: Velocity,
+    pub default_x_acceleration: Acceleration,
+    pub default_y_acceleration: Acceleration,
+    pub default_z_acceleration: Acceleration,
+    /// jerk of the S-curve step profile of a block
+    pub default_jerk: Jerk,
+
+    pub default_homing_feed_rate: Velocity,
+    pub default_homing_seek_rate: Velocity,
 
     ///rapids override value in percent. 1.0 represent 100%
-    pub default_rapid_override: f32, // 100%. don't change this value.
+    pub default_rapid_override: Scalar, // 100%. don't change this value.
+
+    /// tool length, diameter and wear of each tool
+    pub tools: ToolTable,
+
+    /// arc tolerance and segment limits
+    pub arc: ArcSettings,
 }
 
 impl core::default::Default for CNCCfgs {
     fn default() -> Self {
         Self {
-            x_max_travel: 400.0, // mm note: must be a positive value.
-            y_max_travel: 300.0, // mm note: must be a positive value.
-            z_max_travel: 500.0, // mm note: must be a positive value.
-            a_max_travel: 360.0, // °
-            b_max_travel: 360.0, // °
+            x_max_travel: Length::from_mm(400.0),
+            y_max_travel: Length::from_mm(300.0),
+            z_max_travel: Length::from_mm(500.0),
+            a_max_travel: Angle::from_deg(360.0),
+            b_max_travel: Angle::from_deg(360.0),
+            travel_min: Vec3::<Length>::from_mm(Vec3::zero()),
+            soft_limits: false,
 
             x_steps_per_mm: 10.,
             y_steps_per_mm: 10.,
             z_steps_per_mm: 10.,
 
-            minimum_junction_speed: 0.0,      // (mm/min)
-            minimum_feed_rate: 1.0,           // (mm/min)
-            default_junction_deviation: 0.01, // mm
-            default_x_max_rate: 500.0,
-            default_y_max_rate: 500.0,
-            default_z_max_rate: 500.0,
-            default_x_acceleration: 10.0 * 60. * 60.,
-            default_y_acceleration: 10.0 * 60. * 60.,
-            default_z_acceleration: 10.0 * 60. * 60.,
+            minimum_junction_speed: Velocity::from_mm_per_min(0.0),
+            minimum_feed_rate: Velocity::from_mm_per_min(1.0),
+            default_junction_deviation: Length::from_mm(0.01),
+            default_x_max_rate: Velocity::from_mm_per_min(500.0),
+            default_y_max_rate: Velocity::from_mm_per_min(500.0),
+            default_z_max_rate: Velocity::from_mm_per_min(500.0),
+            default_x_acceleration: Acceleration::from_mm_per_sec2(10.0),
+            default_y_acceleration: Acceleration::from_mm_per_sec2(10.0),
+            default_z_acceleration: Acceleration::from_mm_per_sec2(10.0),
+            default_jerk: Jerk::from_mm_per_sec3(100.0),
 
-            default_homing_feed_rate: 50.0,  // mm/min
-            default_homing_seek_rate: 500.0, // mm/min
+            default_homing_feed_rate: Velocity::from_mm_per_min(50.0),
+            default_homing_seek_rate: Velocity::from_mm_per_min(500.0),
 
             default_rapid_override: 1., // 100%. don't change this value.
+
+            tools: ToolTable::new(),
+            arc: ArcSettings::default(),
         }
     }
 }
 
 impl CNCCfgs {
     /// the plane is the plane that the represent orig data
-    pub fn step_pos_to_mm_pos(&self, orig: &Vec3<i32>, plane: &CanonPlane) -> Vec3<f32> {
+    pub fn step_pos_to_mm_pos(&self, orig: &Vec3<i32>, plane: &CanonPlane) -> Vec3<Scalar> {
         let orig_plane = plane;
         let dest_plane = &CanonPlane::CanonPlaneXY;
 
         let d = orig_plane.to_plane(orig, dest_plane);
-        let dest = Vec3::<f32>::new(
-            d.0 as f32 / self.x_steps_per_mm,
-            d.1 as f32 / self.y_steps_per_mm,
-            d.2 as f32 / self.z_steps_per_mm,
+        let dest = Vec3::<Scalar>::new(
+            d.0 as Scalar / self.x_steps_per_mm,
+            d.1 as Scalar / self.y_steps_per_mm,
+            d.2 as Scalar / self.z_steps_per_mm,
         );
         if plane == &CanonPlane::CanonPlaneXY {
             return dest;
@@ -120,7 +134,7 @@ impl CNCCfgs {
         dest
     }
     /// the plane is the plane that the represent orig data
-    pub fn mm_pos_to_step_pos(&self, orig: &Vec3<f32>, plane: &CanonPlane) -> Vec3<i32> {
+    pub fn mm_pos_to_step_pos(&self, orig: &Vec3<Scalar>, plane: &CanonPlane) -> Vec3<i32> {
         let orig_plane = plane;
         let dest_plane = &CanonPlane::CanonPlaneXY;
 
@@ -144,7 +158,7 @@ impl CNCCfgs {
     /// based on unit vector with plane convert a mm(unit is mm) value to a value that unit is step.
     ///
     /// it used in scenarios, e.g. convert distance,velocity that unit is (mm/T) to distance, velocity that unit is (step/T).
-    pub fn mm_to_steps(&self, unit_vec: &Vec3<f32>, plane: &CanonPlane, mm: f32) -> f32 {
+    pub fn mm_to_steps(&self, unit_vec: &Vec3<Scalar>, plane: &CanonPlane, mm: Scalar) -> Scalar {
         //confirm input is unit vector
         assert!((unit_vec.distance() - 1.).abs() < 1.0e-5);
 
@@ -154,55 +168,103 @@ impl CNCCfgs {
 
         let t = unit_vec * mm;
         let t = self.mm_pos_to_step_pos(&t, plane);
+        Vec3::new(t.0 as Scalar, t.1 as Scalar, t.2 as Scalar).distance()
+    }
 
-        // to avoid overflow, temporary convert to unit meter, at last, convert back.
-        let (a, b, c) = (t.0 as f32 / 1000., t.1 as f32 / 1000., t.2 as f32 / 1000.);
-        let length = (a * a + b * b + c * c).sqrt();
-        length * 1000.
+    /// check the bounding box (min, max) of a motion against the travel, Ok if soft limits are off
+    pub fn check_soft_limits(
+        &self,
+        min: &Vec3<Scalar>,
+        max: &Vec3<Scalar>,
+    ) -> Result<(), PlanError> {
+        if !self.soft_limits {
+            return Ok(());
+        }
+        let low = &self.travel_min;
+        let axes = [
+            (Axis::X, min.0, max.0, low.0, self.x_max_travel),
+            (Axis::Y, min.1, max.1, low.1, self.y_max_travel),
+            (Axis::Z, min.2, max.2, low.2, self.z_max_travel),
+        ];
+        for (axis, min, max, low, travel) in axes {
+            let (min, max) = (Length::from_mm(min), Length::from_mm(max));
+            if max > low + travel {
+                let overshoot = max - (low + travel);
+                let bound = Bound::Max;
+                return Err(PlanError::SoftLimit {
+                    axis,
+                    bound,
+                    overshoot,
+                });
+            }
+            if min < low {
+                let overshoot = low - min;
+                let bound = Bound::Min;
+                return Err(PlanError::SoftLimit {
+                    axis,
+                    bound,
+                    overshoot,
+                });
+            }
+        }
+        Ok(())
     }
 
     /// according rapid_rate and MINIMUM_FEED_RATE limit, return vaild velocity.
     #[inline]
-    pub fn get_valid_velocity(&self, rate: &f32, rapid_rate: &f32) -> f32 {
+    pub fn get_valid_velocity(&self, rate: &Scalar, rapid_rate: &Scalar) -> Scalar {
         let nominal_speed = rate.min(*rapid_rate);
-        nominal_speed.max(self.minimum_feed_rate)
+        nominal_speed.max(self.minimum_feed_rate.mm_per_min())
     }
 
     /// input as the direction vecotr, get the max veclocity in this direction
-    pub fn get_max_velocity(&self, input: &Vec3<f32>) -> f32 {
+    pub fn get_max_velocity(&self, input: &Vec3<Scalar>) -> Scalar {
         let unit_vec = input.as_unit_vec();
         return limit_value_by_axis_maximum(
-            self.default_x_max_rate,
-            self.default_y_max_rate,
-            self.default_z_max_rate,
+            self.default_x_max_rate.mm_per_min(),
+            self.default_y_max_rate.mm_per_min(),
+            self.default_z_max_rate.mm_per_min(),
             &unit_vec,
         );
     }
     /// input as the direction vector, get the max acceleration in this direction
-    pub fn get_max_acc(&self, input: &Vec3<f32>) -> f32 {
+    pub fn get_max_acc(&self, input: &Vec3<Scalar>) -> Scalar {
         let unit_vec = input.as_unit_vec();
         return limit_value_by_axis_maximum(
-            self.default_x_acceleration,
-            self.default_y_acceleration,
-            self.default_z_acceleration,
+            self.default_x_acceleration.mm_per_min2(),
+            self.default_y_acceleration.mm_per_min2(),
+            self.default_z_acceleration.mm_per_min2(),
             &unit_vec,
         );
     }
 
+    /// max velocity on a curve with the curvature (1/mm), the centripetal acceleration v^2 * curvature
+    /// is kept in the smallest axis acceleration. it is Scalar::MAX mm/min on a straight path
+    pub fn get_max_curve_velocity(&self, curvature: Scalar) -> Velocity {
+        if curvature <= 0. {
+            return Velocity::from_mm_per_min(Scalar::MAX);
+        }
+        let acceleration = self
+            .default_x_acceleration
+            .min(self.default_y_acceleration)
+            .min(self.default_z_acceleration);
+        Velocity::from_mm_per_min((acceleration.mm_per_min2() / curvature).sqrt())
+    }
+
     /// Compute maximum allowable entry speed at junction by centripetal acceleration approximation.
     pub fn calc_max_junction_speed_sqr(
         &self,
-        previous_unit_vec: &Vec3<f32>,
-        unit_vec: &Vec3<f32>,
-    ) -> f32 {
+        previous_unit_vec: &Vec3<Scalar>,
+        unit_vec: &Vec3<Scalar>,
+    ) -> Scalar {
         let junction_cos_theta = -previous_unit_vec.dot(*unit_vec);
         // NOTE: Computed without any expensive trig, sin() or acos(), by trig half angle identity of cos(theta).
         if junction_cos_theta > 0.999999 {
             //  For a 0 degree acute junction, just set minimum junction speed.
-            return self.minimum_junction_speed * self.minimum_junction_speed;
+            return self.minimum_junction_speed.mm_per_min().powi(2);
         } else if junction_cos_theta < -0.999999 {
             // Junction is a straight line or 180 degrees. Junction speed is infinite.
-            return f32::MAX; // SOME_LARGE_VALUE;
+            return Scalar::MAX; // SOME_LARGE_VALUE;
         }
 
         // 法向量
@@ -212,9 +274,9 @@ impl CNCCfgs {
 
         let sin_theta_d2 = (0.5 * (1.0 - junction_cos_theta)).sqrt(); // Trig half angle identity. Always positive.
 
-        let min_junction_speed_sqr = self.minimum_junction_speed.powi(2);
+        let min_junction_speed_sqr = self.minimum_junction_speed.mm_per_min().powi(2);
         let vecolicity_sqr =
-            (junction_acceleration * self.default_junction_deviation * sin_theta_d2)
+            (junction_acceleration * self.default_junction_deviation.mm() * sin_theta_d2)
                 / (1.0 - sin_theta_d2);
 
         let max_junction_speed_sqr = min_junction_speed_sqr.max(vecolicity_sqr);
@@ -225,11 +287,11 @@ impl CNCCfgs {
     /// NOTE: All system motion commands, such as homing/parking, are not subject to overrides.
     pub fn get_nominal_speed(
         &self,
-        rapid_rate: &f32,
-        programmed_rate: &f32,
+        rapid_rate: &Scalar,
+        programmed_rate: &Scalar,
         is_rapid_motion: bool,
         is_no_feed_override: bool,
-    ) -> f32 {
+    ) -> Scalar {
         let mut nominal_speed = *programmed_rate;
         if is_rapid_motion {
             nominal_speed *= self.default_rapid_override;
@@ -241,29 +303,34 @@ impl CNCCfgs {
             nominal_speed = nominal_speed.min(*rapid_rate);
         }
 
-        nominal_speed.max(self.minimum_feed_rate)
+        nominal_speed.max(self.minimum_feed_rate.mm_per_min())
     }
 }
 
 ///input: max_value is  restraint on each Axial. unit_vec is unit vector define the direction.
 ///
 ///  result is the restraint on the direction
-fn limit_value_by_axis_maximum(max_x: f32, max_y: f32, max_z: f32, unit_vec: &Vec3<f32>) -> f32 {
+fn limit_value_by_axis_maximum(
+    max_x: Scalar,
+    max_y: Scalar,
+    max_z: Scalar,
+    unit_vec: &Vec3<Scalar>,
+) -> Scalar {
     // according vector projection
     let (x, y, z) = {
         // if is zero , means there is no restraint on this axial
         let _x = if unit_vec.0 == 0. {
-            f32::MAX
+            Scalar::MAX
         } else {
             (max_x / unit_vec.0).abs()
         };
         let _y = if unit_vec.1 == 0. {
-            f32::MAX
+            Scalar::MAX
         } else {
             (max_y / unit_vec.1).abs()
         };
         let _z = if unit_vec.2 == 0. {
-            f32::MAX
+            Scalar::MAX
         } else {
             (max_z / unit_vec.2).abs()
         };
@@ -272,3 +339,37 @@ fn limit_value_by_axis_maximum(max_x: f32, max_y: f32, max_z: f32, unit_vec: &Ve
 
     x.min(y).min(z)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::CNCCfgs;
+    use crate::{CanonPlane, Length, Scalar, Vec3};
+
+    /// a 3m gantry, f32 keeps every step at 1000 steps/mm, f64 at 100000 steps/mm
+    #[test]
+    fn test_full_travel_steps() {
+        #[cfg(not(feature = "f64"))]
+        let steps_per_mm = 1000.;
+        #[cfg(feature = "f64")]
+        let steps_per_mm = 100000.;
+        let cfg = CNCCfgs {
+            x_max_travel: Length::from_mm(3000.),
+            x_steps_per_mm: steps_per_mm,
+            y_steps_per_mm: steps_per_mm,
+            z_steps_per_mm: steps_per_mm,
+            ..CNCCfgs::default()
+        };
+        let xy = CanonPlane::CanonPlaneXY;
+        let last = cfg.x_max_travel.to_steps(steps_per_mm).0;
+        for step in (0..=last).rev().step_by(997).chain(last - 100..=last) {
+            let p = Vec3::new(step, 7, -step / 3);
+            let mm = cfg.step_pos_to_mm_pos(&p, &xy);
+            assert_eq!(cfg.mm_pos_to_step_pos(&mm, &xy), p, "{:?}", mm);
+        }
+        // the diagonal of the full travel, within one step
+        let d = Vec3::new(3000., 3000., 0.);
+        let steps = cfg.mm_to_steps(&d.as_unit_vec(), &xy, d.distance());
+        let expected = 3000. * steps_per_mm * (2. as Scalar).sqrt();
+        assert!((steps - expected).abs() <= 1., "{}", steps - expected);
+    }
+}
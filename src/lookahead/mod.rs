@@ -1,8 +1,10 @@
 mod alg;
 mod algorithm_cfg;
+mod tool_table;
 
 pub use alg::{PlanBlock, Planer};
 pub use algorithm_cfg::CNCCfgs;
+pub use tool_table::{Tool, ToolTable};
 /// rotate motor forward or backward
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Direction {
@@ -19,8 +21,38 @@ pub enum Direction {
     Backward = -1,
 }
 
+use crate::{Length, Scalar, Velocity};
 use bitflags::bitflags;
 
+/// linear axis of the planner
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// end of the travel of an axis
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Bound {
+    Min,
+    Max,
+}
+
+/// a motion that [`Planer`] does not plan
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlanError {
+    /// the target is the current position
+    ZeroLength,
+    /// the motion leaves the travel of the axis, see [`CNCCfgs::soft_limits`]. overshoot is the distance
+    /// beyond the bound, it is positive at both bounds
+    SoftLimit {
+        axis: Axis,
+        bound: Bound,
+        overshoot: Length,
+    },
+}
+
 bitflags! {
     #[repr(C)]
     #[derive(Default)]
@@ -40,12 +72,56 @@ bitflags! {
     }
 }
 
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
 pub struct PlanLineData {
-    /// Desired feed rate for line motion. Value is ignored, if rapid motion.
-    pub feed_rate: f32,
+    /// Desired feed rate for line motion, mm/min or 1/min in inverse time. Value is ignored, if rapid motion.
+    /// it is made by [`Self::feed`] and [`Self::inverse_time`], and read by [`Self::feed_velocity`] and
+    /// [`Self::inverse_time_rate`]
+    pub(crate) feed_rate: Scalar,
     /// Desired spindle speed through line motion.
-    pub spindle_speed: f32,
+    pub spindle_speed: Scalar,
     /// condition: BitFlags<PlanCondition>, // Bitflag variable to indicate planner conditions. See [PlanCondition] defines above.
     pub condition: PlanCondition,
 }
+
+impl PlanLineData {
+    /// rapid motion at the max rates
+    pub fn rapid() -> Self {
+        Self {
+            condition: PlanCondition::PL_COND_FLAG_RAPID_MOTION,
+            ..Self::default()
+        }
+    }
+
+    /// feed motion at the rate, e.g. `Velocity::from_inch_per_min(f)` for an F word in G20 mode
+    pub fn feed(rate: Velocity) -> Self {
+        Self {
+            feed_rate: rate.mm_per_min(),
+            ..Self::default()
+        }
+    }
+
+    /// feed motion that completes in 1/per_minute minutes, as G93
+    pub fn inverse_time(per_minute: Scalar) -> Self {
+        Self {
+            feed_rate: per_minute,
+            condition: PlanCondition::PL_COND_FLAG_INVERSE_TIME,
+            ..Self::default()
+        }
+    }
+
+    /// the feed rate of a feed motion in units per minute, None for rapid and inverse time motion
+    pub fn feed_velocity(&self) -> Option<Velocity> {
+        let other =
+            PlanCondition::PL_COND_FLAG_RAPID_MOTION | PlanCondition::PL_COND_FLAG_INVERSE_TIME;
+        (!self.condition.intersects(other)).then(|| Velocity::from_mm_per_min(self.feed_rate))
+    }
+
+    /// the motions per minute of an inverse time motion, None for other motions
+    pub fn inverse_time_rate(&self) -> Option<Scalar> {
+        let inverse_time = PlanCondition::PL_COND_FLAG_INVERSE_TIME;
+        let rapid = PlanCondition::PL_COND_FLAG_RAPID_MOTION;
+        (self.condition.contains(inverse_time) && !self.condition.contains(rapid))
+            .then_some(self.feed_rate)
+    }
+}
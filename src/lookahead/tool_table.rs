@@ -0,0 +1,145 @@
+//! tool table, tool length and diameter used by tool length offset(G43) and cutter radius compensation(G41/G42).
+use crate::Scalar;
+use alloc::collections::btree_map::{BTreeMap, Iter};
+
+/// one tool of the tool table, all values are in mm
+///
+/// wear values are added to the nominal values, so a worn tool normally has a negative wear.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tool {
+    pub length: Scalar,
+    pub diameter: Scalar,
+    pub length_wear: Scalar,
+    pub diameter_wear: Scalar,
+}
+
+impl Tool {
+    pub fn new(length: Scalar, diameter: Scalar) -> Self {
+        Self {
+            length,
+            diameter,
+            ..Self::default()
+        }
+    }
+
+    /// length with wear, it is the value of tool length offset
+    #[inline]
+    pub fn total_length(&self) -> Scalar {
+        self.length + self.length_wear
+    }
+
+    /// diameter with wear
+    #[inline]
+    pub fn total_diameter(&self) -> Scalar {
+        self.diameter + self.diameter_wear
+    }
+
+    /// radius with wear, it is the offset distance of cutter radius compensation
+    #[inline]
+    pub fn radius(&self) -> Scalar {
+        self.total_diameter() * 0.5
+    }
+}
+
+/// tools by tool number(T word)
+///
+/// tool 0 means no tool, it is always a zero tool and can not be set.
+///
+/// example
+/// ```
+/// use ngc_geometry::{CNCCfgs, Tool};
+/// let mut cfg = CNCCfgs::default();
+/// cfg.tools.set(1, Tool::new(50., 6.));
+/// assert_eq!(cfg.tools.radius(1), Some(3.));
+/// assert_eq!(cfg.tools.radius(2), None);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ToolTable {
+    tools: BTreeMap<u16, Tool>,
+}
+
+impl ToolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, number: u16) -> Option<Tool> {
+        if number == 0 {
+            return Some(Tool::default());
+        }
+        self.tools.get(&number).copied()
+    }
+
+    /// add or replace a tool, return the replaced one
+    pub fn set(&mut self, number: u16, tool: Tool) -> Option<Tool> {
+        if number == 0 {
+            return None;
+        }
+        self.tools.insert(number, tool)
+    }
+
+    pub fn get_mut(&mut self, number: u16) -> Option<&mut Tool> {
+        self.tools.get_mut(&number)
+    }
+
+    pub fn remove(&mut self, number: u16) -> Option<Tool> {
+        self.tools.remove(&number)
+    }
+
+    /// tool length with wear
+    pub fn length(&self, number: u16) -> Option<Scalar> {
+        self.get(number).map(|t| t.total_length())
+    }
+
+    /// tool diameter with wear
+    pub fn diameter(&self, number: u16) -> Option<Scalar> {
+        self.get(number).map(|t| t.total_diameter())
+    }
+
+    /// tool radius with wear, e.g. the radius of [`crate::compensation`]
+    pub fn radius(&self, number: u16) -> Option<Scalar> {
+        self.get(number).map(|t| t.radius())
+    }
+
+    /// tools in tool number order, tool 0 is not included
+    pub fn iter(&self) -> Iter<'_, u16, Tool> {
+        self.tools.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tool, ToolTable};
+
+    #[test]
+    fn test_tool_table() {
+        let mut tools = ToolTable::new();
+        assert_eq!(tools.set(0, Tool::new(1., 1.)), None);
+        assert_eq!(tools.get(0), Some(Tool::default()));
+        assert!(tools.is_empty());
+
+        tools.set(3, Tool::new(40., 10.));
+        tools.set(1, Tool::new(50., 6.));
+        let t = tools.get_mut(3).unwrap();
+        t.length_wear = -0.1;
+        t.diameter_wear = -0.2;
+        assert_eq!(tools.length(3), Some(39.9));
+        assert_eq!(tools.diameter(3), Some(9.8));
+        assert_eq!(tools.radius(3), Some(4.9));
+        assert_eq!(tools.length(2), None);
+        assert_eq!(
+            tools.iter().map(|(n, _)| *n).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(tools.remove(1), Some(Tool::new(50., 6.)));
+        assert_eq!(tools.len(), 1);
+    }
+}
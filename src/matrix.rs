@@ -0,0 +1,323 @@
+//! 3x3 and 4x4 matrices for rotations and homogeneous transforms, e.g. rotary axis kinematics and work offsets.
+//!
+//! the matrices are row major and act on column vectors: `m * v`, `a * b` applies b first.
+use super::traits::Real;
+use super::vecx::Vec3;
+
+/// 3x3 matrix, `.0[row][col]`
+///
+/// example, a rotation of 90 degrees around Z
+/// ```
+/// use ngc_geometry::{Mat3, Vec3};
+/// let m = Mat3::rotation_z(core::f32::consts::FRAC_PI_2);
+/// assert!((m * Vec3::new(1., 0., 0.) - Vec3::new(0., 1., 0.)).distance() < 1e-6);
+/// ```
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub struct Mat3<T>(pub [[T; 3]; 3]);
+
+impl<T> Mat3<T>
+where
+    T: Copy
+        + core::cmp::PartialOrd
+        + num_traits::Zero
+        + num_traits::One
+        + core::ops::Add<Output = T>
+        + core::ops::Sub<Output = T>
+        + core::ops::Mul<Output = T>
+        + core::ops::Neg<Output = T>
+        + core::ops::Div<Output = T>,
+{
+    /// rows are the vectors
+    pub fn from_rows(r0: Vec3<T>, r1: Vec3<T>, r2: Vec3<T>) -> Self {
+        Self([[r0.0, r0.1, r0.2], [r1.0, r1.1, r1.2], [r2.0, r2.1, r2.2]])
+    }
+
+    /// columns are the vectors, e.g. the axes of a rotated frame
+    pub fn from_cols(c0: Vec3<T>, c1: Vec3<T>, c2: Vec3<T>) -> Self {
+        Self::from_rows(c0, c1, c2).transpose()
+    }
+
+    pub fn zero() -> Self {
+        Self([[T::zero(); 3]; 3])
+    }
+
+    pub fn identity() -> Self {
+        let mut m = Self::zero();
+        for i in 0..3 {
+            m.0[i][i] = T::one();
+        }
+        m
+    }
+
+    #[inline]
+    pub fn row(&self, i: usize) -> Vec3<T> {
+        Vec3::new(self.0[i][0], self.0[i][1], self.0[i][2])
+    }
+
+    #[inline]
+    pub fn col(&self, i: usize) -> Vec3<T> {
+        Vec3::new(self.0[0][i], self.0[1][i], self.0[2][i])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_rows(self.col(0), self.col(1), self.col(2))
+    }
+
+    pub fn determinant(&self) -> T {
+        self.row(0).dot(self.row(1).cross(self.row(2)))
+    }
+
+    /// None if the matrix is singular. a rotation is inverted by [`Mat3::transpose`] as well
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == T::zero() {
+            return None;
+        }
+        // the rows of the inverse are the cross products of the columns
+        let (c0, c1, c2) = (self.col(0), self.col(1), self.col(2));
+        Some(Self::from_rows(
+            c1.cross(c2) / det,
+            c2.cross(c0) / det,
+            c0.cross(c1) / det,
+        ))
+    }
+}
+
+impl<T> Mat3<T>
+where
+    T: Real,
+{
+    /// ccw rotation around X viewed from +X, as the A axis
+    pub fn rotation_x(angle: T) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let (o, i) = (T::zero(), T::one());
+        Self([[i, o, o], [o, cos, -sin], [o, sin, cos]])
+    }
+
+    /// ccw rotation around Y viewed from +Y, as the B axis
+    pub fn rotation_y(angle: T) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let (o, i) = (T::zero(), T::one());
+        Self([[cos, o, sin], [o, i, o], [-sin, o, cos]])
+    }
+
+    /// ccw rotation around Z viewed from +Z, as the C axis
+    pub fn rotation_z(angle: T) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let (o, i) = (T::zero(), T::one());
+        Self([[cos, -sin, o], [sin, cos, o], [o, o, i]])
+    }
+
+    /// ccw rotation around a unit axis, by rodrigues' formula
+    pub fn from_axis_angle(axis: Vec3<T>, angle: T) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        let k = T::one() - cos;
+        let Vec3(x, y, z) = axis;
+        Self([
+            [cos + x * x * k, x * y * k - z * sin, x * z * k + y * sin],
+            [y * x * k + z * sin, cos + y * y * k, y * z * k - x * sin],
+            [z * x * k - y * sin, z * y * k + x * sin, cos + z * z * k],
+        ])
+    }
+}
+
+impl<T> core::ops::Mul<Vec3<T>> for Mat3<T>
+where
+    T: Copy + core::ops::Add<Output = T> + core::ops::Mul<Output = T>,
+{
+    type Output = Vec3<T>;
+    #[inline]
+    fn mul(self, v: Vec3<T>) -> Self::Output {
+        let row = |r: [T; 3]| r[0] * v.0 + r[1] * v.1 + r[2] * v.2;
+        Vec3(row(self.0[0]), row(self.0[1]), row(self.0[2]))
+    }
+}
+
+impl<T> core::ops::Mul for Mat3<T>
+where
+    T: Copy + core::ops::Add<Output = T> + core::ops::Mul<Output = T>,
+{
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let a = &self.0;
+        let b = &rhs.0;
+        Self(core::array::from_fn(|i| {
+            core::array::from_fn(|j| a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j])
+        }))
+    }
+}
+
+/// 4x4 homogeneous transform, `.0[row][col]`, a point is `(x, y, z, 1)` and a direction is `(x, y, z, 0)`
+///
+/// example, a work offset rotated by 90 degrees around Z, then moved to (100, 50, 0)
+/// ```
+/// use ngc_geometry::{Mat3, Mat4, Vec3};
+/// let rotation = Mat3::rotation_z(core::f32::consts::FRAC_PI_2);
+/// let work = Mat4::from_rotation_translation(rotation, Vec3::new(100., 50., 0.));
+/// let machine = work.transform_point(Vec3::new(10., 0., -1.));
+/// assert!((machine - Vec3::new(100., 60., -1.)).distance() < 1e-5);
+/// let back = work.inverse_affine().unwrap().transform_point(machine);
+/// assert!((back - Vec3::new(10., 0., -1.)).distance() < 1e-5);
+/// ```
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub struct Mat4<T>(pub [[T; 4]; 4]);
+
+impl<T> Mat4<T>
+where
+    T: Copy
+        + core::cmp::PartialOrd
+        + num_traits::Zero
+        + num_traits::One
+        + core::ops::Add<Output = T>
+        + core::ops::Sub<Output = T>
+        + core::ops::Mul<Output = T>
+        + core::ops::Neg<Output = T>
+        + core::ops::Div<Output = T>,
+{
+    pub fn zero() -> Self {
+        Self([[T::zero(); 4]; 4])
+    }
+
+    pub fn identity() -> Self {
+        let mut m = Self::zero();
+        for i in 0..4 {
+            m.0[i][i] = T::one();
+        }
+        m
+    }
+
+    /// rotate first, then translate
+    pub fn from_rotation_translation(rotation: Mat3<T>, translation: Vec3<T>) -> Self {
+        let mut m = Self::identity();
+        for i in 0..3 {
+            m.0[i][..3].copy_from_slice(&rotation.0[i]);
+        }
+        m.0[0][3] = translation.0;
+        m.0[1][3] = translation.1;
+        m.0[2][3] = translation.2;
+        m
+    }
+
+    pub fn from_translation(translation: Vec3<T>) -> Self {
+        Self::from_rotation_translation(Mat3::identity(), translation)
+    }
+
+    pub fn from_rotation(rotation: Mat3<T>) -> Self {
+        Self::from_rotation_translation(rotation, Vec3::zero())
+    }
+
+    /// the upper left 3x3 part
+    pub fn rotation(&self) -> Mat3<T> {
+        Mat3(core::array::from_fn(|i| {
+            core::array::from_fn(|j| self.0[i][j])
+        }))
+    }
+
+    pub fn translation(&self) -> Vec3<T> {
+        Vec3::new(self.0[0][3], self.0[1][3], self.0[2][3])
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self(core::array::from_fn(|i| {
+            core::array::from_fn(|j| self.0[j][i])
+        }))
+    }
+
+    /// transform a position, the translation applies
+    pub fn transform_point(&self, p: Vec3<T>) -> Vec3<T> {
+        self.rotation() * p + self.translation()
+    }
+
+    /// transform a direction, the translation does not apply
+    pub fn transform_vector(&self, v: Vec3<T>) -> Vec3<T> {
+        self.rotation() * v
+    }
+
+    /// inverse of a transform whose last row is (0, 0, 0, 1), None if its rotation part is singular
+    pub fn inverse_affine(&self) -> Option<Self> {
+        let inv = self.rotation().inverse()?;
+        Some(Self::from_rotation_translation(
+            inv,
+            -(inv * self.translation()),
+        ))
+    }
+}
+
+impl<T> core::ops::Mul for Mat4<T>
+where
+    T: Copy + core::ops::Add<Output = T> + core::ops::Mul<Output = T>,
+{
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let a = &self.0;
+        let b = &rhs.0;
+        Self(core::array::from_fn(|i| {
+            core::array::from_fn(|j| {
+                a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j] + a[i][3] * b[3][j]
+            })
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mat3, Mat4};
+    use crate::Vec3;
+    use core::f32::consts::FRAC_PI_2;
+
+    fn near(a: Vec3<f32>, b: Vec3<f32>) -> bool {
+        (a - b).distance() < 1e-5
+    }
+
+    #[test]
+    fn test_rotations() {
+        let (x, y, z) = (
+            Vec3::new(1., 0., 0.),
+            Vec3::new(0., 1., 0.),
+            Vec3::new(0., 0., 1.),
+        );
+        // right handed: x -> y -> z -> x
+        assert!(near(Mat3::rotation_z(FRAC_PI_2) * x, y));
+        assert!(near(Mat3::rotation_x(FRAC_PI_2) * y, z));
+        assert!(near(Mat3::rotation_y(FRAC_PI_2) * z, x));
+        assert_eq!(x.cross(y), z);
+
+        let axis = Vec3::new(1., 2., -2.).as_unit_vec();
+        let m = Mat3::from_axis_angle(axis, 0.7);
+        assert!(near(m * axis, axis));
+        assert!((m.determinant() - 1.).abs() < 1e-5);
+        let p = Vec3::new(3., -1., 4.);
+        assert!(near(m.inverse().unwrap() * (m * p), p));
+        assert!(near(m.transpose() * (m * p), p));
+        let m = Mat3::from_axis_angle(x, 0.7);
+        let r = Mat3::rotation_x(0.7);
+        for i in 0..3 {
+            assert!(near(m.row(i), r.row(i)));
+        }
+        assert_eq!(Mat3::<f32>::zero().inverse(), None);
+    }
+
+    #[test]
+    fn test_transforms() {
+        // a 4th axis: rotate the part by A, then the table is at (0, 0, 50)
+        let table = Mat4::from_translation(Vec3::new(0., 0., 50.));
+        let a = Mat4::from_rotation(Mat3::rotation_x(FRAC_PI_2));
+        let t = table * a;
+        assert!(near(
+            t.transform_point(Vec3::new(1., 10., 0.)),
+            Vec3::new(1., 0., 60.)
+        ));
+        assert!(near(
+            t.transform_vector(Vec3::new(0., 0., 1.)),
+            Vec3::new(0., -1., 0.)
+        ));
+        let i = t.inverse_affine().unwrap() * t;
+        let identity = Mat4::<f32>::identity();
+        for r in 0..4 {
+            for c in 0..4 {
+                assert!((i.0[r][c] - identity.0[r][c]).abs() < 1e-5);
+            }
+        }
+        assert_eq!(t.transpose().transpose(), t);
+    }
+}
@@ -1,5 +1,6 @@
 //! [形象解释PID算法](https://www.cnblogs.com/shangdawei/p/4825259.html)
 
+use crate::Scalar;
 #[allow(unused_imports)]
 use num_traits::Float;
 
@@ -9,23 +10,23 @@ use num_traits::Float;
 ///
 pub struct PID {
     /// Controller Setpoint
-    setpoint: f32,
+    setpoint: Scalar,
     // Tuning parameters
     /// Stores the gain for the Proportional term
-    kp: f32,
+    kp: Scalar,
     /// Stores the gain for the Integral term
-    ki: f32,
+    ki: Scalar,
     /// Stores the gain for the Derivative term
-    kd: f32,
+    kd: Scalar,
 
-    e_prev: f32,
-    e_last: f32,
+    e_prev: Scalar,
+    e_last: Scalar,
 
-    lasto: Option<f32>,
+    lasto: Option<Scalar>,
 }
 
 impl PID {
-    pub fn new(set: f32, kp: f32, ki: f32, kd: f32) -> Self {
+    pub fn new(set: Scalar, kp: Scalar, ki: Scalar, kd: Scalar) -> Self {
         let s = Self {
             setpoint: set,
 
@@ -43,11 +44,11 @@ impl PID {
         s
     }
 
-    pub fn set_point(&mut self, set: f32) {
+    pub fn set_point(&mut self, set: Scalar) {
         self.setpoint = set;
     }
     /// Current Process Value
-    pub fn compute(&mut self, input: f32) -> f32 /*output*/ {
+    pub fn compute(&mut self, input: Scalar) -> Scalar /*output*/ {
         let delta = self.pidctrl_increa(input);
 
         //todo err_max, err_min
@@ -63,8 +64,8 @@ impl PID {
     /// # delta=Kp*[e(t) - e(t-1)] + Ki*e(t) + Kd*[e(t) - 2*e(t-1) +e(t-2)]
     fn pidctrl_increa(
         &mut self,
-        real_output: f32, /*采样时刻被控对象实际输出*/
-    ) -> f32 {
+        real_output: Scalar, /*采样时刻被控对象实际输出*/
+    ) -> Scalar {
         let err = self.setpoint - real_output;
 
         let delta = self.kp * (err - self.e_last)
@@ -82,7 +83,7 @@ impl PID {
     /// Sets the gain for the Proportional (Kp), Integral (Ki) and Derivative (Kd)
     /// terms.
     ///
-    pub fn tune(&mut self, kp: f32, ki: f32, _kd: f32) {
+    pub fn tune(&mut self, kp: Scalar, ki: Scalar, _kd: Scalar) {
         // Check for validity
         if kp < 0. || ki < 0. || _kd < 0. {
             return;
@@ -1,4 +1,8 @@
-use crate::{vecx::Vec3, Line3D};
+use crate::Scalar;
+use crate::{
+    vecx::{Point3, Vec3},
+    Line3D,
+};
 #[allow(unused_imports)]
 use num_traits::{Float, Inv};
 
@@ -10,34 +14,31 @@ use super::{
 /// a implement of LinearMotionProfile, it use SCurve profile.
 /// notes: it generate profile that not include direction information.
 /// it means you should indepdent deal the direction by youself.
-pub struct LinearMotionSCurve {
-    line: Line3D,
+///
+/// other step paths, e.g. [`crate::Arc3D`], are used by [`LinearMotionSCurve::with_path`],
+/// they yield signed positions.
+pub struct LinearMotionSCurve<P = Line3D> {
+    line: P,
     scurve: SCurve,
 
-    index: f32,
-    percent: f32,
+    index: Scalar,
+    percent: Scalar,
 }
 
 impl LinearMotionSCurve {
-    ///distance unit is S, velocity unit is S/T, acceleration unit is S/T^2.
+    ///distance unit is S, velocity unit is S/T, acceleration unit is S/T^2, jerk unit is S/T^3.
     /// it dont care about direction. on other word, the algorithem dont care
     /// about sign of velocity, acceleration and distance.
     pub fn new(
-        distance: f32,
+        distance: Scalar,
         steps: &Vec3<i32>,
-        max_acceleration: f32,
-        max_velocity: f32,
-        enter_velocity: f32,
-        end_velocity: f32,
+        max_acceleration: Scalar,
+        max_jerk: Scalar,
+        max_velocity: Scalar,
+        enter_velocity: Scalar,
+        end_velocity: Scalar,
     ) -> Self {
-        let (distance, steps, max_acceleration, max_velocity, enter_velocity, end_velocity) = (
-            distance.abs(),
-            steps.abs(),
-            max_acceleration.abs(),
-            max_velocity.abs(),
-            enter_velocity.abs(),
-            end_velocity.abs(),
-        );
+        let steps = steps.abs();
 
         //////////////////////////////////
         let line = Line3D::new(Vec3::<i32>::new(0, 0, 0), steps);
@@ -48,10 +49,44 @@ impl LinearMotionSCurve {
                 steps, max_acceleration, max_velocity, enter_velocity, end_velocity, line.len(),
             );
         }
+        Self::with_path(
+            line,
+            distance,
+            max_acceleration,
+            max_jerk,
+            max_velocity,
+            enter_velocity,
+            end_velocity,
+        )
+    }
+}
+
+impl<P> LinearMotionSCurve<P>
+where
+    P: ExactSizeIterator<Item = Point3>,
+{
+    /// profile along any step path, distance is the travel of the whole path
+    pub fn with_path(
+        line: P,
+        distance: Scalar,
+        max_acceleration: Scalar,
+        max_jerk: Scalar,
+        max_velocity: Scalar,
+        enter_velocity: Scalar,
+        end_velocity: Scalar,
+    ) -> Self {
+        let (distance, max_acceleration, max_jerk, max_velocity, enter_velocity, end_velocity) = (
+            distance.abs(),
+            max_acceleration.abs(),
+            max_jerk.abs(),
+            max_velocity.abs(),
+            enter_velocity.abs(),
+            end_velocity.abs(),
+        );
 
         let scurve = {
             let constraints = SCurveConstraints {
-                max_jerk: 3.0,
+                max_jerk,
                 max_acceleration,
                 max_velocity,
             };
@@ -64,7 +99,7 @@ impl LinearMotionSCurve {
             };
             SCurve::new(&constraints, &start_conditions)
         };
-        let percent = scurve.params.time_intervals.total_duration() / (line.len() + 1) as f32;
+        let percent = scurve.params.time_intervals.total_duration() / (line.len() + 1) as Scalar;
 
         Self {
             line,
@@ -76,20 +111,23 @@ impl LinearMotionSCurve {
     }
 }
 
-impl LinearMotionProfile for LinearMotionSCurve {
-    fn next_profile(&mut self) -> Option<(Vec3<i32>, f32 /*velocity*/)> {
+impl<P> LinearMotionProfile for LinearMotionSCurve<P>
+where
+    P: ExactSizeIterator<Item = Point3>,
+{
+    fn next_profile(&mut self) -> Option<(Vec3<i32>, Scalar /*velocity*/)> {
         if let Some(p) = self.line.next() {
             let velocity = self.scurve.params.eval_velocity(self.index * self.percent);
             self.index += 1.;
 
-            return Some((p, velocity as f32));
+            return Some((p, velocity as Scalar));
         }
         None
     }
 }
 
-pub struct LinearMotionTrapezoidal {
-    line: Line3D,
+pub struct LinearMotionTrapezoidal<P = Line3D> {
+    line: P,
     trap: Trapezoidal,
 }
 
@@ -97,14 +135,35 @@ impl LinearMotionTrapezoidal {
     #[allow(dead_code)]
     pub fn new(
         steps: &Vec3<i32>,
-        target_accel: f32,
-        max_velocity: f32,
-        enter_velocity: f32,
-        end_velocity: f32,
+        target_accel: Scalar,
+        max_velocity: Scalar,
+        enter_velocity: Scalar,
+        end_velocity: Scalar,
     ) -> Self {
         //////////////////////////////////
         let line = Line3D::new(Vec3::<i32>::new(0, 0, 0), steps.abs());
+        Self::with_path(
+            line,
+            target_accel,
+            max_velocity,
+            enter_velocity,
+            end_velocity,
+        )
+    }
+}
 
+impl<P> LinearMotionTrapezoidal<P>
+where
+    P: ExactSizeIterator<Item = Point3>,
+{
+    /// profile along any step path
+    pub fn with_path(
+        line: P,
+        target_accel: Scalar,
+        max_velocity: Scalar,
+        enter_velocity: Scalar,
+        end_velocity: Scalar,
+    ) -> Self {
         let conditions = Conditions {
             enter_velocity,
             end_velocity,
@@ -116,8 +175,11 @@ impl LinearMotionTrapezoidal {
     }
 }
 
-impl LinearMotionProfile for LinearMotionTrapezoidal {
-    fn next_profile(&mut self) -> Option<(Vec3<i32>, f32 /*velocity*/)> {
+impl<P> LinearMotionProfile for LinearMotionTrapezoidal<P>
+where
+    P: ExactSizeIterator<Item = Point3>,
+{
+    fn next_profile(&mut self) -> Option<(Vec3<i32>, Scalar /*velocity*/)> {
         if let Some(delay) = self.trap.next_delay() {
             if let Some(p) = self.line.next() {
                 return Some((p, delay.inv()));
@@ -129,6 +191,7 @@ impl LinearMotionProfile for LinearMotionTrapezoidal {
 
 #[cfg(test)]
 mod tests {
+    use crate::Scalar;
 
     use super::{
         LinearMotionProfile,
@@ -136,20 +199,47 @@ mod tests {
         // LinearMotionTrapezoidal,
         Vec3,
     };
+    use crate::{Arc3D, Point, Point3};
     use num_traits::Inv;
 
+    #[test]
+    fn stack_arc_scurve() {
+        // half circle of radius 100 steps
+        let arc = Arc3D::new(
+            Point3::new(100, 0, 0),
+            Point3::new(-100, 0, 0),
+            Point::new(0, 0),
+            true,
+            1,
+        );
+        let len = arc.len();
+        let mut linear =
+            LinearMotionSCurve::with_path(arc, crate::consts::PI * 100., 360000., 3., 48., 0., 0.);
+
+        let mut n = 0;
+        let mut last = None;
+        while let Some((p, v)) = linear.next_profile() {
+            assert!(v >= 0.);
+            last = Some(p);
+            n += 1;
+        }
+        assert_eq!(n, len);
+        assert_eq!(last, Some(Vec3::new(-100, 0, 0)));
+    }
+
     #[test]
     fn stack_new_scurve() {
-        let enter_velocity: f32 = 48.0;
-        let end_velocity: f32 = 0.0;
-        let max_acceleration: f32 = 360000.;
-        let max_velocity: f32 = 48.0;
+        let enter_velocity: Scalar = 48.0;
+        let end_velocity: Scalar = 0.0;
+        let max_acceleration: Scalar = 360000.;
+        let max_velocity: Scalar = 48.0;
         let steps: Vec3<i32> = Vec3::new(200, 700, 0);
 
         let mut linear = LinearMotionSCurve::new(
             700.,
             &steps,
             max_acceleration,
+            3.,
             max_velocity,
             enter_velocity,
             end_velocity,
@@ -158,9 +248,9 @@ mod tests {
         let total_duration = linear.scurve.params.time_intervals.total_duration();
         println!("total duration:{duration}", duration = total_duration);
 
-        let mut all = Vec::<f32>::new();
-        let mut all_inv = Vec::<f32>::new();
-        let mut sum: f32 = 0.;
+        let mut all = Vec::<Scalar>::new();
+        let mut all_inv = Vec::<Scalar>::new();
+        let mut sum: Scalar = 0.;
         while let Some(prf) = linear.next_profile() {
             all.push(prf.1);
 
@@ -6,7 +6,7 @@ pub mod trapezoidal;
 pub use linearmotion::{LinearMotionSCurve, LinearMotionTrapezoidal};
 pub use scurve::{SCurve, SCurveConstraints, SCurveStartConditions};
 
-use crate::Vec3;
+use crate::{Scalar, Vec3};
 /// Abstract interface for motion profiles. e.g. using SCureve profile, get related motion profile
 ///
 /// Implemented by all motion profiles in this library. Can be used to
@@ -15,9 +15,10 @@ use crate::Vec3;
 pub trait LinearMotionProfile: Sized {
     /// Return the next step positon and exist-velocity
     ///
-    /// Produces the velocity for the next step. The unit of this velocity is
-    /// implementation-defined. when no more steps need to betaken, `None` is returned.
+    /// Produces the velocity for the next step. The unit of this velocity is the one of the profile
+    /// parameters, e.g. mm/min for [`crate::PlanBlock::to_step_profile`], which passes the mm, mm/min and
+    /// mm/min^2 of the planer. when no more steps need to betaken, `None` is returned.
     /// on other word, the motion has ended.
     ///
-    fn next_profile(&mut self) -> Option<(Vec3<i32>, f32 /*exist-velocity*/)>;
+    fn next_profile(&mut self) -> Option<(Vec3<i32>, Scalar /*exist-velocity*/)>;
 }
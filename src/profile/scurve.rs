@@ -4,6 +4,7 @@
 /// Trajectory_Planning_for_Automatic_Machines_and_Robots.pdf book
 ///
 // use core::ops::Neg;
+use crate::Scalar;
 #[allow(unused_imports)]
 use num_traits::{Float, Inv};
 
@@ -34,9 +35,9 @@ impl SCurve {
  */
 #[derive(Clone, Debug, Default)]
 pub struct SCurveConstraints {
-    pub max_jerk: f32,
-    pub max_acceleration: f32,
-    pub max_velocity: f32,
+    pub max_jerk: Scalar,
+    pub max_acceleration: Scalar,
+    pub max_velocity: Scalar,
 }
 
 /// Enum which is used to select whether you want to calculate
@@ -53,20 +54,20 @@ pub enum Derivative {
 #[derive(Clone, Debug, Default)]
 pub struct SCurveTimeIntervals {
     ///  time-interval in which the jerk is constant (j max or j min ) during the acceleration phase
-    pub t_j1: f32,
+    pub t_j1: Scalar,
     /// time-interval in which the jerk is constant (j max or j min ) during the deceleration phase
-    pub t_j2: f32,
+    pub t_j2: Scalar,
     ///   Acceleration period
-    pub t_a: f32,
+    pub t_a: Scalar,
     ///  constant velocity period
-    pub t_v: f32,
+    pub t_v: Scalar,
     ///   deceleration period
-    pub t_d: f32,
+    pub t_d: Scalar,
 }
 
 impl SCurveTimeIntervals {
     /// calculates the total duration of the S-Curve
-    pub fn total_duration(&self) -> f32 {
+    pub fn total_duration(&self) -> Scalar {
         self.t_a + self.t_d + self.t_v
     }
     fn is_max_acceleration_not_reached(&self) -> bool {
@@ -78,13 +79,13 @@ impl SCurveTimeIntervals {
 #[derive(Clone, Debug)]
 pub struct SCurveStartConditions {
     /// start position
-    pub q0: f32,
+    pub q0: Scalar,
     /// end position
-    pub q1: f32,
+    pub q1: Scalar,
     ///start velocity
-    pub v0: f32,
+    pub v0: Scalar,
     ///end velocity
-    pub v1: f32,
+    pub v1: Scalar,
 }
 
 impl Default for SCurveStartConditions {
@@ -100,7 +101,7 @@ impl Default for SCurveStartConditions {
 
 impl SCurveStartConditions {
     /// displacement
-    fn h(&self) -> f32 {
+    fn h(&self) -> Scalar {
         self.q1 - self.q0
     }
 }
@@ -111,15 +112,15 @@ pub struct SCurveParameters {
     /// tine intervals of the Trajectory
     pub time_intervals: SCurveTimeIntervals,
     /// maximum jerk
-    pub j_max: f32,
+    pub j_max: Scalar,
     /// minimum jerk
-    pub j_min: f32,
+    pub j_min: Scalar,
     ///maximum achieved acceleration during the acceleration phase
-    pub a_lim_a: f32,
+    pub a_lim_a: Scalar,
     /// minimum achieved acceleration during the deceleration phase
-    pub a_lim_d: f32,
+    pub a_lim_d: Scalar,
     /// maximum  achieved velocity
-    pub v_lim: f32,
+    pub v_lim: Scalar,
     /// The start conditions of the S-Curve
     pub conditions: SCurveStartConditions,
 }
@@ -141,7 +142,7 @@ impl SCurveParameters {
     }
 
     #[allow(dead_code)]
-    fn eval_position(&self, t: f32) -> f32 {
+    fn eval_position(&self, t: Scalar) -> Scalar {
         let p = self;
         let times = &p.time_intervals;
         if t < 0. {
@@ -181,7 +182,7 @@ impl SCurveParameters {
         }
     }
 
-    pub fn eval_velocity(&self, t: f32) -> f32 {
+    pub fn eval_velocity(&self, t: Scalar) -> Scalar {
         let p = self;
         let times = &p.time_intervals;
         if t < 0. {
@@ -207,7 +208,7 @@ impl SCurveParameters {
     }
 
     #[allow(dead_code)]
-    fn eval_acceleration(&self, t: f32) -> f32 {
+    fn eval_acceleration(&self, t: Scalar) -> Scalar {
         let p = self;
         let times = &p.time_intervals;
         if t < 0. {
@@ -268,9 +269,9 @@ impl SCurveInput {
     /// too much. But if it returns yes it is certainly doable. If it returns false it can still work by reducing acceleration and velocity
     #[allow(dead_code)]
     pub fn is_trajectory_feasible(&self) -> bool {
-        let t_j_star: f32 = f32::min(
-            f32::sqrt(
-                f32::abs(self.start_conditions.v1 - self.start_conditions.v0)
+        let t_j_star: Scalar = Scalar::min(
+            Scalar::sqrt(
+                Scalar::abs(self.start_conditions.v1 - self.start_conditions.v0)
                     / self.constraints.max_jerk,
             ),
             self.constraints.max_acceleration / self.constraints.max_jerk,
@@ -281,7 +282,7 @@ impl SCurveInput {
                 > 0.5
                     * (self.start_conditions.v1 + self.start_conditions.v0)
                     * (t_j_star
-                        + f32::abs(self.start_conditions.v1 - self.start_conditions.v0)
+                        + Scalar::abs(self.start_conditions.v1 - self.start_conditions.v0)
                             / self.constraints.max_acceleration);
         }
         if t_j_star < self.constraints.max_acceleration / self.constraints.max_jerk {
@@ -303,7 +304,7 @@ impl SCurveInput {
         let mut times = SCurveTimeIntervals::default();
         let mut new_input = self.clone();
         if self.is_a_max_not_reached() {
-            times.t_j1 = f32::sqrt(
+            times.t_j1 = Scalar::sqrt(
                 (new_input.constraints.max_velocity - self.start_conditions.v0)
                     / new_input.constraints.max_jerk,
             );
@@ -316,7 +317,7 @@ impl SCurveInput {
         }
 
         if self.is_a_min_not_reached() {
-            times.t_j2 = f32::sqrt(
+            times.t_j2 = Scalar::sqrt(
                 (new_input.constraints.max_velocity - self.start_conditions.v1)
                     / new_input.constraints.max_jerk,
             );
@@ -374,11 +375,11 @@ impl SCurveInput {
                         * (self.start_conditions.v0 + self.start_conditions.v1));
         let t_a = (self.constraints.max_acceleration.powi(2) / self.constraints.max_jerk
             - 2. * self.start_conditions.v0
-            + f32::sqrt(delta))
+            + Scalar::sqrt(delta))
             / (2. * self.constraints.max_acceleration);
         let t_d = (self.constraints.max_acceleration.powi(2) / self.constraints.max_jerk
             - 2. * self.start_conditions.v1
-            + f32::sqrt(delta))
+            + Scalar::sqrt(delta))
             / (2. * self.constraints.max_acceleration);
         let t_v = 0.;
         SCurveTimeIntervals {
@@ -415,7 +416,7 @@ impl SCurveInput {
             times.t_d = 2. * self.start_conditions.h()
                 / (self.start_conditions.v0 + self.start_conditions.v1);
             times.t_j2 = (new_input.constraints.max_jerk * self.start_conditions.h()
-                - f32::sqrt(
+                - Scalar::sqrt(
                     new_input.constraints.max_jerk
                         * (new_input.constraints.max_jerk * self.start_conditions.h().powi(2)
                             + (self.start_conditions.v0 + self.start_conditions.v1).powi(2)
@@ -430,7 +431,7 @@ impl SCurveInput {
             times.t_a = 2. * self.start_conditions.h()
                 / (self.start_conditions.v0 + self.start_conditions.v1);
             times.t_j2 = (new_input.constraints.max_jerk * self.start_conditions.h()
-                - f32::sqrt(
+                - Scalar::sqrt(
                     new_input.constraints.max_jerk
                         * (new_input.constraints.max_jerk * self.start_conditions.h().powi(2)
                             - (self.start_conditions.v0 + self.start_conditions.v1).powi(2)
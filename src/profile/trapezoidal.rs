@@ -4,6 +4,7 @@
 // use core::ops;
 
 // use core::cmp::max;
+use crate::Scalar;
 #[allow(unused_imports)]
 use num_traits::{clamp_min, Float, Inv /*clamp_max,One, Zero*/};
 
@@ -11,11 +12,11 @@ use num_traits::{clamp_min, Float, Inv /*clamp_max,One, Zero*/};
 #[derive(Clone, Debug)]
 pub struct Conditions {
     ///start velocity: unit is steps per unit-time
-    pub enter_velocity: f32,
+    pub enter_velocity: Scalar,
     ///end velocity: unit is steps per unit-time
-    pub end_velocity: f32,
+    pub end_velocity: Scalar,
     ///: unit is steps per (unit-time)^2
-    pub target_accel: f32,
+    pub target_accel: Scalar,
 }
 
 impl Default for Conditions {
@@ -69,14 +70,14 @@ pub struct Trapezoidal {
      *    │
      */
     /// it is \cfrac{1}{max-velocity}
-    delay_min: f32,
-    delay_initial: f32,
-    delay_prev: f32,
+    delay_min: Scalar,
+    delay_initial: Scalar,
+    delay_prev: Scalar,
 
-    target_accel: f32,
+    target_accel: Scalar,
     steps_left: u32,
 
-    end_velocity: f32,
+    end_velocity: Scalar,
 }
 
 impl Trapezoidal {
@@ -95,7 +96,7 @@ impl Trapezoidal {
     ///
     /// Panics, if `target_accel` is zero.
     #[allow(dead_code)]
-    pub fn new(cond: Option<Conditions>, max_velocity: Option<f32>, num_steps: u32) -> Self {
+    pub fn new(cond: Option<Conditions>, max_velocity: Option<Scalar>, num_steps: u32) -> Self {
         let conditions = if let Some(v) = cond {
             v
         } else {
@@ -126,7 +127,7 @@ impl Trapezoidal {
     }
 
     // if input max_velocity is invalid, re arrange it
-    fn get_max_velocity(cond: &Conditions, max_velocity: Option<f32>, num_steps: u32) -> f32 {
+    fn get_max_velocity(cond: &Conditions, max_velocity: Option<Scalar>, num_steps: u32) -> Scalar {
         let sv = &cond.enter_velocity;
         let ev = &cond.end_velocity;
         let a = &cond.target_accel;
@@ -154,7 +155,7 @@ impl Trapezoidal {
         // input max velocity is too large to complete steps with required end_velocity in limited distance
         let max = if dis > num_steps {
             // minimum requirements
-            let t = ((num_steps as f32 + temp) * a).sqrt();
+            let t = ((num_steps as Scalar + temp) * a).sqrt();
             t
         } else {
             max_velocity
@@ -163,25 +164,25 @@ impl Trapezoidal {
         max
     }
 
-    pub fn next_delay(&mut self) -> Option<f32> {
+    pub fn next_delay(&mut self) -> Option<Scalar> {
         let mode = RampMode::compute(self);
 
         // Compute the delay for the next step. [22]
         // p_i=p_{i-1} (1+m*p^2_{i-1} + \cfrac{3}{2}(m*p^2_{i-1})^2)
         let q = self.target_accel * self.delay_prev * self.delay_prev;
-        let addend = 1.5_f32 * q * q;
+        let addend = 1.5 * q * q;
         let delay_next = match mode {
             RampMode::Idle => {
                 return None;
             }
             RampMode::RampUp => {
-                let delay_next = self.delay_prev * (1.0_f32 - q + addend);
+                let delay_next = self.delay_prev * (1.0 - q + addend);
                 //confirm all veclocity is lee than the max veclocity
                 clamp_min(delay_next, self.delay_min)
             }
             RampMode::Plateau => self.delay_prev,
             RampMode::RampDown => {
-                let delay_next = self.delay_prev * (1.0_f32 + q + addend);
+                let delay_next = self.delay_prev * (1.0 + q + addend);
                 delay_next
             }
         };
@@ -193,7 +194,7 @@ impl Trapezoidal {
     }
 
     #[allow(dead_code)]
-    pub fn next_velocity(&mut self) -> Option<f32> {
+    pub fn next_velocity(&mut self) -> Option<Scalar> {
         self.next_delay().map(|delay| delay.inv())
     }
 }
@@ -0,0 +1,795 @@
+//! spline interpolation, G5 cubic and G5.1 quadratic bezier splines, G5.2/G5.3 NURBS
+//!
+//! a bezier curve is cut into segments of equal parameter steps, the count comes from the second differences
+//! of the control points (Wang's formula), so every segment is within the chord tolerance.
+//! a NURBS curve is cut in adaptive parameter steps, see [`Nurbs`].
+use super::vecx::Vec3;
+use crate::ArcSettings;
+use crate::Scalar;
+use alloc::vec::Vec;
+
+pub(crate) mod config {
+    /// the default limit of the length difference between a NURBS segment and its planned length, as a part
+    /// of the planned length. a segment of the same time at constant feed has the same length, so this is
+    /// the feed-rate fluctuation.
+    pub const NURBS_DEFAULT_FEED_FLUCTUATION: crate::Scalar = 0.01;
+
+    /// correction iterations of a NURBS parameter step to meet the feed-rate fluctuation
+    pub const NURBS_STEP_CORRECTIONS: u32 = 4;
+
+    /// the highest NURBS degree, the basis functions are computed in fixed arrays of this size
+    pub const NURBS_MAX_DEGREE: usize = 7;
+}
+
+/// a point of the flattened curve with the curve shape there
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SplinePoint {
+    pub position: Vec3<Scalar>,
+    /// unit vector of the moving direction
+    pub tangent: Vec3<Scalar>,
+    /// 1/mm, 0 on a straight section
+    pub curvature: Scalar,
+    /// curve parameter, 0 is the start and 1 is the target
+    pub fraction: Scalar,
+}
+
+/// cubic bezier curve, a quadratic one is elevated to the same cubic curve.
+///
+/// in its core::iter::Iterator implement, all output points include end point, not include start point.
+/// the tolerance and max_segment_length of [`ArcSettings`] apply to it as to [`crate::Arc`].
+///
+/// example
+/// ```
+/// use ngc_geometry::{Bezier, Vec3};
+/// let curve = Bezier::new_cubic(
+///     Vec3::new(0., 0., 0.),
+///     Vec3::new(0., 10., 0.),
+///     Vec3::new(10., 10., 0.),
+///     Vec3::new(10., 0., 0.),
+/// );
+/// let points: Vec<_> = curve.collect();
+/// assert_eq!(points.last(), Some(&Vec3::new(10., 0., 0.)));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Bezier {
+    /// start, two control points and target
+    p: [Vec3<Scalar>; 4],
+    settings: ArcSettings,
+    segments: u32,
+    index: u32,
+}
+
+impl Bezier {
+    /// G5, the curve leaves start towards c1 and arrives at target from c2
+    pub fn new_cubic(
+        start: Vec3<Scalar>,
+        c1: Vec3<Scalar>,
+        c2: Vec3<Scalar>,
+        target: Vec3<Scalar>,
+    ) -> Self {
+        Self {
+            p: [start, c1, c2, target],
+            settings: ArcSettings::default(),
+            segments: 0,
+            index: 0,
+        }
+        .init()
+    }
+
+    /// G5.1, one control point
+    pub fn new_quadratic(start: Vec3<Scalar>, c: Vec3<Scalar>, target: Vec3<Scalar>) -> Self {
+        let two_thirds = 2. / 3.;
+        Self::new_cubic(
+            start,
+            start + (c - start) * two_thirds,
+            target + (c - target) * two_thirds,
+            target,
+        )
+    }
+
+    /// reset the interpolation with new settings
+    pub fn with_settings(mut self, settings: ArcSettings) -> Self {
+        self.settings = settings;
+        self.init()
+    }
+
+    fn init(mut self) -> Self {
+        let [p0, p1, p2, p3] = self.p;
+        let d1 = (p0 - &p1 * 2. + p2).distance();
+        let d2 = (p1 - &p2 * 2. + p3).distance();
+        let tolerance = self.settings.tolerance.max(Scalar::EPSILON);
+        let mut segments = num_traits::Float::sqrt(0.75 * d1.max(d2) / tolerance);
+        if self.settings.max_segment_length > 0. {
+            // the control polygon is never shorter than the curve
+            let polygon = (p1 - p0).distance() + (p2 - p1).distance() + (p3 - p2).distance();
+            segments = segments.max(polygon / self.settings.max_segment_length);
+        }
+        self.segments = (num_traits::Float::ceil(segments) as u32).max(1);
+        self.index = 0;
+        self
+    }
+
+    pub fn settings(&self) -> &ArcSettings {
+        &self.settings
+    }
+
+    /// start, two control points and target
+    pub fn control_points(&self) -> [Vec3<Scalar>; 4] {
+        self.p
+    }
+
+    pub fn start(&self) -> Vec3<Scalar> {
+        self.p[0]
+    }
+
+    pub fn target(&self) -> Vec3<Scalar> {
+        self.p[3]
+    }
+
+    /// how many segments the curve is cut into
+    pub fn segments(&self) -> u32 {
+        self.segments
+    }
+
+    /// length of the flattened path in mm
+    pub fn length(&self) -> Scalar {
+        let mut last = self.p[0];
+        (1..=self.segments)
+            .map(|i| {
+                let p = self.point_at(i as Scalar / self.segments as Scalar);
+                let d = (p - last).distance();
+                last = p;
+                d
+            })
+            .sum()
+    }
+
+    /// point at the curve parameter, 0 is the start and 1 is the target
+    pub fn point_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let [p0, p1, p2, p3] = self.p;
+        let (t, u) = (fraction, 1. - fraction);
+        p0 * (u * u * u) + p1 * (3. * u * u * t) + p2 * (3. * u * t * t) + p3 * (t * t * t)
+    }
+
+    /// first derivative by the curve parameter
+    fn derivative(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let [p0, p1, p2, p3] = self.p;
+        let (t, u) = (fraction, 1. - fraction);
+        (p1 - p0) * (3. * u * u) + (p2 - p1) * (6. * u * t) + (p3 - p2) * (3. * t * t)
+    }
+
+    /// second derivative by the curve parameter
+    fn second_derivative(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let [p0, p1, p2, p3] = self.p;
+        (p0 - p1 * 2. + p2) * (6. * (1. - fraction)) + (p1 - p2 * 2. + p3) * (6. * fraction)
+    }
+
+    /// a control point on the start or target stops the curve there, the shape is taken a bit inside
+    fn regular_fraction(&self, fraction: Scalar) -> Scalar {
+        if self.derivative(fraction).distance_sqr() > Scalar::EPSILON {
+            fraction
+        } else if fraction < 0.5 {
+            fraction + 1e-3
+        } else {
+            fraction - 1e-3
+        }
+    }
+
+    /// unit vector of the moving direction at the curve parameter
+    pub fn tangent_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let d = self.derivative(self.regular_fraction(fraction));
+        if d.distance_sqr() == 0. {
+            // all points are the same
+            return Vec3::zero();
+        }
+        d.as_unit_vec()
+    }
+
+    /// curvature at the curve parameter in 1/mm
+    pub fn curvature_at(&self, fraction: Scalar) -> Scalar {
+        let t = self.regular_fraction(fraction);
+        curvature(self.derivative(t), self.second_derivative(t))
+    }
+
+    /// unit vector of the moving direction at the start
+    pub fn start_tangent(&self) -> Vec3<Scalar> {
+        self.tangent_at(0.)
+    }
+
+    /// unit vector of the moving direction at the target
+    pub fn end_tangent(&self) -> Vec3<Scalar> {
+        self.tangent_at(1.)
+    }
+
+    /// next segment end with the curve shape there
+    pub fn next_point(&mut self) -> Option<SplinePoint> {
+        if self.index >= self.segments {
+            return None;
+        }
+        self.index += 1;
+        let (position, fraction) = if self.index == self.segments {
+            // Ensure last segment arrives at target location.
+            (self.p[3], 1.)
+        } else {
+            let fraction = self.index as Scalar / self.segments as Scalar;
+            (self.point_at(fraction), fraction)
+        };
+        Some(SplinePoint {
+            position,
+            tangent: self.tangent_at(fraction),
+            curvature: self.curvature_at(fraction),
+            fraction,
+        })
+    }
+}
+
+impl core::iter::Iterator for Bezier {
+    type Item = Vec3<Scalar>;
+
+    /// output internal interpolation, not include start point:
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_point().map(|p| p.position)
+    }
+}
+
+/// reasons that a NURBS curve can not be built
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NurbsError {
+    /// not more control points than the degree, or the degree is 0
+    TooFewPoints,
+    /// the count of weights differs from the count of control points, or a weight is not positive
+    InvalidWeights,
+    /// the count of knots is not points + degree + 1, the knots decrease, or the curve domain is empty
+    InvalidKnots,
+    /// the degree is above 7
+    DegreeTooHigh,
+}
+
+/// NURBS curve, control points with weights and a knot vector.
+///
+/// the curve is cut in adaptive parameter steps: a step is as long as the chord tolerance allows at the
+/// local curvature, at most `max_segment_length` of [`ArcSettings`], and ends at most at the next knot.
+/// the parameter step comes from a second order taylor expansion, then it is corrected until the segment
+/// length is within the feed fluctuation of the planned length, so the feed rate stays smooth where the
+/// parameter speed changes.
+///
+/// in its core::iter::Iterator implement, all output points include end point, not include start point.
+///
+/// example
+/// ```
+/// use ngc_geometry::{Nurbs, Vec3};
+/// // quarter circle of radius 10
+/// let w = ngc_geometry::consts::FRAC_1_SQRT_2;
+/// let curve = Nurbs::new(
+///     vec![Vec3::new(10., 0., 0.), Vec3::new(10., 10., 0.), Vec3::new(0., 10., 0.)],
+///     vec![1., w, 1.],
+///     vec![0., 0., 0., 1., 1., 1.],
+/// )
+/// .unwrap();
+/// assert!((curve.curvature_at(0.5) - 0.1).abs() < 1e-4);
+/// let points: Vec<_> = curve.collect();
+/// assert_eq!(points.last(), Some(&Vec3::new(0., 10., 0.)));
+/// ```
+const BASIS_LEN: usize = config::NURBS_MAX_DEGREE + 1;
+
+/// basis function values of a span
+type Basis = [Scalar; BASIS_LEN];
+
+#[derive(Clone, Debug)]
+pub struct Nurbs {
+    points: Vec<Vec3<Scalar>>,
+    weights: Vec<Scalar>,
+    knots: Vec<Scalar>,
+    degree: usize,
+    settings: ArcSettings,
+    feed_fluctuation: Scalar,
+    /// curve parameter of the last output point
+    u: Scalar,
+    completed: bool,
+}
+
+impl Nurbs {
+    /// the degree is knots - points - 1
+    pub fn new(
+        points: Vec<Vec3<Scalar>>,
+        weights: Vec<Scalar>,
+        knots: Vec<Scalar>,
+    ) -> Result<Self, NurbsError> {
+        if knots.len() < points.len() + 2 {
+            return Err(if points.len() < 2 {
+                NurbsError::TooFewPoints
+            } else {
+                NurbsError::InvalidKnots
+            });
+        }
+        let degree = knots.len() - points.len() - 1;
+        if points.len() <= degree {
+            return Err(NurbsError::TooFewPoints);
+        }
+        if degree > config::NURBS_MAX_DEGREE {
+            return Err(NurbsError::DegreeTooHigh);
+        }
+        if weights.len() != points.len() || weights.iter().any(|w| *w <= 0.) {
+            return Err(NurbsError::InvalidWeights);
+        }
+        if knots.windows(2).any(|k| k[1] < k[0]) || knots[points.len()] <= knots[degree] {
+            return Err(NurbsError::InvalidKnots);
+        }
+        let u = knots[degree];
+        Ok(Self {
+            points,
+            weights,
+            knots,
+            degree,
+            settings: ArcSettings::default(),
+            feed_fluctuation: config::NURBS_DEFAULT_FEED_FLUCTUATION,
+            u,
+            completed: false,
+        })
+    }
+
+    /// uniform clamped knots, the curve starts at the first control point and ends at the last one.
+    /// it is the knot vector of G5.2, whose L word is the order, degree + 1
+    pub fn new_clamped(
+        degree: usize,
+        points: Vec<Vec3<Scalar>>,
+        weights: Vec<Scalar>,
+    ) -> Result<Self, NurbsError> {
+        if degree == 0 || points.len() <= degree {
+            return Err(NurbsError::TooFewPoints);
+        }
+        if degree > config::NURBS_MAX_DEGREE {
+            return Err(NurbsError::DegreeTooHigh);
+        }
+        let spans = (points.len() - degree) as Scalar;
+        let knots = (0..points.len() + degree + 1)
+            .map(|i| (i.saturating_sub(degree) as Scalar / spans).min(1.))
+            .collect();
+        Self::new(points, weights, knots)
+    }
+
+    /// reset the interpolation with new settings
+    pub fn with_settings(mut self, settings: ArcSettings) -> Self {
+        self.settings = settings;
+        self.restart()
+    }
+
+    /// reset the interpolation with a new feed fluctuation, e.g. 0.01 is 1%
+    pub fn with_feed_fluctuation(mut self, feed_fluctuation: Scalar) -> Self {
+        self.feed_fluctuation = feed_fluctuation;
+        self.restart()
+    }
+
+    fn restart(mut self) -> Self {
+        self.u = self.knots[self.degree];
+        self.completed = false;
+        self
+    }
+
+    pub fn settings(&self) -> &ArcSettings {
+        &self.settings
+    }
+
+    pub fn feed_fluctuation(&self) -> Scalar {
+        self.feed_fluctuation
+    }
+
+    pub fn degree(&self) -> usize {
+        self.degree
+    }
+
+    pub fn control_points(&self) -> &[Vec3<Scalar>] {
+        &self.points
+    }
+
+    pub fn weights(&self) -> &[Scalar] {
+        &self.weights
+    }
+
+    pub fn knots(&self) -> &[Scalar] {
+        &self.knots
+    }
+
+    pub fn start(&self) -> Vec3<Scalar> {
+        self.point_at(0.)
+    }
+
+    pub fn target(&self) -> Vec3<Scalar> {
+        self.point_at(1.)
+    }
+
+    /// curve domain (first, last) of the knot parameter
+    fn domain(&self) -> (Scalar, Scalar) {
+        (self.knots[self.degree], self.knots[self.points.len()])
+    }
+
+    fn to_parameter(&self, fraction: Scalar) -> Scalar {
+        let (u0, u1) = self.domain();
+        u0 + (u1 - u0) * fraction.clamp(0., 1.)
+    }
+
+    /// point at the fraction of the curve domain, 0 is the start and 1 is the target
+    pub fn point_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        self.derivatives(self.to_parameter(fraction)).0
+    }
+
+    /// unit vector of the moving direction at the fraction of the curve domain
+    pub fn tangent_at(&self, fraction: Scalar) -> Vec3<Scalar> {
+        let d1 = self.derivatives(self.to_parameter(fraction)).1;
+        if d1.distance_sqr() == 0. {
+            return Vec3::zero();
+        }
+        d1.as_unit_vec()
+    }
+
+    /// curvature at the fraction of the curve domain in 1/mm
+    pub fn curvature_at(&self, fraction: Scalar) -> Scalar {
+        let (_, d1, d2) = self.derivatives(self.to_parameter(fraction));
+        curvature(d1, d2)
+    }
+
+    /// knot span of the parameter, the last span for the end of the domain
+    fn span(&self, u: Scalar) -> usize {
+        let n = self.points.len() - 1;
+        if u >= self.knots[n + 1] {
+            // the last non-empty span
+            let mut span = n;
+            while self.knots[span] == self.knots[span + 1] {
+                span -= 1;
+            }
+            return span;
+        }
+        let (mut low, mut high) = (self.degree, n + 1);
+        let mut mid = (low + high) / 2;
+        while u < self.knots[mid] || u >= self.knots[mid + 1] {
+            if u < self.knots[mid] {
+                high = mid;
+            } else {
+                low = mid;
+            }
+            mid = (low + high) / 2;
+        }
+        mid
+    }
+
+    /// basis functions and their first two derivatives on the span, "The NURBS Book" A2.3.
+    /// the first degree + 1 entries of each row are used
+    fn basis_derivatives(&self, span: usize, u: Scalar) -> [Basis; 3] {
+        let p = self.degree;
+        let knots = &self.knots;
+        let mut ndu = [[0. as Scalar; BASIS_LEN]; BASIS_LEN];
+        let mut left = [0. as Scalar; BASIS_LEN];
+        let mut right = [0. as Scalar; BASIS_LEN];
+        ndu[0][0] = 1.;
+        for j in 1..=p {
+            left[j] = u - knots[span + 1 - j];
+            right[j] = knots[span + j] - u;
+            let mut saved = 0.;
+            for r in 0..j {
+                // lower triangle is the knot differences
+                ndu[j][r] = right[r + 1] + left[j - r];
+                let temp = ndu[r][j - 1] / ndu[j][r];
+                ndu[r][j] = saved + right[r + 1] * temp;
+                saved = left[j - r] * temp;
+            }
+            ndu[j][j] = saved;
+        }
+
+        let mut ders = [[0. as Scalar; BASIS_LEN]; 3];
+        for j in 0..=p {
+            ders[0][j] = ndu[j][p];
+        }
+        let n = p.min(2);
+        let mut a = [[0. as Scalar; BASIS_LEN]; 2];
+        for r in 0..=p {
+            let (mut s1, mut s2) = (0, 1);
+            a[0][0] = 1.;
+            for k in 1..=n {
+                let mut d = 0.;
+                let pk = p - k;
+                if r >= k {
+                    let rk = r - k;
+                    a[s2][0] = a[s1][0] / ndu[pk + 1][rk];
+                    d = a[s2][0] * ndu[rk][pk];
+                }
+                let j1 = if r >= k { 1 } else { k - r };
+                let j2 = if r <= p + 1 - k { k - 1 } else { p - r };
+                for j in j1..=j2 {
+                    let rkj = r + j - k;
+                    a[s2][j] = (a[s1][j] - a[s1][j - 1]) / ndu[pk + 1][rkj];
+                    d += a[s2][j] * ndu[rkj][pk];
+                }
+                if r <= pk {
+                    a[s2][k] = -a[s1][k - 1] / ndu[pk + 1][r];
+                    d += a[s2][k] * ndu[r][pk];
+                }
+                ders[k][r] = d;
+                core::mem::swap(&mut s1, &mut s2);
+            }
+        }
+        let mut factor = p as Scalar;
+        for (k, der) in ders.iter_mut().enumerate().take(n + 1).skip(1) {
+            for d in der.iter_mut().take(p + 1) {
+                *d *= factor;
+            }
+            factor *= (p - k) as Scalar;
+        }
+        ders
+    }
+
+    /// point, first and second derivative by the knot parameter
+    fn derivatives(&self, u: Scalar) -> (Vec3<Scalar>, Vec3<Scalar>, Vec3<Scalar>) {
+        let span = self.span(u);
+        let ders = self.basis_derivatives(span, u);
+        // derivatives of the weighted points and of the weight
+        let mut a = [Vec3::zero(); 3];
+        let mut w = [0. as Scalar; 3];
+        for (k, der) in ders.iter().enumerate() {
+            for (j, n) in der.iter().enumerate().take(self.degree + 1) {
+                let i = span - self.degree + j;
+                let nw = n * self.weights[i];
+                a[k] += self.points[i] * nw;
+                w[k] += nw;
+            }
+        }
+        let c = a[0] / w[0];
+        let d1 = (a[1] - c * w[1]) / w[0];
+        let d2 = (a[2] - d1 * (2. * w[1]) - c * w[2]) / w[0];
+        (c, d1, d2)
+    }
+
+    /// the next parameter from u, see [`Nurbs`]
+    fn next_parameter(
+        &self,
+        u: Scalar,
+        c: Vec3<Scalar>,
+        d1: Vec3<Scalar>,
+        d2: Vec3<Scalar>,
+    ) -> Scalar {
+        let (u0, u1) = self.domain();
+        // the step never passes a knot, so no feature of the curve is skipped
+        let span_end = self.knots[self.span(u) + 1].min(u1);
+        let mut length = Scalar::MAX;
+        let kappa = curvature(d1, d2);
+        if kappa > 0. {
+            // sagitta of a chord on a circle of the same curvature
+            let tolerance = self.settings.tolerance.max(Scalar::EPSILON);
+            length = num_traits::Float::sqrt(8. * tolerance / kappa);
+        }
+        if self.settings.max_segment_length > 0. {
+            length = length.min(self.settings.max_segment_length);
+        }
+        let speed_sqr = d1.distance_sqr();
+        if length == Scalar::MAX || speed_sqr == 0. {
+            return span_end;
+        }
+        let speed = num_traits::Float::sqrt(speed_sqr);
+        // s(u + du) = s(u) + |C'| du + (C'.C'') / (2 |C'|) du^2, solved to the second order
+        let mut du = length / speed - d1.dot(d2) * length * length / (2. * speed_sqr * speed_sqr);
+        if du <= 0. {
+            du = length / speed;
+        }
+        let min_step = (u1 - u0) * 1e-6;
+        for _ in 0..config::NURBS_STEP_CORRECTIONS {
+            if u + du >= span_end {
+                return span_end;
+            }
+            let chord = (self.derivatives(u + du).0 - c).distance();
+            if num_traits::Float::abs(chord - length) <= self.feed_fluctuation * length
+                || chord == 0.
+            {
+                break;
+            }
+            du *= length / chord;
+        }
+        (u + du.max(min_step)).min(span_end)
+    }
+
+    /// next segment end with the curve shape there
+    pub fn next_point(&mut self) -> Option<SplinePoint> {
+        if self.completed {
+            return None;
+        }
+        let (u0, u1) = self.domain();
+        let (c, d1, d2) = self.derivatives(self.u);
+        self.u = self.next_parameter(self.u, c, d1, d2);
+        if self.u >= u1 {
+            self.u = u1;
+            self.completed = true;
+        }
+        let (position, d1, d2) = self.derivatives(self.u);
+        Some(SplinePoint {
+            position,
+            tangent: if d1.distance_sqr() == 0. {
+                Vec3::zero()
+            } else {
+                d1.as_unit_vec()
+            },
+            curvature: curvature(d1, d2),
+            fraction: (self.u - u0) / (u1 - u0),
+        })
+    }
+}
+
+impl core::iter::Iterator for Nurbs {
+    type Item = Vec3<Scalar>;
+
+    /// output internal interpolation, not include start point:
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_point().map(|p| p.position)
+    }
+}
+
+/// |C' x C''| / |C'|^3
+fn curvature(d1: Vec3<Scalar>, d2: Vec3<Scalar>) -> Scalar {
+    let len_sqr = d1.distance_sqr();
+    if len_sqr == 0. {
+        return 0.;
+    }
+    let cross = Vec3::new(
+        d1.1 * d2.2 - d1.2 * d2.1,
+        d1.2 * d2.0 - d1.0 * d2.2,
+        d1.0 * d2.1 - d1.1 * d2.0,
+    );
+    cross.distance() / (len_sqr * num_traits::Float::sqrt(len_sqr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bezier, Nurbs, NurbsError};
+    use crate::Scalar;
+    use crate::{ArcSettings, Vec3};
+
+    #[test]
+    fn test_cubic() {
+        let curve = Bezier::new_cubic(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 10., 0.),
+            Vec3::new(10., 10., 0.),
+            Vec3::new(10., 0., 0.),
+        );
+        assert_eq!(curve.point_at(0.5), Vec3::new(5., 7.5, 0.));
+        assert_eq!(curve.start_tangent(), Vec3::new(0., 1., 0.));
+        assert_eq!(curve.end_tangent(), Vec3::new(0., -1., 0.));
+        // B'(0.5) = (15, 0, 0), B''(0.5) = (-30, -60, 0)
+        assert!((curve.curvature_at(0.5) - 900. / 3375.).abs() < 1e-6);
+
+        // each segment is within the chord tolerance: check the middle of every chord
+        let segments = curve.segments();
+        let mut last = curve.start();
+        let mut n = 0;
+        for p in curve {
+            n += 1;
+            let mid = curve.point_at((n as Scalar - 0.5) / segments as Scalar);
+            let chord_mid = &(last + p) * 0.5;
+            assert!((mid - chord_mid).distance() < curve.settings().tolerance);
+            last = p;
+        }
+        assert_eq!(n, segments);
+        assert_eq!(last, Vec3::new(10., 0., 0.));
+
+        let coarse = curve.with_settings(ArcSettings {
+            tolerance: 0.1,
+            ..ArcSettings::default()
+        });
+        assert!(coarse.segments() < segments);
+        let capped = coarse.with_settings(ArcSettings {
+            tolerance: 0.1,
+            max_segment_length: 0.5,
+            ..ArcSettings::default()
+        });
+        assert!(capped.length() / capped.segments() as Scalar <= 0.5);
+    }
+
+    #[test]
+    fn test_quadratic() {
+        // a quadratic with control point on the start is a straight line
+        let line = Bezier::new_quadratic(
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 0., 0.),
+            Vec3::new(4., 0., 0.),
+        );
+        assert_eq!(line.start_tangent(), Vec3::new(1., 0., 0.));
+        assert_eq!(line.curvature_at(0.), 0.);
+        assert!((line.length() - 4.).abs() < 1e-4);
+
+        // parabola y = x^2 / 4 from x = -2 to 2, curvature 1/2 at the apex
+        let mut curve = Bezier::new_quadratic(
+            Vec3::new(-2., 1., 0.),
+            Vec3::new(0., -1., 0.),
+            Vec3::new(2., 1., 0.),
+        );
+        assert!(curve.point_at(0.5).distance() < 1e-6);
+        assert_eq!(curve.tangent_at(0.5), Vec3::new(1., 0., 0.));
+        assert!((curve.curvature_at(0.5) - 0.5).abs() < 1e-5);
+        let apex = curve.curvature_at(0.5);
+        let points: Vec<_> = core::iter::from_fn(|| curve.next_point()).collect();
+        assert_eq!(points.len() as u32, curve.segments());
+        assert!(points.iter().all(|p| p.curvature <= apex + 1e-6));
+        let end = points.last().unwrap();
+        assert_eq!((end.position, end.fraction), (Vec3::new(2., 1., 0.), 1.));
+        assert!((end.tangent.distance() - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_nurbs() {
+        // a clamped cubic with unit weights is the bezier curve
+        let points = vec![
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 10., 1.),
+            Vec3::new(10., 10., 2.),
+            Vec3::new(10., 0., 3.),
+        ];
+        let bezier = Bezier::new_cubic(points[0], points[1], points[2], points[3]);
+        let nurbs = Nurbs::new_clamped(3, points.clone(), vec![1.; 4]).unwrap();
+        assert_eq!(nurbs.knots(), [0., 0., 0., 0., 1., 1., 1., 1.]);
+        for t in [0., 0.3, 0.5, 1.] {
+            assert!((nurbs.point_at(t) - bezier.point_at(t)).distance() < 1e-5);
+            assert!((nurbs.tangent_at(t) - bezier.tangent_at(t)).distance() < 1e-5);
+            assert!((nurbs.curvature_at(t) - bezier.curvature_at(t)).abs() < 1e-5);
+        }
+
+        // circle of radius 10 from two quarters, the weight of the corners is cos(45)
+        let w = crate::consts::FRAC_1_SQRT_2;
+        let circle = Nurbs::new(
+            vec![
+                Vec3::new(10., 0., 0.),
+                Vec3::new(10., 10., 0.),
+                Vec3::new(0., 10., 0.),
+                Vec3::new(-10., 10., 0.),
+                Vec3::new(-10., 0., 0.),
+            ],
+            vec![1., w, 1., w, 1.],
+            vec![0., 0., 0., 1., 1., 2., 2., 2.],
+        )
+        .unwrap()
+        .with_settings(ArcSettings {
+            tolerance: 0.01,
+            ..ArcSettings::default()
+        });
+        // planned segment length is sqrt(8 * 0.01 * 10)
+        let planned = num_traits::Float::sqrt(0.8);
+        let mut curve = circle.clone();
+        let points: Vec<_> = core::iter::from_fn(|| curve.next_point()).collect();
+        assert_eq!(points.last().unwrap().position, Vec3::new(-10., 0., 0.));
+        // the step ends at the knot of the two quarters
+        assert!(points.iter().any(|p| p.fraction == 0.5));
+        let mut last = circle.start();
+        for p in points.iter() {
+            assert!((p.position.distance() - 10.).abs() < 1e-3);
+            assert!((p.curvature - 0.1).abs() < 1e-3);
+            let chord = (p.position - last).distance();
+            // a step may be cut short by a knot, otherwise it is the planned length
+            assert!(chord <= planned * 1.01);
+            if p.fraction != 0.5 && p.fraction != 1. {
+                assert!((chord - planned).abs() <= planned * 0.01);
+            }
+            // chord error
+            assert!(10. - (&(p.position + last) * 0.5).distance() <= 0.0101);
+            last = p.position;
+        }
+
+        assert_eq!(
+            Nurbs::new_clamped(2, vec![Vec3::zero(); 2], vec![1.; 2]).unwrap_err(),
+            NurbsError::TooFewPoints
+        );
+        assert_eq!(
+            Nurbs::new_clamped(1, vec![Vec3::zero(); 2], vec![1., 0.]).unwrap_err(),
+            NurbsError::InvalidWeights
+        );
+        assert_eq!(
+            Nurbs::new(vec![Vec3::zero(); 2], vec![1.; 2], vec![0., 1., 0., 1.]).unwrap_err(),
+            NurbsError::InvalidKnots
+        );
+        let points: Vec<_> = (0..9).map(|i| Vec3::new(i as Scalar, 0., 0.)).collect();
+        assert_eq!(
+            Nurbs::new_clamped(8, points.clone(), vec![1.; 9]).unwrap_err(),
+            NurbsError::DegreeTooHigh
+        );
+        let highest = Nurbs::new_clamped(7, points, vec![1.; 9]).unwrap();
+        assert!((highest.point_at(0.5) - Vec3::new(4., 0., 0.)).distance() < 1e-4);
+        assert_eq!(highest.target(), Vec3::new(8., 0., 0.));
+    }
+}
@@ -10,7 +10,43 @@ pub trait Sqrt {
 //     fn ceil(self) -> Self;
 // }
 
-/// defines some custom traits and provides implementations for `f32`,
+/// Scalar of the geometry, `f32` or a fixed point type like [`crate::Q16_16`] for parts without a float unit.
+///
+/// it is the arithmetic bound of [`crate::Vec3`] with the square root, trig and rounding. only the rotations
+/// of [`crate::Mat3`] and the points of an arc, [`crate::ArcSegments`], are generic over it. the arc setup
+/// in the `Arc::new_*` constructors, the planner, [`crate::CNCCfgs`] and the profiles stay in
+/// [`crate::Scalar`]: they square velocities in mm/min, which are far out of the range of Q16.16.
+pub trait Real:
+    Copy
+    + core::cmp::PartialOrd
+    + num_traits::Zero
+    + num_traits::One
+    + core::ops::Add<Output = Self>
+    + core::ops::Sub<Output = Self>
+    + core::ops::Mul<Output = Self>
+    + core::ops::Neg<Output = Self>
+    + core::ops::Div<Output = Self>
+    + num_traits::Inv<Output = Self>
+    + Sqrt
+{
+    /// nearest value, it saturates out of the range
+    fn from_f32(v: f32) -> Self;
+    /// nearest value, it saturates out of the range
+    fn from_f64(v: f64) -> Self;
+    fn to_f32(self) -> f32;
+    fn pi() -> Self;
+    /// (sin, cos) of the radians
+    fn sin_cos(self) -> (Self, Self);
+    /// radians of the point (other, self) from the positive axis0, in [-pi, pi], as `f32::atan2`
+    fn atan2(self, other: Self) -> Self;
+    fn abs(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    /// half away from zero
+    fn round(self) -> Self;
+}
+
+/// defines some custom traits and provides implementations for `f32` and `f64`,
 mod impl_using_libm {
     impl super::Sqrt for f32 {
         fn sqrt(self) -> Self {
@@ -18,4 +54,96 @@ mod impl_using_libm {
             // libm::sqrtf(self)
         }
     }
+
+    impl super::Real for f32 {
+        #[inline]
+        fn from_f32(v: f32) -> Self {
+            v
+        }
+        #[inline]
+        fn from_f64(v: f64) -> Self {
+            v as f32
+        }
+        #[inline]
+        fn to_f32(self) -> f32 {
+            self
+        }
+        #[inline]
+        fn pi() -> Self {
+            core::f32::consts::PI
+        }
+        #[inline]
+        fn sin_cos(self) -> (Self, Self) {
+            num_traits::Float::sin_cos(self)
+        }
+        #[inline]
+        fn atan2(self, other: Self) -> Self {
+            num_traits::Float::atan2(self, other)
+        }
+        #[inline]
+        fn abs(self) -> Self {
+            num_traits::Float::abs(self)
+        }
+        #[inline]
+        fn floor(self) -> Self {
+            num_traits::Float::floor(self)
+        }
+        #[inline]
+        fn ceil(self) -> Self {
+            num_traits::Float::ceil(self)
+        }
+        #[inline]
+        fn round(self) -> Self {
+            num_traits::Float::round(self)
+        }
+    }
+
+    impl super::Sqrt for f64 {
+        fn sqrt(self) -> Self {
+            num_traits::Float::sqrt(self)
+        }
+    }
+
+    impl super::Real for f64 {
+        #[inline]
+        fn from_f32(v: f32) -> Self {
+            v as f64
+        }
+        #[inline]
+        fn from_f64(v: f64) -> Self {
+            v
+        }
+        #[inline]
+        fn to_f32(self) -> f32 {
+            self as f32
+        }
+        #[inline]
+        fn pi() -> Self {
+            core::f64::consts::PI
+        }
+        #[inline]
+        fn sin_cos(self) -> (Self, Self) {
+            num_traits::Float::sin_cos(self)
+        }
+        #[inline]
+        fn atan2(self, other: Self) -> Self {
+            num_traits::Float::atan2(self, other)
+        }
+        #[inline]
+        fn abs(self) -> Self {
+            num_traits::Float::abs(self)
+        }
+        #[inline]
+        fn floor(self) -> Self {
+            num_traits::Float::floor(self)
+        }
+        #[inline]
+        fn ceil(self) -> Self {
+            num_traits::Float::ceil(self)
+        }
+        #[inline]
+        fn round(self) -> Self {
+            num_traits::Float::round(self)
+        }
+    }
 }
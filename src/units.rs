@@ -0,0 +1,333 @@
+//! unit newtypes of the planner, so a length in inch or a rate in mm/s is converted where it is made.
+//!
+//! the planner works in mm and minutes: [`Length`] is mm, [`Velocity`] is mm/min, [`Acceleration`] is
+//! mm/min^2 and [`Jerk`] is mm/min^3. [`Angle`] is a rotary axis in degrees. [`Steps`] is a motor position, it
+//! is a length only with steps per mm.
+use crate::{Scalar, Vec3};
+#[allow(unused_imports)]
+use num_traits::Float;
+
+/// millimeters per inch
+pub const MM_PER_INCH: Scalar = 25.4;
+
+/// ops of a unit with the same unit and with a factor, the ratio of two values is a factor
+macro_rules! unit_ops {
+    ($unit:ident) => {
+        impl core::ops::Add for $unit {
+            type Output = Self;
+            #[inline]
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl core::ops::Sub for $unit {
+            type Output = Self;
+            #[inline]
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl core::ops::Neg for $unit {
+            type Output = Self;
+            #[inline]
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl core::ops::Mul<Scalar> for $unit {
+            type Output = Self;
+            #[inline]
+            fn mul(self, rhs: Scalar) -> Self {
+                Self(self.0 * rhs)
+            }
+        }
+
+        impl core::ops::Div<Scalar> for $unit {
+            type Output = Self;
+            #[inline]
+            fn div(self, rhs: Scalar) -> Self {
+                Self(self.0 / rhs)
+            }
+        }
+
+        impl core::ops::Div for $unit {
+            type Output = Scalar;
+            #[inline]
+            fn div(self, rhs: Self) -> Scalar {
+                self.0 / rhs.0
+            }
+        }
+
+        impl $unit {
+            #[inline]
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+
+            #[inline]
+            pub fn min(self, other: Self) -> Self {
+                Self(self.0.min(other.0))
+            }
+
+            #[inline]
+            pub fn max(self, other: Self) -> Self {
+                Self(self.0.max(other.0))
+            }
+        }
+    };
+}
+
+/// length in mm
+///
+/// example
+/// ```
+/// use ngc_geometry::Length;
+/// assert_eq!(Length::from_inch(2.).mm(), 50.8);
+/// assert!(Length::from_mm(25.4) + Length::from_inch(1.) > Length::from_mm(50.));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Length(Scalar);
+unit_ops!(Length);
+
+impl Length {
+    #[inline]
+    pub const fn from_mm(mm: Scalar) -> Self {
+        Self(mm)
+    }
+
+    #[inline]
+    pub fn from_inch(inch: Scalar) -> Self {
+        Self(inch * MM_PER_INCH)
+    }
+
+    #[inline]
+    pub const fn mm(self) -> Scalar {
+        self.0
+    }
+
+    #[inline]
+    pub fn inch(self) -> Scalar {
+        self.0 / MM_PER_INCH
+    }
+
+    /// nearest motor position
+    #[inline]
+    pub fn to_steps(self, steps_per_mm: Scalar) -> Steps {
+        Steps((self.0 * steps_per_mm).round() as i32)
+    }
+}
+
+/// a position of the linear axes
+impl Vec3<Length> {
+    #[inline]
+    pub fn from_mm(p: Vec3<Scalar>) -> Self {
+        Vec3(Length(p.0), Length(p.1), Length(p.2))
+    }
+
+    #[inline]
+    pub fn mm(&self) -> Vec3<Scalar> {
+        Vec3(self.0 .0, self.1 .0, self.2 .0)
+    }
+}
+
+/// velocity in mm/min, as the F word and the rates of [`crate::CNCCfgs`]
+///
+/// example
+/// ```
+/// use ngc_geometry::Velocity;
+/// assert_eq!(Velocity::from_mm_per_sec(10.), Velocity::from_mm_per_min(600.));
+/// assert_eq!(Velocity::from_inch_per_min(10.).mm_per_min(), 254.);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Velocity(Scalar);
+unit_ops!(Velocity);
+
+impl Velocity {
+    #[inline]
+    pub const fn from_mm_per_min(v: Scalar) -> Self {
+        Self(v)
+    }
+
+    #[inline]
+    pub fn from_mm_per_sec(v: Scalar) -> Self {
+        Self(v * 60.)
+    }
+
+    #[inline]
+    pub fn from_inch_per_min(v: Scalar) -> Self {
+        Self(v * MM_PER_INCH)
+    }
+
+    #[inline]
+    pub const fn mm_per_min(self) -> Scalar {
+        self.0
+    }
+
+    #[inline]
+    pub fn mm_per_sec(self) -> Scalar {
+        self.0 / 60.
+    }
+
+    #[inline]
+    pub fn inch_per_min(self) -> Scalar {
+        self.0 / MM_PER_INCH
+    }
+}
+
+/// acceleration in mm/min^2
+///
+/// example
+/// ```
+/// use ngc_geometry::Acceleration;
+/// assert_eq!(Acceleration::from_mm_per_sec2(10.).mm_per_min2(), 36000.);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Acceleration(Scalar);
+unit_ops!(Acceleration);
+
+impl Acceleration {
+    #[inline]
+    pub const fn from_mm_per_min2(v: Scalar) -> Self {
+        Self(v)
+    }
+
+    #[inline]
+    pub fn from_mm_per_sec2(v: Scalar) -> Self {
+        Self(v * 3600.)
+    }
+
+    #[inline]
+    pub const fn mm_per_min2(self) -> Scalar {
+        self.0
+    }
+
+    #[inline]
+    pub fn mm_per_sec2(self) -> Scalar {
+        self.0 / 3600.
+    }
+}
+
+/// jerk in mm/min^3, the limit of the S-curve profile
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Jerk(Scalar);
+unit_ops!(Jerk);
+
+impl Jerk {
+    #[inline]
+    pub const fn from_mm_per_min3(v: Scalar) -> Self {
+        Self(v)
+    }
+
+    #[inline]
+    pub fn from_mm_per_sec3(v: Scalar) -> Self {
+        Self(v * 216000.)
+    }
+
+    #[inline]
+    pub const fn mm_per_min3(self) -> Scalar {
+        self.0
+    }
+
+    #[inline]
+    pub fn mm_per_sec3(self) -> Scalar {
+        self.0 / 216000.
+    }
+}
+
+/// angle of a rotary axis in degrees, as the A and B words
+///
+/// example
+/// ```
+/// use ngc_geometry::Angle;
+/// assert!((Angle::from_deg(180.).rad() - ngc_geometry::consts::PI).abs() < 1e-6);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Angle(Scalar);
+unit_ops!(Angle);
+
+impl Angle {
+    #[inline]
+    pub const fn from_deg(deg: Scalar) -> Self {
+        Self(deg)
+    }
+
+    #[inline]
+    pub fn from_rad(rad: Scalar) -> Self {
+        Self(rad.to_degrees())
+    }
+
+    #[inline]
+    pub const fn deg(self) -> Scalar {
+        self.0
+    }
+
+    #[inline]
+    pub fn rad(self) -> Scalar {
+        self.0.to_radians()
+    }
+}
+
+/// motor position or distance in steps
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Steps(pub i32);
+
+impl Steps {
+    #[inline]
+    pub fn to_length(self, steps_per_mm: Scalar) -> Length {
+        Length(self.0 as Scalar / steps_per_mm)
+    }
+}
+
+impl core::ops::Add for Steps {
+    type Output = Self;
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl core::ops::Sub for Steps {
+    type Output = Self;
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Acceleration, Angle, Jerk, Length, Steps, Velocity};
+    use crate::Vec3;
+
+    #[test]
+    fn test_unit_conversions() {
+        let l = Length::from_inch(1.5);
+        assert!((l.mm() - 38.1).abs() < 1e-5);
+        assert!((l.inch() - 1.5).abs() < 1e-6);
+        assert_eq!(Length::from_mm(2.5).to_steps(80.), Steps(200));
+        assert_eq!(Steps(-40).to_length(80.), Length::from_mm(-0.5));
+        assert_eq!(
+            (Length::from_mm(3.) - Length::from_mm(1.)) * 2.,
+            Length::from_mm(4.)
+        );
+        assert_eq!(Length::from_mm(6.) / Length::from_mm(2.), 3.);
+
+        let v = Velocity::from_mm_per_sec(25.);
+        assert_eq!(v.mm_per_min(), 1500.);
+        assert!(
+            (Velocity::from_inch_per_min(v.inch_per_min()) - v).abs()
+                < Velocity::from_mm_per_min(1e-3)
+        );
+        assert_eq!(Acceleration::from_mm_per_sec2(2.).mm_per_sec2(), 2.);
+        assert_eq!(Jerk::from_mm_per_sec3(1.).mm_per_min3(), 216000.);
+        assert_eq!(v.max(-v), v);
+
+        assert!((Angle::from_rad(Angle::from_deg(90.).rad()).deg() - 90.).abs() < 1e-4);
+        let p = Vec3::new(1., -2., 3.5);
+        assert_eq!(Vec3::<Length>::from_mm(p).mm(), p);
+        assert_eq!(Vec3::<Length>::from_mm(p).1, Length::from_mm(-2.));
+    }
+}
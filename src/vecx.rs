@@ -29,13 +29,6 @@ where
             2: axis2,
         }
     }
-    pub fn zero() -> Self {
-        Self {
-            0: T::zero(),
-            1: T::zero(),
-            2: T::zero(),
-        }
-    }
     #[inline]
     pub fn new_from_plane(plane: Vec2<T>, linear: T) -> Self {
         Self {
@@ -46,91 +39,14 @@ where
     }
 
     #[inline]
-    pub fn dot(&self, other: Self) -> T {
-        (self.0 * other.0) + (self.1 * other.1) + (self.2 * other.2)
-    }
-    // #[inline]
-    // pub fn cross(&self, other: Self) -> Self {
-    //     Self {
-    //         0: (self.y * other.z) + (-self.z * other.y),
-    //         1: (self.z * other.x) + (-self.x * other.z),
-    //         2: (self.x * other.y) + (-self.y * other.x),
-    //     }
-    // }
-
-    pub fn abs(&self) -> Self {
-        Self {
-            0: if self.0 < T::zero() {
-                self.0.neg()
-            } else {
-                self.0
-            },
-            1: if self.1 < T::zero() {
-                self.1.neg()
-            } else {
-                self.1
-            },
-            2: if self.2 < T::zero() {
-                self.2.neg()
-            } else {
-                self.2
-            },
-        }
-    }
-    pub fn max_element(&self) -> T {
-        let t = if self.0 > self.1 { self.0 } else { self.1 };
-
-        let tt = if t > self.2 { t } else { self.2 };
-
-        tt
-    }
-    pub fn max_element_index(&self) -> Self {
-        let mut unit = Self::new(T::one(), T::zero(), T::zero());
-        let t = if self.0 > self.1 {
-            self.0
-        } else {
-            unit = Self::new(T::zero(), T::one(), T::zero());
-            self.1
-        };
-
-        if t > self.2 {
-        } else {
-            unit = Self::new(T::zero(), T::zero(), T::one());
-        };
-
-        unit
-    }
-
-    /// x^2+y^2+z^2
-    #[inline]
-    pub fn distance_sqr(&self) -> T
-    where
-        T: core::ops::Mul<Output = T>,
-    {
-        self.0 * self.0 + self.1 * self.1 + self.2 * self.2
-    }
-
-    /// \sqrt{x^2+y^2+z^2}
-    #[inline]
-    pub fn distance(&self) -> T
-    where
-        T: core::ops::Mul<Output = T> + Sqrt,
-    {
-        self.distance_sqr().sqrt()
+    pub fn cross(&self, other: Self) -> Self {
+        Self(
+            (self.1 * other.2) - (self.2 * other.1),
+            (self.2 * other.0) - (self.0 * other.2),
+            (self.0 * other.1) - (self.1 * other.0),
+        )
     }
 
-    /// unit vec
-    pub fn as_unit_vec(&self) -> Self
-    where
-        T: core::ops::Mul<Output = T> + num_traits::Inv<Output = T> + Sqrt,
-    {
-        let magnitude = self.distance().inv();
-        Self {
-            0: self.0 * magnitude,
-            1: self.1 * magnitude,
-            2: self.2 * magnitude,
-        }
-    }
     #[inline]
     pub fn plane(&self) -> Vec2<T> {
         Vec2 {
@@ -145,81 +61,6 @@ where
     }
 }
 
-impl<T> core::ops::Sub for Vec3<T>
-where
-    T: Copy + core::ops::Sub<Output = T>,
-{
-    type Output = Self;
-    #[inline]
-    fn sub(self, rhs: Self::Output) -> Self {
-        Self {
-            0: self.0 - rhs.0,
-            1: self.1 - rhs.1,
-            2: self.2 - rhs.2,
-        }
-    }
-}
-
-impl<T> core::ops::Add for Vec3<T>
-where
-    T: Copy + core::ops::Add<Output = T>,
-{
-    type Output = Self;
-    #[inline]
-    fn add(self, rhs: Self::Output) -> Self {
-        Self {
-            0: self.0 + rhs.0,
-            1: self.1 + rhs.1,
-            2: self.2 + rhs.2,
-        }
-    }
-}
-
-impl<T> core::ops::Mul<T> for &Vec3<T>
-where
-    T: Copy + core::ops::Mul<Output = T>,
-{
-    type Output = Vec3<T>;
-    #[inline]
-    fn mul(self, rhs: T) -> Self::Output {
-        Self::Output {
-            0: self.0 * rhs,
-            1: self.1 * rhs,
-            2: self.2 * rhs,
-        }
-    }
-}
-
-impl<T> core::ops::Div<T> for &Vec3<T>
-where
-    T: Copy + core::ops::Div<Output = T>,
-{
-    type Output = Vec3<T>;
-    #[inline]
-    fn div(self, rhs: T) -> Self::Output {
-        Self::Output {
-            0: self.0 / rhs,
-            1: self.1 / rhs,
-            2: self.2 / rhs,
-        }
-    }
-}
-
-impl<T> core::ops::Neg for Vec3<T>
-where
-    T: core::ops::Neg<Output = T>,
-{
-    type Output = Self;
-    #[inline]
-    fn neg(self) -> Self::Output {
-        Self {
-            0: -self.0,
-            1: -self.1,
-            2: -self.2,
-        }
-    }
-}
-
 pub type Point = Vec2<i32>;
 pub type Point3 = Vec3<i32>;
 
@@ -265,21 +106,130 @@ where
         (self.0 * other.1) - (self.1 * other.0)
     }
 
+    /// rotate 90 degrees counterclockwise, so `v.wedge(v.perp())` is `v.dot(v)`
+    #[inline]
+    pub fn perp(&self) -> Self {
+        Self(-self.1, self.0)
+    }
+}
+
+/// 5 axes, e.g. XYZ and two rotary axes
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default)]
+pub struct Vec5<T>(pub T, pub T, pub T, pub T, pub T);
+impl<T> Vec5<T>
+where
+    T: Copy,
+{
+    /// Create a new point from the coordinates of 5 axes.
+    pub fn new(axis0: T, axis1: T, axis2: T, axis3: T, axis4: T) -> Self {
+        Self {
+            0: axis0,
+            1: axis1,
+            2: axis2,
+            3: axis3,
+            4: axis4,
+        }
+    }
+
+    /// the first 3 axes
+    #[inline]
+    pub fn xyz(&self) -> Vec3<T> {
+        Vec3(self.0, self.1, self.2)
+    }
+}
+
+/// Helper struct defining a point of N axes, e.g. the XYZAB position of a 5 axis machine.
+///
+/// the math of [`Vec2`], [`Vec3`] and [`Vec5`] is the math of `VecN` with 2, 3 and 5 axes, they only add
+/// the named fields and the products of their own dimension, e.g. [`Vec3::cross`].
+///
+/// example
+/// ```
+/// use ngc_geometry::{Vec3, Vec5, VecN};
+/// let a = VecN([1_f32, 2., 2., 0., 90.]);
+/// let b: VecN<f32, 5> = VecN([1., 0., 0., 0., 0.]);
+/// assert_eq!((a - b).max_element(), 90.);
+/// assert_eq!(VecN::from(Vec3::new(1_f32, 2., 2.)).distance(), 3.);
+/// assert_eq!(Vec5::from(a).4, 90.);
+/// ```
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub struct VecN<T, const N: usize>(pub [T; N]);
+
+impl<T, const N: usize> VecN<T, N>
+where
+    T: Copy
+        + core::cmp::PartialOrd
+        + num_traits::Zero
+        + num_traits::One
+        + core::ops::Add<Output = T>
+        + core::ops::Sub<Output = T>
+        + core::ops::Mul<Output = T>
+        + core::ops::Neg<Output = T>
+        + core::ops::Div<Output = T>,
+{
+    pub fn zero() -> Self {
+        Self([T::zero(); N])
+    }
+
+    /// number of axes
+    #[inline]
+    pub const fn len(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        N == 0
+    }
+
     #[inline]
     pub fn dot(&self, other: Self) -> T {
-        (self.0 * other.0) + (self.1 * other.1)
+        self.0
+            .iter()
+            .zip(other.0.iter())
+            .fold(T::zero(), |sum, (a, b)| sum + *a * *b)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self(self.0.map(|a| if a < T::zero() { a.neg() } else { a }))
+    }
+
+    /// the largest element, zero if N is 0
+    pub fn max_element(&self) -> T {
+        let mut max = match self.0.first() {
+            Some(a) => *a,
+            None => return T::zero(),
+        };
+        for a in self.0.iter() {
+            if *a > max {
+                max = *a;
+            }
+        }
+        max
+    }
+
+    /// unit vector along the axis of the largest element, the last one of equal elements
+    pub fn max_element_index(&self) -> Self {
+        let mut unit = Self::zero();
+        let mut index = 0;
+        for (i, a) in self.0.iter().enumerate() {
+            if *a >= self.0[index] {
+                index = i;
+            }
+        }
+        if N > 0 {
+            unit.0[index] = T::one();
+        }
+        unit
     }
 
-    /// x^2+y^2+z^2
+    /// sum of the squares
     #[inline]
-    pub fn distance_sqr(&self) -> T
-    where
-        T: core::ops::Mul<Output = T>,
-    {
-        self.0 * self.0 + self.1 * self.1
+    pub fn distance_sqr(&self) -> T {
+        self.dot(*self)
     }
 
-    /// \sqrt{x^2+y^2+z^2}
+    /// square root of the sum of the squares, in [`crate::Q16_16`] the sum saturates past about 181
     #[inline]
     pub fn distance(&self) -> T
     where
@@ -291,77 +241,419 @@ where
     /// unit vec
     pub fn as_unit_vec(&self) -> Self
     where
-        T: core::ops::Mul<Output = T> + num_traits::Inv<Output = T> + Sqrt,
+        T: num_traits::Inv<Output = T> + Sqrt,
     {
         let magnitude = self.distance().inv();
-        Self {
-            0: self.0 * magnitude,
-            1: self.1 * magnitude,
-        }
+        Self(self.0.map(|a| a * magnitude))
+    }
+
+    /// self at t 0 and other at t 1
+    #[inline]
+    pub fn lerp(&self, other: Self, t: T) -> Self {
+        *self + (other - *self) * t
+    }
+
+    /// component-wise minimum
+    pub fn min(&self, other: Self) -> Self {
+        Self(core::array::from_fn(|i| {
+            if other.0[i] < self.0[i] {
+                other.0[i]
+            } else {
+                self.0[i]
+            }
+        }))
+    }
+
+    /// component-wise maximum
+    pub fn max(&self, other: Self) -> Self {
+        Self(core::array::from_fn(|i| {
+            if other.0[i] > self.0[i] {
+                other.0[i]
+            } else {
+                self.0[i]
+            }
+        }))
+    }
+
+    /// component-wise clamp into [min, max], e.g. a position into the machine travel
+    pub fn clamp(&self, min: Self, max: Self) -> Self {
+        self.max(min).min(max)
+    }
+}
+
+impl<T: Copy + Default, const N: usize> core::default::Default for VecN<T, N> {
+    fn default() -> Self {
+        Self([T::default(); N])
+    }
+}
+
+impl<T, const N: usize> core::ops::Index<usize> for VecN<T, N> {
+    type Output = T;
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        &self.0[index]
     }
 }
 
-impl<T> core::ops::Sub for Vec2<T>
+impl<T, const N: usize> core::ops::IndexMut<usize> for VecN<T, N> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut self.0[index]
+    }
+}
+
+impl<T, const N: usize> core::ops::Sub for VecN<T, N>
 where
     T: Copy + core::ops::Sub<Output = T>,
 {
     type Output = Self;
     #[inline]
     fn sub(self, rhs: Self::Output) -> Self {
-        Self {
-            0: self.0 - rhs.0,
-            1: self.1 - rhs.1,
-        }
+        Self(core::array::from_fn(|i| self.0[i] - rhs.0[i]))
     }
 }
 
-impl<T> core::ops::Add for Vec2<T>
+impl<T, const N: usize> core::ops::Add for VecN<T, N>
 where
     T: Copy + core::ops::Add<Output = T>,
 {
     type Output = Self;
     #[inline]
     fn add(self, rhs: Self::Output) -> Self {
-        Self {
-            0: self.0 + rhs.0,
-            1: self.1 + rhs.1,
-        }
+        Self(core::array::from_fn(|i| self.0[i] + rhs.0[i]))
+    }
+}
+
+impl<T, const N: usize> core::ops::Mul<T> for VecN<T, N>
+where
+    T: Copy + core::ops::Mul<Output = T>,
+{
+    type Output = Self;
+    #[inline]
+    fn mul(self, rhs: T) -> Self::Output {
+        Self(self.0.map(|a| a * rhs))
     }
 }
 
-impl<T> core::ops::Neg for Vec2<T>
+impl<T, const N: usize> core::ops::Mul<T> for &VecN<T, N>
 where
-    T: core::ops::Neg<Output = T>,
+    T: Copy + core::ops::Mul<Output = T>,
+{
+    type Output = VecN<T, N>;
+    #[inline]
+    fn mul(self, rhs: T) -> Self::Output {
+        *self * rhs
+    }
+}
+
+impl<T, const N: usize> core::ops::Div<T> for VecN<T, N>
+where
+    T: Copy + core::ops::Div<Output = T>,
+{
+    type Output = Self;
+    #[inline]
+    fn div(self, rhs: T) -> Self::Output {
+        Self(self.0.map(|a| a / rhs))
+    }
+}
+
+impl<T, const N: usize> core::ops::Div<T> for &VecN<T, N>
+where
+    T: Copy + core::ops::Div<Output = T>,
+{
+    type Output = VecN<T, N>;
+    #[inline]
+    fn div(self, rhs: T) -> Self::Output {
+        *self / rhs
+    }
+}
+
+impl<T, const N: usize> core::ops::Neg for VecN<T, N>
+where
+    T: Copy + core::ops::Neg<Output = T>,
 {
     type Output = Self;
     #[inline]
     fn neg(self) -> Self::Output {
-        Self {
-            0: -self.0,
-            1: -self.1,
-        }
+        Self(self.0.map(|a| -a))
     }
 }
 
-#[allow(dead_code)]
-pub struct Vec5<T>(pub T, pub T, pub T, pub T, pub T);
-impl<T> Vec5<T>
+impl<T, const N: usize> core::ops::AddAssign for VecN<T, N>
 where
-    T: Copy,
+    T: Copy + core::ops::Add<Output = T>,
 {
-    /// Create a new point from an x/y/z coordinate.
-    #[allow(dead_code)]
-    pub fn new(axis0: T, axis1: T, axis2: T, axis3: T, axis4: T) -> Self {
-        Self {
-            0: axis0,
-            1: axis1,
-            2: axis2,
-            3: axis3,
-            4: axis4,
-        }
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
     }
 }
 
+impl<T, const N: usize> core::ops::SubAssign for VecN<T, N>
+where
+    T: Copy + core::ops::Sub<Output = T>,
+{
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T, const N: usize> core::ops::MulAssign<T> for VecN<T, N>
+where
+    T: Copy + core::ops::Mul<Output = T>,
+{
+    #[inline]
+    fn mul_assign(&mut self, rhs: T) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T, const N: usize> core::ops::DivAssign<T> for VecN<T, N>
+where
+    T: Copy + core::ops::Div<Output = T>,
+{
+    #[inline]
+    fn div_assign(&mut self, rhs: T) {
+        *self = *self / rhs;
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for VecN<T, N> {
+    fn from(axes: [T; N]) -> Self {
+        Self(axes)
+    }
+}
+
+/// a vector with named fields as [`VecN`] of its dimension: the conversions both ways, and the math and
+/// ops of [`VecN`] through them
+macro_rules! named_axes {
+    ($vec:ident, $n:literal, $($i:tt),+) => {
+        impl<T: Copy> From<$vec<T>> for VecN<T, $n> {
+            #[inline]
+            fn from(v: $vec<T>) -> Self {
+                Self([$(v.$i),+])
+            }
+        }
+
+        impl<T: Copy> From<VecN<T, $n>> for $vec<T> {
+            #[inline]
+            fn from(v: VecN<T, $n>) -> Self {
+                $vec($(v.0[$i]),+)
+            }
+        }
+
+        impl<T> $vec<T>
+        where
+            T: Copy
+                + core::cmp::PartialOrd
+                + num_traits::Zero
+                + num_traits::One
+                + core::ops::Add<Output = T>
+                + core::ops::Sub<Output = T>
+                + core::ops::Mul<Output = T>
+                + core::ops::Neg<Output = T>
+                + core::ops::Div<Output = T>,
+        {
+            #[inline]
+            pub fn zero() -> Self {
+                VecN::zero().into()
+            }
+
+            #[inline]
+            pub fn dot(&self, other: Self) -> T {
+                VecN::from(*self).dot(other.into())
+            }
+
+            #[inline]
+            pub fn abs(&self) -> Self {
+                VecN::from(*self).abs().into()
+            }
+
+            /// see [`VecN::max_element`]
+            #[inline]
+            pub fn max_element(&self) -> T {
+                VecN::from(*self).max_element()
+            }
+
+            /// see [`VecN::max_element_index`]
+            #[inline]
+            pub fn max_element_index(&self) -> Self {
+                VecN::from(*self).max_element_index().into()
+            }
+
+            /// sum of the squares
+            #[inline]
+            pub fn distance_sqr(&self) -> T {
+                VecN::from(*self).distance_sqr()
+            }
+
+            /// square root of the sum of the squares
+            #[inline]
+            pub fn distance(&self) -> T
+            where
+                T: Sqrt,
+            {
+                VecN::from(*self).distance()
+            }
+
+            /// unit vec
+            #[inline]
+            pub fn as_unit_vec(&self) -> Self
+            where
+                T: num_traits::Inv<Output = T> + Sqrt,
+            {
+                VecN::from(*self).as_unit_vec().into()
+            }
+
+            /// self at t 0 and other at t 1
+            #[inline]
+            pub fn lerp(&self, other: Self, t: T) -> Self {
+                VecN::from(*self).lerp(other.into(), t).into()
+            }
+
+            /// component-wise minimum
+            #[inline]
+            pub fn min(&self, other: Self) -> Self {
+                VecN::from(*self).min(other.into()).into()
+            }
+
+            /// component-wise maximum
+            #[inline]
+            pub fn max(&self, other: Self) -> Self {
+                VecN::from(*self).max(other.into()).into()
+            }
+
+            /// component-wise clamp into [min, max], e.g. a position into the machine travel
+            #[inline]
+            pub fn clamp(&self, min: Self, max: Self) -> Self {
+                VecN::from(*self).clamp(min.into(), max.into()).into()
+            }
+        }
+
+        impl<T> core::ops::Sub for $vec<T>
+        where
+            T: Copy + core::ops::Sub<Output = T>,
+        {
+            type Output = Self;
+            #[inline]
+            fn sub(self, rhs: Self) -> Self {
+                (VecN::from(self) - VecN::from(rhs)).into()
+            }
+        }
+
+        impl<T> core::ops::Add for $vec<T>
+        where
+            T: Copy + core::ops::Add<Output = T>,
+        {
+            type Output = Self;
+            #[inline]
+            fn add(self, rhs: Self) -> Self {
+                (VecN::from(self) + VecN::from(rhs)).into()
+            }
+        }
+
+        impl<T> core::ops::Neg for $vec<T>
+        where
+            T: Copy + core::ops::Neg<Output = T>,
+        {
+            type Output = Self;
+            #[inline]
+            fn neg(self) -> Self {
+                (-VecN::from(self)).into()
+            }
+        }
+
+        impl<T> core::ops::Mul<T> for $vec<T>
+        where
+            T: Copy + core::ops::Mul<Output = T>,
+        {
+            type Output = Self;
+            #[inline]
+            fn mul(self, rhs: T) -> Self {
+                (VecN::from(self) * rhs).into()
+            }
+        }
+
+        impl<T> core::ops::Div<T> for $vec<T>
+        where
+            T: Copy + core::ops::Div<Output = T>,
+        {
+            type Output = Self;
+            #[inline]
+            fn div(self, rhs: T) -> Self {
+                (VecN::from(self) / rhs).into()
+            }
+        }
+
+        impl<T> core::ops::Mul<T> for &$vec<T>
+        where
+            T: Copy + core::ops::Mul<Output = T>,
+        {
+            type Output = $vec<T>;
+            #[inline]
+            fn mul(self, rhs: T) -> Self::Output {
+                *self * rhs
+            }
+        }
+
+        impl<T> core::ops::Div<T> for &$vec<T>
+        where
+            T: Copy + core::ops::Div<Output = T>,
+        {
+            type Output = $vec<T>;
+            #[inline]
+            fn div(self, rhs: T) -> Self::Output {
+                *self / rhs
+            }
+        }
+
+        impl<T> core::ops::AddAssign for $vec<T>
+        where
+            T: Copy + core::ops::Add<Output = T>,
+        {
+            #[inline]
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
+
+        impl<T> core::ops::SubAssign for $vec<T>
+        where
+            T: Copy + core::ops::Sub<Output = T>,
+        {
+            #[inline]
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
+
+        impl<T> core::ops::MulAssign<T> for $vec<T>
+        where
+            T: Copy + core::ops::Mul<Output = T>,
+        {
+            #[inline]
+            fn mul_assign(&mut self, rhs: T) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl<T> core::ops::DivAssign<T> for $vec<T>
+        where
+            T: Copy + core::ops::Div<Output = T>,
+        {
+            #[inline]
+            fn div_assign(&mut self, rhs: T) {
+                *self = *self / rhs;
+            }
+        }
+    };
+}
+
+named_axes!(Vec2, 2, 0, 1);
+named_axes!(Vec3, 3, 0, 1, 2);
+named_axes!(Vec5, 5, 0, 1, 2, 3, 4);
+
 #[allow(non_camel_case_types)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum CanonPlane {
@@ -400,3 +692,67 @@ impl CanonPlane {
         dest
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Vec2, Vec3, Vec5, VecN};
+
+    #[test]
+    fn test_vecn() {
+        // the same results with Vec3
+        let (a, b) = (Vec3::new(1_f32, -4., 2.), Vec3::new(-3_f32, 0.5, 2.));
+        let (na, nb) = (VecN::from(a), VecN::from(b));
+        assert_eq!(Vec3::from(na + nb), a + b);
+        assert_eq!(Vec3::from(na - nb), a - b);
+        assert_eq!(Vec3::from(-na), -a);
+        assert_eq!(Vec3::from(&na * 2.), &a * 2.);
+        assert_eq!(Vec3::from(&na / 2.), &a / 2.);
+        assert_eq!(na.dot(nb), a.dot(b));
+        assert_eq!(na.distance(), a.distance());
+        assert_eq!(Vec3::from(na.as_unit_vec()), a.as_unit_vec());
+        assert_eq!(Vec3::from(na.abs()), a.abs());
+        assert_eq!(na.max_element(), a.max_element());
+        assert_eq!(Vec3::from(na.max_element_index()), a.max_element_index());
+        assert_eq!(
+            Vec3::from(nb.abs().max_element_index()),
+            b.abs().max_element_index()
+        );
+        assert_eq!(Vec2::from(VecN::from(a.plane())), a.plane());
+
+        let mut p = Vec5::new(1_f32, 2., 3., 4., 5.);
+        p.4 = -90.;
+        assert_eq!(p.xyz(), Vec3::new(1., 2., 3.));
+        assert_eq!(p.abs().max_element(), 90.);
+        assert_eq!(p.max_element_index(), Vec5::new(0., 0., 0., 1., 0.));
+        assert_eq!(VecN::from(p)[4], p.4);
+        assert_eq!(Vec5::from(VecN::from(p) * 2.), p * 2.);
+        assert_eq!(VecN::<f32, 5>::default(), VecN::zero());
+    }
+
+    #[test]
+    fn test_vec_ops() {
+        let mut a = Vec3::new(1_f32, -4., 2.);
+        a += Vec3::new(1., 1., 1.);
+        a -= Vec3::new(0., 0., 3.);
+        a *= 2.;
+        a /= 4.;
+        assert_eq!(a, Vec3::new(1., -1.5, 0.));
+        assert_eq!(a * 2., &a * 2.);
+        assert_eq!(a.lerp(Vec3::new(3., 0.5, 4.), 0.5), Vec3::new(2., -0.5, 2.));
+        let (min, max) = (Vec3::new(0., 0., -1.), Vec3::new(0.5, 1., 1.));
+        assert_eq!(a.clamp(min, max), Vec3::new(0.5, 0., 0.));
+        assert_eq!(a.min(max), Vec3::new(0.5, -1.5, 0.));
+        assert_eq!(a.max(min), Vec3::new(1., 0., 0.));
+
+        let mut b = Vec2::new(3_f32, 4.);
+        b *= 0.5;
+        b += Vec2::zero();
+        assert_eq!(b / 0.5, Vec2::new(3., 4.));
+        assert_eq!(&b * 2., Vec2::new(3., 4.));
+        assert_eq!(b.lerp(Vec2::new(0., 0.), 1.), Vec2::zero());
+        assert_eq!(
+            b.clamp(Vec2::new(2., 0.), Vec2::new(4., 1.)),
+            Vec2::new(2., 1.)
+        );
+    }
+}